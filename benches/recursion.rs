@@ -0,0 +1,53 @@
+// Times recursive workloads on both backends: `cargo bench --bench recursion`.
+
+use genesix::runtime::evaluator::{parse_source, Backend, Evaluator};
+
+use std::time::{Duration, Instant};
+
+const RUNS: usize = 5;
+
+const FIB: &str = "
+init fib(n):
+    match n < 2:
+        true:
+            return n
+    return fib(n - 1) + fib(n - 2)
+fib(20)
+";
+
+// `fib` again, but every call also creates and drops a closure with a long
+// body that never runs, so the cost of defining and calling functions with
+// large bodies shows up.
+fn long_bodies() -> String {
+    let mut source = String::from("init fib(n):\n    (x) =>:\n");
+    for line in 0..50 {
+        source.push_str(&format!("        x = x + {} * [x, {{value: x}}].length\n", line));
+    }
+    source.push_str("        return x\n");
+    source.push_str("    match n < 2:\n        true:\n            return n\n");
+    source.push_str("    return fib(n - 1) + fib(n - 2)\nfib(20)\n");
+    source
+}
+
+// The fastest of a few runs, which is the least disturbed by other work.
+fn best_of(source: &str, backend: Backend) -> Duration {
+    (0..RUNS)
+        .map(|_| {
+            let program = parse_source(source);
+            let start = Instant::now();
+            Evaluator::with_backend(backend).evaluate(program).unwrap();
+            start.elapsed()
+        })
+        .min()
+        .unwrap_or_default()
+}
+
+fn main() {
+    let workloads = [("fib(20)", FIB.to_string()), ("fib(20), long bodies", long_bodies())];
+    for (name, source) in &workloads {
+        for backend in Backend::ALL {
+            let time = best_of(source, backend);
+            println!("{:<22} {:<12} {:>8.1} ms", name, format!("{:?}", backend), time.as_secs_f64() * 1000.0);
+        }
+    }
+}
@@ -1,4 +1,7 @@
 pub mod parser;
+pub mod repl;
+pub mod runtime;
+pub mod utils;
 
 pub fn add(left: u64, right: u64) -> u64 {
     left + right
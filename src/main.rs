@@ -1,35 +1,64 @@
-mod parser;
-mod runtime;
+use genesix::repl::Repl;
+use genesix::runtime::evaluator::{Backend, Evaluator};
+use genesix::utils::errors::{Control, EvalResult};
 
-use parser::lexer::Lexer;
-use parser::parser::Parser;
-
-use runtime::evaluator::Evaluator;
+use std::env;
+use std::io;
+use std::path::Path;
+use std::process;
 
 fn main() {
-    let source = r#"
-        init greet(name):
-            log(`Hello, ${name}`)
-
-        greet("Joe")
-        greet("Ada")
-    "#;
-
-    let mut lexer = Lexer::new(source);
-    let mut tokens = Vec::new();
-
-    loop {
-        let token = lexer.next_token();
-        tokens.push(token.clone());
-        if token.kind == parser::tokens::TokenKind::EOF {
-            break;
+    let mut args = env::args().skip(1).peekable();
+
+    // `--vm` compiles to bytecode instead of walking the syntax tree.
+    let backend = if args.next_if_eq("--vm").is_some() {
+        Backend::Bytecode
+    } else {
+        Backend::TreeWalker
+    };
+
+    let Some(path) = args.next() else {
+        process::exit(repl(backend));
+    };
+
+    let mut evaluator = Evaluator::with_backend(backend);
+    evaluator.set_args(args.collect());
+    let result = evaluator.run_file(Path::new(&path));
+    report(&mut evaluator, result);
+}
+
+// With no script to run, `genesix` starts an interactive session.
+fn repl(backend: Backend) -> i32 {
+    let mut repl = Repl::with_backend(backend);
+    if let Some(home) = env::var_os("HOME").or_else(|| env::var_os("USERPROFILE")) {
+        repl = repl.with_history_file(Path::new(&home).join(".genesix_history"));
+    }
+
+    match repl.run(io::stdin().lock(), &mut io::stdout()) {
+        Ok(code) => code,
+        Err(err) => {
+            eprintln!("genesix: {}", err);
+            1
         }
     }
+}
 
-    let mut parser = Parser::new(tokens);
-    let ast = parser.parse();
+fn report(evaluator: &mut Evaluator, result: EvalResult<()>) {
+    match result {
+        // The parser only allows `return` inside functions.
+        Ok(()) | Err(Control::Return(_)) => {}
+        Err(Control::Throw(error)) => {
+            eprintln!("Uncaught {}", evaluator.describe_error(error));
+            process::exit(1);
+        }
+        Err(Control::Exit(code)) => process::exit(code),
+    }
 
-    let mut evaluator = Evaluator::new();
-    evaluator.evaluate(ast);
+    let rejections = evaluator.take_unhandled_rejections();
+    for reason in &rejections {
+        eprintln!("Unhandled promise rejection: {}", evaluator.describe_error(reason.clone()));
+    }
+    if !rejections.is_empty() {
+        process::exit(1);
+    }
 }
-    
\ No newline at end of file
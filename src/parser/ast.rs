@@ -1,25 +1,273 @@
-use crate::parser::tokens::Token;
+use crate::parser::tokens::TokenKind;
+
+use std::rc::Rc;
 
 #[derive(Debug, Clone)]
 pub enum Stmt {
     Init {
-        name: String,
-        params: Vec<String>,
-        body: Vec<Stmt>,
+        name: Ident,
+        function: Rc<Function>,
     },
     Log {
         value: Expr,
     },
+    Assign {
+        name: Ident,
+        value: Expr,
+    },
+    // {name, age} = user or [first, ...rest] = list
+    Destructure {
+        pattern: Pattern,
+        value: Expr,
+    },
+    Get {
+        target: ImportTarget,
+        // A string literal for static imports, any expression for dynamic ones.
+        source: Expr,
+    },
+    Try {
+        body: Vec<Stmt>,
+        catches: Vec<CatchClause>,
+        finally: Option<Vec<Stmt>>,
+    },
+    Throw {
+        value: Expr,
+    },
+    Return {
+        value: Option<Expr>,
+    },
+    // enum Role { Admin = "admin", User, Guest }
+    Enum {
+        name: Ident,
+        members: Vec<(String, Option<Expr>)>,
+    },
+    Match {
+        subject: Expr,
+        cases: Vec<MatchCase>,
+        otherwise: Option<Vec<Stmt>>,
+    },
+    // for (item in items): or for ({name, age} in users):
+    For {
+        pattern: Pattern,
+        iterable: Expr,
+        body: Vec<Stmt>,
+    },
+    // Marks the names bound by the wrapped statement as part of the module's public surface.
+    Share(Box<Stmt>),
     ExprStmt(Expr),
 }
 
+#[derive(Debug, Clone)]
+pub enum ImportTarget {
+    // get utils from "./utils.gx"
+    Namespace(Ident),
+    // get {greet, VERSION} from "./utils.gx"
+    Names(Vec<Ident>),
+}
+
+#[derive(Debug, Clone)]
+pub struct CatchClause {
+    // catch (ValidationError err): only handles errors whose `type` matches.
+    pub error_type: Option<String>,
+    pub name: Option<Ident>,
+    pub body: Vec<Stmt>,
+}
+
+#[derive(Debug, Clone)]
+pub struct MatchCase {
+    // A case runs when the subject equals any of its patterns.
+    pub patterns: Vec<Expr>,
+    pub body: Vec<Stmt>,
+}
+
+// The left-hand side of a destructuring assignment, a parameter or a `for` head.
+#[derive(Debug, Clone)]
+pub enum Pattern {
+    Name(Ident),
+    // {name, age: years, role = "user", ...others}; each entry is the property
+    // read and what it is bound to.
+    Object {
+        properties: Vec<(String, Binding)>,
+        rest: Option<Ident>,
+    },
+    // [first, second = 0, ...rest]
+    Array {
+        items: Vec<Binding>,
+        rest: Option<Box<Pattern>>,
+    },
+}
+
+// The parameters and body of an `init` or arrow function. Closures share it
+// with the syntax tree, so defining or calling a function never copies its body.
+#[derive(Debug)]
+pub struct Function {
+    pub params: Params,
+    pub body: Vec<Stmt>,
+    // Set when the body awaits, which makes calls return a promise.
+    pub is_async: bool,
+    // How many variables a call needs room for; filled in by the resolver.
+    pub locals: usize,
+}
+
+// init f(a, {b} = {}, c = 2, ...rest)
+#[derive(Debug, Clone, Default)]
+pub struct Params {
+    pub positional: Vec<Binding>,
+    pub rest: Option<Ident>,
+}
+
+#[derive(Debug, Clone)]
+pub struct Binding {
+    pub target: Pattern,
+    // Used when the value is missing or `undefined`.
+    pub default: Option<Expr>,
+}
+
 #[derive(Debug, Clone)]
 pub enum Expr {
     Literal(String),
-    Variable(String),
+    Number(f64),
+    Boolean(bool),
+    Null,
+    Undefined,
+    Variable(Ident),
+    // `optional` marks a `?.` link: when the value before it is `null` or
+    // `undefined`, the rest of the chain is skipped and gives `undefined`.
     Call {
-        callee: String,
+        callee: Box<Expr>,
         arguments: Vec<Expr>,
+        optional: bool,
+    },
+    Member {
+        object: Box<Expr>,
+        property: String,
+        optional: bool,
+    },
+    Template(Vec<TemplatePart>),
+    // (x, y) =>: return x + y, or (x) => x * 2
+    Function(Rc<Function>),
+    Await(Box<Expr>),
+    Unary {
+        operator: TokenKind,
+        operand: Box<Expr>,
     },
-    Template(String),
+    Binary {
+        left: Box<Expr>,
+        operator: TokenKind,
+        right: Box<Expr>,
+    },
+    Index {
+        object: Box<Expr>,
+        index: Box<Expr>,
+        optional: bool,
+    },
+    // `??`, `&&` and `||`, which only evaluate `right` when `left` doesn't decide the result.
+    Logical {
+        left: Box<Expr>,
+        operator: TokenKind,
+        right: Box<Expr>,
+    },
+    // `...items` inside an array literal or call arguments.
+    Spread(Box<Expr>),
+    // save(name: "Joe"); only valid as a call argument.
+    Keyword {
+        name: String,
+        value: Box<Expr>,
+    },
+    // target = value, target += value, target ??= value, ...; the target is
+    // a variable, member or index.
+    Assign {
+        target: Box<Expr>,
+        operator: TokenKind,
+        value: Box<Expr>,
+    },
+    // ++target, target--
+    Update {
+        target: Box<Expr>,
+        operator: TokenKind,
+        prefix: bool,
+    },
+    Array(Vec<Expr>),
+    Object(Vec<(PropertyKey, Expr)>),
+}
+
+// A variable as written in the source. The parser leaves `slot` unresolved
+// and the resolver fills it in before the program runs.
+#[derive(Debug, Clone)]
+pub struct Ident {
+    pub name: String,
+    pub line: usize,
+    pub slot: Slot,
+}
+
+// Where a variable lives at runtime.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Slot {
+    Unresolved,
+    // `index` in the scope `depth` functions out from the one running; 0 is
+    // the current call, and the outermost is the script or module.
+    Local { depth: u32, index: u32 },
+    // Not declared anywhere in the program, so looked up among the builtins by name.
+    Global,
+}
+
+// `Hello ${name}!` is the text "Hello ", the expression `name` and the text "!".
+#[derive(Debug, Clone)]
+pub enum TemplatePart {
+    Text(String),
+    Expr(Expr),
+}
+
+#[derive(Debug, Clone)]
+pub enum PropertyKey {
+    // {name: value} or {"name": value}
+    Named(String),
+    // {[key]: value}, where the key may be a symbol
+    Computed(Expr),
+    // {...defaults, name: "x"}: the paired expression's properties are copied in.
+    Spread,
+}
+
+impl Stmt {
+    pub fn bound_names(&self) -> Vec<String> {
+        match self {
+            Stmt::Init { name, .. } | Stmt::Assign { name, .. } | Stmt::Enum { name, .. } => vec![name.name.clone()],
+            Stmt::Destructure { pattern, .. } => pattern.names(),
+            Stmt::Get { target: ImportTarget::Namespace(name), .. } => vec![name.name.clone()],
+            Stmt::Get { target: ImportTarget::Names(names), .. } => names.iter().map(|name| name.name.clone()).collect(),
+            Stmt::Share(inner) => inner.bound_names(),
+            Stmt::Log { .. }
+            | Stmt::Try { .. }
+            | Stmt::Throw { .. }
+            | Stmt::Return { .. }
+            | Stmt::Match { .. }
+            | Stmt::For { .. }
+            | Stmt::ExprStmt(_) => Vec::new(),
+        }
+    }
+}
+
+impl Pattern {
+    // Every variable the pattern binds, in source order.
+    pub fn names(&self) -> Vec<String> {
+        match self {
+            Pattern::Name(name) => vec![name.name.clone()],
+            Pattern::Object { properties, rest } => properties
+                .iter()
+                .flat_map(|(_, binding)| binding.target.names())
+                .chain(rest.iter().map(|rest| rest.name.clone()))
+                .collect(),
+            Pattern::Array { items, rest } => items
+                .iter()
+                .flat_map(|binding| binding.target.names())
+                .chain(rest.iter().flat_map(|rest| rest.names()))
+                .collect(),
+        }
+    }
+}
+
+impl Ident {
+    pub fn new(name: String, line: usize) -> Self {
+        Self { name, line, slot: Slot::Unresolved }
+    }
 }
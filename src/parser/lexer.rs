@@ -38,6 +38,10 @@ impl Lexer {
         keywords.insert("log".to_string(), TokenKind::Log);
         keywords.insert("get".to_string(), TokenKind::Get);
         keywords.insert("from".to_string(), TokenKind::From);
+        keywords.insert("share".to_string(), TokenKind::Share);
+        keywords.insert("await".to_string(), TokenKind::Await);
+        keywords.insert("enum".to_string(), TokenKind::Enum);
+        keywords.insert("match".to_string(), TokenKind::Match);
         keywords.insert("range".to_string(), TokenKind::Range);
         keywords.insert("secure".to_string(), TokenKind::Secure);
         keywords.insert("true".to_string(), TokenKind::BooleanLiteral(true));
@@ -54,6 +58,21 @@ impl Lexer {
         }
     }
 
+    pub fn tokenize(&mut self) -> Vec<Token> {
+        let mut tokens = Vec::new();
+
+        loop {
+            let token = self.next_token();
+            let done = token.kind == TokenKind::EOF;
+            tokens.push(token);
+            if done {
+                break;
+            }
+        }
+
+        tokens
+    }
+
     pub fn next_token(&mut self) -> Token {
         self.skip_whitespace_and_comments();
 
@@ -78,42 +97,147 @@ impl Lexer {
             ':' => self.make_token(TokenKind::Colon, line, column),
             ';' => self.make_token(TokenKind::Semicolon, line, column),
             ',' => self.make_token(TokenKind::Comma, line, column),
-            '.' => self.make_token(TokenKind::Dot, line, column),
-            '*' => self.make_token(TokenKind::Star, line, column),
-            '+' => self.make_token(TokenKind::Plus, line, column),
+            '.' => {
+                if self.peek() == '.' && self.peek_ahead(1) == '.' {
+                    self.advance();
+                    self.advance();
+                    self.make_token(TokenKind::Ellipsis, line, column)
+                } else {
+                    self.make_token(TokenKind::Dot, line, column)
+                }
+            }
+            '*' => {
+                if self.match_char('*') {
+                    if self.match_char('=') {
+                        self.make_token(TokenKind::StarStarEqual, line, column)
+                    } else {
+                        self.make_token(TokenKind::StarStar, line, column)
+                    }
+                } else if self.match_char('=') {
+                    self.make_token(TokenKind::StarEqual, line, column)
+                } else {
+                    self.make_token(TokenKind::Star, line, column)
+                }
+            }
+            '+' => {
+                if self.match_char('+') {
+                    self.make_token(TokenKind::PlusPlus, line, column)
+                } else if self.match_char('=') {
+                    self.make_token(TokenKind::PlusEqual, line, column)
+                } else {
+                    self.make_token(TokenKind::Plus, line, column)
+                }
+            }
             '-' => {
-                if self.match_char('>') {
-                    self.make_token(TokenKind::Arrow, line, column)
+                if self.match_char('-') {
+                    self.make_token(TokenKind::MinusMinus, line, column)
+                } else if self.match_char('=') {
+                    self.make_token(TokenKind::MinusEqual, line, column)
                 } else {
                     self.make_token(TokenKind::Minus, line, column)
                 }
             }
-            '/' => self.make_token(TokenKind::Slash, line, column),
-            '%' => self.make_token(TokenKind::Percent, line, column),
-            '?' => self.make_token(TokenKind::Question, line, column),
-            '=' => {
+            '/' => {
                 if self.match_char('=') {
+                    self.make_token(TokenKind::SlashEqual, line, column)
+                } else {
+                    self.make_token(TokenKind::Slash, line, column)
+                }
+            }
+            '%' => {
+                if self.match_char('=') {
+                    self.make_token(TokenKind::PercentEqual, line, column)
+                } else {
+                    self.make_token(TokenKind::Percent, line, column)
+                }
+            }
+            '?' => {
+                // `a ?.5 : b` is a conditional, not optional chaining.
+                if self.peek() == '.' && !self.peek_ahead(1).is_ascii_digit() {
+                    self.advance();
+                    self.make_token(TokenKind::QuestionDot, line, column)
+                } else if self.match_char('?') {
+                    if self.match_char('=') {
+                        self.make_token(TokenKind::QuestionQuestionEqual, line, column)
+                    } else {
+                        self.make_token(TokenKind::QuestionQuestion, line, column)
+                    }
+                } else {
+                    self.make_token(TokenKind::Question, line, column)
+                }
+            }
+            '=' => {
+                if self.match_char('>') {
+                    self.make_token(TokenKind::Arrow, line, column)
+                } else if self.match_char('=') {
+                    self.match_char('=');
                     self.make_token(TokenKind::DoubleEqual, line, column)
                 } else {
                     self.make_token(TokenKind::Equal, line, column)
                 }
             }
+            // `==` is already strict, so `===` and `!==` are spelled-out aliases.
             '!' => {
                 if self.match_char('=') {
+                    self.match_char('=');
                     self.make_token(TokenKind::NotEqual, line, column)
                 } else {
-                    self.make_token(TokenKind::NotEqual, line, column)
+                    self.make_token(TokenKind::Bang, line, column)
                 }
             }
-            '>' => {
+            '&' => {
+                if self.match_char('&') {
+                    self.make_token(TokenKind::AmpAmp, line, column)
+                } else if self.match_char('=') {
+                    self.make_token(TokenKind::AmpEqual, line, column)
+                } else {
+                    self.make_token(TokenKind::Amp, line, column)
+                }
+            }
+            '|' => {
+                if self.match_char('|') {
+                    self.make_token(TokenKind::PipePipe, line, column)
+                } else if self.match_char('=') {
+                    self.make_token(TokenKind::PipeEqual, line, column)
+                } else {
+                    self.make_token(TokenKind::Pipe, line, column)
+                }
+            }
+            '^' => {
                 if self.match_char('=') {
+                    self.make_token(TokenKind::CaretEqual, line, column)
+                } else {
+                    self.make_token(TokenKind::Caret, line, column)
+                }
+            }
+            '~' => self.make_token(TokenKind::Tilde, line, column),
+            '>' => {
+                if self.match_char('>') {
+                    if self.match_char('>') {
+                        if self.match_char('=') {
+                            self.make_token(TokenKind::GreaterGreaterGreaterEqual, line, column)
+                        } else {
+                            self.make_token(TokenKind::GreaterGreaterGreater, line, column)
+                        }
+                    } else if self.match_char('=') {
+                        self.make_token(TokenKind::GreaterGreaterEqual, line, column)
+                    } else {
+                        self.make_token(TokenKind::GreaterGreater, line, column)
+                    }
+                } else if self.match_char('=') {
                     self.make_token(TokenKind::GreaterEqual, line, column)
                 } else {
                     self.make_token(TokenKind::Greater, line, column)
                 }
             }
             '<' => {
-                if self.match_char('=') {
+                if self.match_char('<') {
+                    if self.match_char('=') {
+                        self.make_token(TokenKind::LessLessEqual, line, column)
+                    } else {
+                        self.make_token(TokenKind::LessLess, line, column)
+                    }
+                } else if self.match_char('=') {
                     self.make_token(TokenKind::LessEqual, line, column)
                 } else {
                     self.make_token(TokenKind::Less, line, column)
@@ -228,7 +352,7 @@ impl Lexer {
                 '<' if self.peek_ahead(1) == '*' => {
                     self.advance(); // <
                     self.advance(); // *
-                    while !self.is_at_end() && !(self.peek() == '*' && self.peek_ahead(1) == '>') {
+                    while !(self.is_at_end() || self.peek() == '*' && self.peek_ahead(1) == '>') {
                         self.advance();
                     }
                     if !self.is_at_end() {
@@ -264,7 +388,7 @@ impl Lexer {
         }
     
         if self.is_at_end() {
-            return self.make_token(TokenKind::EOF, line, column);
+            return self.make_token(TokenKind::UnterminatedTemplate, line, column);
         }
     
         self.advance(); // closing backtick
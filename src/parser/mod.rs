@@ -1,4 +1,6 @@
 pub mod lexer;
 pub mod tokens;
+#[allow(clippy::module_inception)]
 pub mod parser;
-pub mod ast;
\ No newline at end of file
+pub mod ast;
+pub mod resolver;
\ No newline at end of file
@@ -1,34 +1,137 @@
-use crate::parser::ast::{Stmt, Expr};
+use crate::parser::ast::{Stmt, Expr, ImportTarget, CatchClause, MatchCase, PropertyKey, Pattern, Binding, Params, Ident, TemplatePart, Function};
+use crate::parser::lexer::Lexer;
 use crate::parser::tokens::TokenKind;
 
 use crate::parser::tokens::Token;
 
+use std::fmt;
+use std::mem;
+use std::rc::Rc;
+
+// Why a program could not be parsed, phrased for whoever wrote it.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ParseError {
+    pub message: String,
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+pub type ParseResult<T> = Result<T, ParseError>;
+
+fn fail<T>(message: impl Into<String>) -> ParseResult<T> {
+    Err(ParseError { message: message.into() })
+}
+
 pub struct Parser {
     tokens: Vec<Token>,
     position: usize,
+    // One entry per enclosing `init`, recording whether its body awaits.
+    functions: Vec<bool>,
 }
 
 impl Parser {
     pub fn new(tokens: Vec<Token>) -> Self {
-        Self { tokens, position: 0 }
+        Self {
+            tokens,
+            position: 0,
+            functions: Vec::new(),
+        }
     }
 
-    pub fn parse(&mut self) -> Vec<Stmt> {
+    pub fn parse(&mut self) -> ParseResult<Vec<Stmt>> {
         let mut statements = Vec::new();
 
         while !self.is_at_end() {
-            if self.match_token(TokenKind::Init) {
-                statements.push(self.parse_init());
-            } else if self.match_token(TokenKind::Log) {
-                statements.push(self.parse_log());
-            } else if matches!(self.peek().kind, TokenKind::Identifier(_)) {
-                statements.push(self.parse_expression_stmt());
+            let column = self.peek().column;
+            if self.match_token(TokenKind::Share) {
+                statements.push(self.parse_share(column)?);
             } else {
-                panic!("Unexpected top-level token: {:?}", self.peek().kind);
+                statements.push(self.parse_statement()?);
             }
         }
 
-        statements
+        Ok(statements)
+    }
+
+    // Parses source that must consist of exactly one expression, such as a `${...}` template slot.
+    pub fn parse_lone_expression(&mut self) -> ParseResult<Expr> {
+        let expr = self.parse_expression()?;
+        if !self.is_at_end() {
+            return fail(format!("Unexpected token after expression: {:?}", self.peek().kind));
+        }
+        Ok(expr)
+    }
+
+    fn parse_statement(&mut self) -> ParseResult<Stmt> {
+        let column = self.peek().column;
+        self.parse_statement_at(column)
+    }
+
+    // `column` is where the statement starts, including any `share` prefix,
+    // so that block bodies are measured against it.
+    fn parse_statement_at(&mut self, column: usize) -> ParseResult<Stmt> {
+        if self.match_token(TokenKind::Init) {
+            self.parse_init(column)
+        } else if self.match_token(TokenKind::Log) {
+            self.parse_log()
+        } else if self.match_token(TokenKind::Get) {
+            self.parse_get()
+        } else if self.match_token(TokenKind::Try) {
+            self.parse_try(column)
+        } else if self.match_token(TokenKind::Throw) {
+            Ok(Stmt::Throw { value: self.parse_expression()? })
+        } else if self.match_token(TokenKind::Return) {
+            self.parse_return()
+        } else if self.match_token(TokenKind::Enum) {
+            self.parse_enum()
+        } else if self.match_token(TokenKind::Match) {
+            self.parse_match(column)
+        } else if self.match_token(TokenKind::For) {
+            self.parse_for(column)
+        } else if matches!(self.peek().kind, TokenKind::LeftBrace | TokenKind::LeftBracket)
+            && self.group_followed_by(self.position + 1, TokenKind::Equal)
+        {
+            self.parse_destructure()
+        } else if self.check(&TokenKind::Share) {
+            fail("`share` is only allowed at the top level of a module")
+        } else if matches!(self.peek().kind, TokenKind::Identifier(_)) && self.peek_next().kind == TokenKind::Equal {
+            self.parse_assign()
+        } else {
+            self.parse_expression_stmt()
+        }
+    }
+
+    // A block is either a single statement on the same line as the `:` or an
+    // indented run of statements that all start in the same column.
+    fn parse_block(&mut self, header_column: usize) -> ParseResult<Vec<Stmt>> {
+        let colon_line = self.previous().line;
+
+        if self.is_at_end() {
+            return fail("Expected block after ':'");
+        }
+
+        if self.peek().line == colon_line {
+            return Ok(vec![self.parse_statement()?]);
+        }
+
+        let column = self.peek().column;
+        if column <= header_column {
+            return fail(format!("Expected an indented block on line {}", self.peek().line));
+        }
+
+        let mut body = Vec::new();
+        while !self.is_at_end() && self.peek().column >= column {
+            if self.peek().column > column {
+                return fail(format!("Unexpected indent on line {}", self.peek().line));
+            }
+            body.push(self.parse_statement()?);
+        }
+
+        Ok(body)
     }
 
     fn advance(&mut self) -> &Token {
@@ -41,6 +144,15 @@ impl Parser {
         &self.tokens[self.position]
     }
 
+    fn peek_next(&self) -> &Token {
+        let index = (self.position + 1).min(self.tokens.len() - 1);
+        &self.tokens[index]
+    }
+
+    fn previous(&self) -> &Token {
+        &self.tokens[self.position - 1]
+    }
+
     fn is_at_end(&self) -> bool {
         self.peek().kind == TokenKind::EOF
     }
@@ -58,101 +170,826 @@ impl Parser {
         &self.peek().kind == expected
     }
 
-    fn parse_init(&mut self) -> Stmt {
-        let name = if let TokenKind::Identifier(name) = &self.advance().kind {
-            name.clone()
+    fn parse_init(&mut self, column: usize) -> ParseResult<Stmt> {
+        let name = self.expect_ident("Expected function name after `init`")?;
+
+        self.expect(TokenKind::LeftParen, "Expected '(' after function name")?;
+        let params = self.parse_params()?;
+        self.expect(TokenKind::Colon, "Expected ':' after function signature")?;
+
+        self.functions.push(false);
+        let body = self.parse_block(column)?;
+        let is_async = self.functions.pop().unwrap_or(false);
+
+        Ok(Stmt::Init {
+            name,
+            function: Rc::new(Function { params, body, is_async, locals: 0 }),
+        })
+    }
+
+    // Parameters up to and including the closing ')'.
+    fn parse_params(&mut self) -> ParseResult<Params> {
+        let mut params = Params::default();
+        let mut names = Vec::new();
+        while !self.check(&TokenKind::RightParen) {
+            if self.match_token(TokenKind::Ellipsis) {
+                let rest = self.expect_ident("Expected a name after '...'")?;
+                names.push(rest.name.clone());
+                params.rest = Some(rest);
+                break;
+            }
+
+            let target = self.parse_pattern()?;
+            names.extend(target.names());
+            params.positional.push(self.parse_binding_default(target)?);
+
+            if !self.match_token(TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenKind::RightParen, "Expected ')' after parameters (a rest parameter must come last)")?;
+
+        for (index, name) in names.iter().enumerate() {
+            if names[..index].contains(name) {
+                return fail(format!("Duplicate parameter '{}' on line {}", name, self.previous().line));
+            }
+        }
+
+        Ok(params)
+    }
+
+    fn parse_return(&mut self) -> ParseResult<Stmt> {
+        if self.functions.is_empty() {
+            return fail(format!("`return` outside of a function on line {}", self.previous().line));
+        }
+
+        // A value only counts if it starts on the same line as `return`.
+        let value = if !self.is_at_end() && self.peek().line == self.previous().line {
+            Some(self.parse_expression()?)
         } else {
-            panic!("Expected function name after `init`");
+            None
         };
 
-        self.expect(TokenKind::LeftParen, "Expected '(' after function name");
+        Ok(Stmt::Return { value })
+    }
 
-        let mut params = Vec::new();
-        while !self.check(&TokenKind::RightParen) {
-            if let TokenKind::Identifier(param) = &self.advance().kind {
-                params.push(param.clone());
+    fn parse_log(&mut self) -> ParseResult<Stmt> {
+        self.expect(TokenKind::LeftParen, "Expected '(' after `log`")?;
+
+        let expr = self.parse_expression()?;
+
+        self.expect(TokenKind::RightParen, "Expected ')' after expression")?;
+
+        Ok(Stmt::Log { value: expr })
+    }
+
+    fn parse_assign(&mut self) -> ParseResult<Stmt> {
+        let name = self.expect_ident("Expected variable name")?;
+        self.expect(TokenKind::Equal, "Expected '=' after variable name")?;
+        let value = self.parse_expression()?;
+
+        Ok(Stmt::Assign { name, value })
+    }
+
+    fn parse_destructure(&mut self) -> ParseResult<Stmt> {
+        let pattern = self.parse_pattern()?;
+        self.expect(TokenKind::Equal, "Expected '=' after destructuring pattern")?;
+        let value = self.parse_expression()?;
+
+        Ok(Stmt::Destructure { pattern, value })
+    }
+
+    fn parse_for(&mut self, column: usize) -> ParseResult<Stmt> {
+        self.expect(TokenKind::LeftParen, "Expected '(' after `for`")?;
+        let pattern = self.parse_pattern()?;
+        self.expect(TokenKind::In, "Expected `in` after loop variable")?;
+        let iterable = self.parse_expression()?;
+        self.expect(TokenKind::RightParen, "Expected ')' after loop head")?;
+        self.expect(TokenKind::Colon, "Expected ':' after loop head")?;
+
+        let body = self.parse_block(column)?;
+
+        Ok(Stmt::For { pattern, iterable, body })
+    }
+
+    fn parse_pattern(&mut self) -> ParseResult<Pattern> {
+        if self.match_token(TokenKind::LeftBrace) {
+            self.parse_object_pattern()
+        } else if self.match_token(TokenKind::LeftBracket) {
+            self.parse_array_pattern()
+        } else {
+            Ok(Pattern::Name(self.expect_ident("Expected a name or destructuring pattern")?))
+        }
+    }
+
+    // {name, age: years, role = "user", ...others}
+    fn parse_object_pattern(&mut self) -> ParseResult<Pattern> {
+        let mut properties = Vec::new();
+        let mut rest = None;
+        while !self.check(&TokenKind::RightBrace) {
+            if self.match_token(TokenKind::Ellipsis) {
+                rest = Some(self.expect_ident("Expected a name after '...'")?);
+                break;
+            }
+
+            let line = self.peek().line;
+            let key = if matches!(self.peek().kind, TokenKind::Identifier(_)) {
+                self.expect_identifier("Expected property name in pattern")?
             } else {
-                panic!("Expected parameter name");
+                let key = self.expect_property_name("Expected property name in pattern")?;
+                if !self.check(&TokenKind::Colon) {
+                    return fail(format!("`{}` cannot be a variable name; rename it with `{}: name`", key, key));
+                }
+                key
+            };
+            let target = if self.match_token(TokenKind::Colon) {
+                self.parse_pattern()?
+            } else {
+                Pattern::Name(Ident::new(key.clone(), line))
+            };
+            properties.push((key, self.parse_binding_default(target)?));
+
+            if !self.match_token(TokenKind::Comma) {
+                break;
             }
+        }
+        self.expect(TokenKind::RightBrace, "Expected '}' after object pattern (a rest element must come last)")?;
 
-            if self.check(&TokenKind::Comma) {
-                self.advance();
+        Ok(Pattern::Object { properties, rest })
+    }
+
+    // [first, second = 0, ...rest]
+    fn parse_array_pattern(&mut self) -> ParseResult<Pattern> {
+        let mut items = Vec::new();
+        let mut rest = None;
+        while !self.check(&TokenKind::RightBracket) {
+            if self.match_token(TokenKind::Ellipsis) {
+                rest = Some(Box::new(self.parse_pattern()?));
+                break;
+            }
+
+            let target = self.parse_pattern()?;
+            items.push(self.parse_binding_default(target)?);
+
+            if !self.match_token(TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenKind::RightBracket, "Expected ']' after array pattern (a rest element must come last)")?;
+
+        Ok(Pattern::Array { items, rest })
+    }
+
+    fn parse_binding_default(&mut self, target: Pattern) -> ParseResult<Binding> {
+        let default = if self.match_token(TokenKind::Equal) {
+            Some(self.parse_expression()?)
+        } else {
+            None
+        };
+        Ok(Binding { target, default })
+    }
+
+    fn parse_get(&mut self) -> ParseResult<Stmt> {
+        let target = if self.match_token(TokenKind::LeftBrace) {
+            let mut names = Vec::new();
+            while !self.check(&TokenKind::RightBrace) {
+                names.push(self.expect_ident("Expected imported name")?);
+                if !self.match_token(TokenKind::Comma) {
+                    break;
+                }
+            }
+            self.expect(TokenKind::RightBrace, "Expected '}' after imported names")?;
+            ImportTarget::Names(names)
+        } else {
+            ImportTarget::Namespace(self.expect_ident("Expected module name after `get`")?)
+        };
+
+        self.expect(TokenKind::From, "Expected `from` after import target")?;
+
+        let source = self.parse_expression()?;
+
+        Ok(Stmt::Get { target, source })
+    }
+
+    fn parse_try(&mut self, column: usize) -> ParseResult<Stmt> {
+        self.expect(TokenKind::Colon, "Expected ':' after `try`")?;
+        let body = self.parse_block(column)?;
+
+        let mut catches = Vec::new();
+        while self.check_clause(TokenKind::Catch, column) {
+            self.advance();
+            let mut names = Vec::new();
+            if self.match_token(TokenKind::LeftParen) {
+                while !self.check(&TokenKind::RightParen) {
+                    names.push(self.expect_ident("Expected error name in `catch`")?);
+                }
+                self.expect(TokenKind::RightParen, "Expected ')' after `catch` binding")?;
+            }
+            self.expect(TokenKind::Colon, "Expected ':' after `catch`")?;
+
+            let (error_type, name) = match names.len() {
+                0 => (None, None),
+                1 => (None, names.pop()),
+                2 => {
+                    let name = names.pop();
+                    (names.pop().map(|error_type| error_type.name), name)
+                }
+                _ => return fail("Expected `catch (err)` or `catch (ErrorType err)`"),
+            };
+
+            catches.push(CatchClause {
+                error_type,
+                name,
+                body: self.parse_block(column)?,
+            });
+        }
+
+        let finally = if self.check_clause(TokenKind::Finally, column) {
+            self.advance();
+            self.expect(TokenKind::Colon, "Expected ':' after `finally`")?;
+            Some(self.parse_block(column)?)
+        } else {
+            None
+        };
+
+        if catches.is_empty() && finally.is_none() {
+            return fail("Expected `catch` or `finally` after `try` block");
+        }
+
+        Ok(Stmt::Try { body, catches, finally })
+    }
+
+    fn parse_enum(&mut self) -> ParseResult<Stmt> {
+        let name = self.expect_ident("Expected enum name after `enum`")?;
+        self.expect(TokenKind::LeftBrace, "Expected '{' after enum name")?;
+
+        let mut members: Vec<(String, Option<Expr>)> = Vec::new();
+        while !self.check(&TokenKind::RightBrace) {
+            let member = self.expect_identifier("Expected enum member name")?;
+            if members.iter().any(|(existing, _)| *existing == member) {
+                return fail(format!("Duplicate member `{}` in enum {}", member, name.name));
+            }
+            let value = if self.match_token(TokenKind::Equal) {
+                Some(self.parse_expression()?)
             } else {
+                None
+            };
+            members.push((member, value));
+
+            if !self.match_token(TokenKind::Comma) {
                 break;
             }
         }
+        self.expect(TokenKind::RightBrace, "Expected '}' after enum members")?;
 
-        self.expect(TokenKind::RightParen, "Expected ')' after parameters");
-        self.expect(TokenKind::Colon, "Expected ':' after function signature");
+        if members.is_empty() {
+            return fail(format!("enum {} must have at least one member", name.name));
+        }
 
-        let mut body = Vec::new();
-        if self.match_token(TokenKind::Log) {
-            body.push(self.parse_log());
+        Ok(Stmt::Enum { name, members })
+    }
+
+    // match value:
+    //     Role.Admin, Role.User:
+    //         ...
+    //     else:
+    //         ...
+    fn parse_match(&mut self, column: usize) -> ParseResult<Stmt> {
+        let subject = self.parse_expression()?;
+        self.expect(TokenKind::Colon, "Expected ':' after `match` subject")?;
+
+        let header_line = self.previous().line;
+        if self.is_at_end() || self.peek().line == header_line || self.peek().column <= column {
+            return fail(format!("Expected indented `match` cases after line {}", header_line));
         }
 
-        Stmt::Init {
-            name,
-            params,
-            body,
+        let case_column = self.peek().column;
+        let mut cases = Vec::new();
+        let mut otherwise = None;
+
+        while !self.is_at_end() && self.peek().column >= case_column {
+            if self.peek().column > case_column {
+                return fail(format!("Unexpected indent on line {}", self.peek().line));
+            }
+            if otherwise.is_some() {
+                return fail(format!("`else` must be the last case of a `match` (line {})", self.peek().line));
+            }
+
+            if self.match_token(TokenKind::Else) {
+                self.expect(TokenKind::Colon, "Expected ':' after `else`")?;
+                otherwise = Some(self.parse_block(case_column)?);
+                continue;
+            }
+
+            let mut patterns = vec![self.parse_expression()?];
+            while self.match_token(TokenKind::Comma) {
+                patterns.push(self.parse_expression()?);
+            }
+            self.expect(TokenKind::Colon, "Expected ':' after `match` case")?;
+            cases.push(MatchCase {
+                patterns,
+                body: self.parse_block(case_column)?,
+            });
         }
+
+        Ok(Stmt::Match { subject, cases, otherwise })
     }
 
-    fn parse_log(&mut self) -> Stmt {
-        self.expect(TokenKind::LeftParen, "Expected '(' after `log`");
+    // Continuation clauses (`catch`, `finally`) line up with the statement they belong to.
+    fn check_clause(&self, kind: TokenKind, column: usize) -> bool {
+        self.check(&kind) && self.peek().column == column
+    }
 
-        let expr = self.parse_expression();
+    fn parse_share(&mut self, column: usize) -> ParseResult<Stmt> {
+        let stmt = self.parse_statement_at(column)?;
+        match stmt {
+            Stmt::Init { .. } | Stmt::Assign { .. } | Stmt::Get { .. } | Stmt::Enum { .. } => Ok(Stmt::Share(Box::new(stmt))),
+            _ => fail("`share` must be followed by `init`, `enum`, an assignment or `get`"),
+        }
+    }
 
-        self.expect(TokenKind::RightParen, "Expected ')' after expression");
+    fn parse_expression_stmt(&mut self) -> ParseResult<Stmt> {
+        let expr = self.parse_expression()?;
+        Ok(Stmt::ExprStmt(expr))
+    }
 
-        Stmt::Log { value: expr }
+    fn parse_expression(&mut self) -> ParseResult<Expr> {
+        self.parse_assignment()
     }
 
-    fn parse_expression_stmt(&mut self) -> Stmt {
-        let expr = self.parse_expression();
-        Stmt::ExprStmt(expr)
+    // Assignment groups to the right, so `a = b = 1` sets both.
+    fn parse_assignment(&mut self) -> ParseResult<Expr> {
+        let target = self.parse_nullish()?;
+
+        let operators = [
+            TokenKind::Equal,
+            TokenKind::PlusEqual,
+            TokenKind::MinusEqual,
+            TokenKind::StarEqual,
+            TokenKind::SlashEqual,
+            TokenKind::PercentEqual,
+            TokenKind::StarStarEqual,
+            TokenKind::QuestionQuestionEqual,
+            TokenKind::AmpEqual,
+            TokenKind::PipeEqual,
+            TokenKind::CaretEqual,
+            TokenKind::LessLessEqual,
+            TokenKind::GreaterGreaterEqual,
+            TokenKind::GreaterGreaterGreaterEqual,
+        ];
+        if !operators.contains(&self.peek().kind) {
+            return Ok(target);
+        }
+
+        let operator = self.advance().kind.clone();
+        self.check_assignment_target(&target)?;
+        Ok(Expr::Assign {
+            target: Box::new(target),
+            operator,
+            value: Box::new(self.parse_assignment()?),
+        })
     }
 
-    fn parse_expression(&mut self) -> Expr {
-        let token = self.advance();
-        match &token.kind {
-            TokenKind::Identifier(name) => {
-                let name = name.clone();
-
-                if self.check(&TokenKind::LeftParen) {
-                    self.advance();
-                    let mut args = Vec::new();
-
-                    while !self.check(&TokenKind::RightParen) {
-                        args.push(self.parse_expression());
-                        if self.check(&TokenKind::Comma) {
-                            self.advance();
-                        } else {
-                            break;
-                        }
-                    }
+    fn check_assignment_target(&self, target: &Expr) -> ParseResult<()> {
+        match target {
+            Expr::Variable(_)
+            | Expr::Member { optional: false, .. }
+            | Expr::Index { optional: false, .. } => Ok(()),
+            Expr::Member { .. } | Expr::Index { .. } => {
+                fail(format!("Cannot assign to an optional chain on line {}", self.previous().line))
+            }
+            _ => fail(format!("Invalid assignment target on line {}", self.previous().line)),
+        }
+    }
 
-                    self.expect(TokenKind::RightParen, "Expected ')' after arguments");
+    fn parse_nullish(&mut self) -> ParseResult<Expr> {
+        self.parse_logical(TokenKind::QuestionQuestion, Self::parse_or)
+    }
+
+    fn parse_or(&mut self) -> ParseResult<Expr> {
+        self.parse_logical(TokenKind::PipePipe, Self::parse_and)
+    }
+
+    fn parse_and(&mut self) -> ParseResult<Expr> {
+        self.parse_logical(TokenKind::AmpAmp, Self::parse_bit_or)
+    }
 
-                    Expr::Call {
-                        callee: name,
-                        arguments: args,
+    fn parse_logical(&mut self, operator: TokenKind, operand: fn(&mut Self) -> ParseResult<Expr>) -> ParseResult<Expr> {
+        let mut expr = operand(self)?;
+
+        while self.match_token(operator.clone()) {
+            let right = operand(self)?;
+            expr = Expr::Logical {
+                left: Box::new(expr),
+                operator: operator.clone(),
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    // Bitwise operators bind looser than equality, as in C and JavaScript.
+    fn parse_bit_or(&mut self) -> ParseResult<Expr> {
+        self.parse_binary(&[TokenKind::Pipe], Self::parse_bit_xor)
+    }
+
+    fn parse_bit_xor(&mut self) -> ParseResult<Expr> {
+        self.parse_binary(&[TokenKind::Caret], Self::parse_bit_and)
+    }
+
+    fn parse_bit_and(&mut self) -> ParseResult<Expr> {
+        self.parse_binary(&[TokenKind::Amp], Self::parse_equality)
+    }
+
+    fn parse_equality(&mut self) -> ParseResult<Expr> {
+        self.parse_binary(&[TokenKind::DoubleEqual, TokenKind::NotEqual], Self::parse_comparison)
+    }
+
+    fn parse_comparison(&mut self) -> ParseResult<Expr> {
+        let operators = [TokenKind::Greater, TokenKind::GreaterEqual, TokenKind::Less, TokenKind::LessEqual];
+        self.parse_binary(&operators, Self::parse_shift)
+    }
+
+    fn parse_shift(&mut self) -> ParseResult<Expr> {
+        let operators = [TokenKind::LessLess, TokenKind::GreaterGreater, TokenKind::GreaterGreaterGreater];
+        self.parse_binary(&operators, Self::parse_term)
+    }
+
+    fn parse_term(&mut self) -> ParseResult<Expr> {
+        self.parse_binary(&[TokenKind::Plus, TokenKind::Minus], Self::parse_factor)
+    }
+
+    fn parse_factor(&mut self) -> ParseResult<Expr> {
+        self.parse_binary(&[TokenKind::Star, TokenKind::Slash, TokenKind::Percent], Self::parse_unary)
+    }
+
+    // One left-associative precedence level.
+    fn parse_binary(&mut self, operators: &[TokenKind], operand: fn(&mut Self) -> ParseResult<Expr>) -> ParseResult<Expr> {
+        let mut expr = operand(self)?;
+
+        while operators.contains(&self.peek().kind) {
+            let operator = self.advance().kind.clone();
+            let right = operand(self)?;
+            expr = Expr::Binary {
+                left: Box::new(expr),
+                operator,
+                right: Box::new(right),
+            };
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_unary(&mut self) -> ParseResult<Expr> {
+        if self.match_token(TokenKind::Await) {
+            if let Some(awaits) = self.functions.last_mut() {
+                *awaits = true;
+            }
+            return Ok(Expr::Await(Box::new(self.parse_unary()?)));
+        }
+
+        if matches!(self.peek().kind, TokenKind::PlusPlus | TokenKind::MinusMinus) {
+            let operator = self.advance().kind.clone();
+            let target = self.parse_unary()?;
+            self.check_assignment_target(&target)?;
+            return Ok(Expr::Update {
+                target: Box::new(target),
+                operator,
+                prefix: true,
+            });
+        }
+
+        if matches!(self.peek().kind, TokenKind::Minus | TokenKind::Bang | TokenKind::Tilde) {
+            let operator = self.advance().kind.clone();
+            return Ok(Expr::Unary {
+                operator,
+                operand: Box::new(self.parse_unary()?),
+            });
+        }
+
+        self.parse_power()
+    }
+
+    // `**` binds tighter than a leading minus and groups to the right, so
+    // `-2 ** 2` is -4 and `2 ** 3 ** 2` is 2 ** 9.
+    fn parse_power(&mut self) -> ParseResult<Expr> {
+        let mut base = self.parse_call()?;
+
+        // Like calls, a postfix `++` or `--` has to be on the same line.
+        if matches!(self.peek().kind, TokenKind::PlusPlus | TokenKind::MinusMinus)
+            && self.peek().line == self.previous().line
+        {
+            let operator = self.advance().kind.clone();
+            self.check_assignment_target(&base)?;
+            base = Expr::Update {
+                target: Box::new(base),
+                operator,
+                prefix: false,
+            };
+        }
+
+        if self.match_token(TokenKind::StarStar) {
+            return Ok(Expr::Binary {
+                left: Box::new(base),
+                operator: TokenKind::StarStar,
+                right: Box::new(self.parse_unary()?),
+            });
+        }
+        Ok(base)
+    }
+
+    fn parse_call(&mut self) -> ParseResult<Expr> {
+        let mut expr = self.parse_primary()?;
+
+        loop {
+            // `?.` may be followed by a name, `[index]` or `(arguments)`.
+            let optional = self.match_token(TokenKind::QuestionDot);
+            // A '(' on a new line starts a new statement rather than calling the previous one.
+            let same_line = optional || self.peek().line == self.previous().line;
+
+            if same_line && self.match_token(TokenKind::LeftParen) {
+                let mut args = Vec::new();
+
+                while !self.check(&TokenKind::RightParen) {
+                    args.push(self.parse_argument(&args)?);
+                    if self.check(&TokenKind::Comma) {
+                        self.advance();
+                    } else {
+                        break;
                     }
+                }
+
+                self.expect(TokenKind::RightParen, "Expected ')' after arguments")?;
+
+                expr = Expr::Call {
+                    callee: Box::new(expr),
+                    arguments: args,
+                    optional,
+                };
+            } else if same_line && self.match_token(TokenKind::LeftBracket) {
+                // Like calls, `[` only indexes when it is on the same line.
+                let index = self.parse_expression()?;
+                self.expect(TokenKind::RightBracket, "Expected ']' after index")?;
+                expr = Expr::Index {
+                    object: Box::new(expr),
+                    index: Box::new(index),
+                    optional,
+                };
+            } else if optional || self.match_token(TokenKind::Dot) {
+                let property = self.expect_property_name("Expected property name after '.'")?;
+                expr = Expr::Member {
+                    object: Box::new(expr),
+                    property,
+                    optional,
+                };
+            } else {
+                break;
+            }
+        }
+
+        Ok(expr)
+    }
+
+    fn parse_primary(&mut self) -> ParseResult<Expr> {
+        let token = self.advance();
+        match &token.kind {
+            TokenKind::Identifier(name) => Ok(Expr::Variable(Ident::new(name.clone(), token.line))),
+            TokenKind::StringLiteral(value) => Ok(Expr::Literal(value.clone())),
+            TokenKind::NumberLiteral(value) => match value.parse() {
+                Ok(number) => Ok(Expr::Number(number)),
+                Err(_) => fail(format!("Invalid number literal: {}", value)),
+            },
+            TokenKind::BooleanLiteral(value) => Ok(Expr::Boolean(*value)),
+            TokenKind::Null => Ok(Expr::Null),
+            TokenKind::Undefined => Ok(Expr::Undefined),
+            TokenKind::TemplateString(value) => {
+                let (template, line) = (value.clone(), token.line);
+                Ok(Expr::Template(self.parse_template(&template, line)?))
+            }
+            TokenKind::UnterminatedTemplate => fail(format!("Unterminated template string on line {}", token.line)),
+            TokenKind::LeftParen => {
+                if self.is_arrow_ahead() {
+                    self.parse_arrow()
                 } else {
-                    Expr::Variable(name)
+                    let expr = self.parse_expression()?;
+                    self.expect(TokenKind::RightParen, "Expected ')' after expression")?;
+                    Ok(expr)
+                }
+            }
+            TokenKind::LeftBracket => self.parse_array(),
+            TokenKind::LeftBrace => self.parse_object(),
+            _ => fail(format!("Unexpected token in expression: {:?}", token.kind)),
+        }
+    }
+
+    // Called just after a '('; true when its matching ')' is followed by `=>`.
+    fn is_arrow_ahead(&self) -> bool {
+        self.group_followed_by(self.position, TokenKind::Arrow)
+    }
+
+    // Whether the bracket group opened just before `start` is followed by
+    // `next` once it closes.
+    fn group_followed_by(&self, start: usize, next: TokenKind) -> bool {
+        let mut depth = 1;
+        for (offset, token) in self.tokens[start..].iter().enumerate() {
+            match token.kind {
+                TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::LeftBrace => depth += 1,
+                TokenKind::RightParen | TokenKind::RightBracket | TokenKind::RightBrace => depth -= 1,
+                TokenKind::EOF => return false,
+                _ => {}
+            }
+            if depth == 0 {
+                return self.tokens.get(start + offset + 1).is_some_and(|token| token.kind == next);
+            }
+        }
+        false
+    }
+
+    // The body is either a block after `=>:` or a single expression after `=>`.
+    fn parse_arrow(&mut self) -> ParseResult<Expr> {
+        let params = self.parse_params()?;
+        self.expect(TokenKind::Arrow, "Expected '=>' after arrow function parameters")?;
+
+        self.functions.push(false);
+        let body = if self.match_token(TokenKind::Colon) {
+            // An indented body is measured against the line the arrow starts on.
+            let header_column = self.line_start_column();
+            self.parse_block(header_column)?
+        } else {
+            vec![Stmt::Return { value: Some(self.parse_expression()?) }]
+        };
+        let is_async = self.functions.pop().unwrap_or(false);
+
+        Ok(Expr::Function(Rc::new(Function { params, body, is_async, locals: 0 })))
+    }
+
+    // Splits a template string into text and `${...}` slots, parsing each slot
+    // as an expression. `line` is where the template starts.
+    fn parse_template(&mut self, template: &str, line: usize) -> ParseResult<Vec<TemplatePart>> {
+        let mut parts = Vec::new();
+        let mut text = String::new();
+        let mut slot_line = line;
+        let mut chars = template.chars().peekable();
+
+        while let Some(c) = chars.next() {
+            if c != '$' || chars.peek() != Some(&'{') {
+                if c == '\n' {
+                    slot_line += 1;
+                }
+                text.push(c);
+                continue;
+            }
+            chars.next(); // consume {
+
+            let mut source = String::new();
+            for next in chars.by_ref() {
+                if next == '}' {
+                    break;
+                }
+                source.push(next);
+            }
+
+            if !text.is_empty() {
+                parts.push(TemplatePart::Text(mem::take(&mut text)));
+            }
+
+            let mut tokens = Lexer::new(&source).tokenize();
+            for token in &mut tokens {
+                token.line += slot_line - 1;
+            }
+            // A slot that awaits makes the function around the template async.
+            let mut parser = Parser::new(tokens);
+            parser.functions.push(false);
+            let expr = parser.parse_lone_expression()?;
+            if parser.functions.pop() == Some(true)
+                && let Some(awaits) = self.functions.last_mut()
+            {
+                *awaits = true;
+            }
+            parts.push(TemplatePart::Expr(expr));
+        }
+
+        if !text.is_empty() {
+            parts.push(TemplatePart::Text(text));
+        }
+        Ok(parts)
+    }
+
+    // Column of the first token on the line of the previous token.
+    fn line_start_column(&self) -> usize {
+        let line = self.previous().line;
+        self.tokens[..self.position]
+            .iter()
+            .rev()
+            .take_while(|token| token.line == line)
+            .last()
+            .map_or(1, |token| token.column)
+    }
+
+    fn parse_array(&mut self) -> ParseResult<Expr> {
+        let mut items = Vec::new();
+        while !self.check(&TokenKind::RightBracket) {
+            items.push(self.parse_element()?);
+            if !self.match_token(TokenKind::Comma) {
+                break;
+            }
+        }
+        self.expect(TokenKind::RightBracket, "Expected ']' after array items")?;
+
+        Ok(Expr::Array(items))
+    }
+
+    // An array item or call argument, either of which may be spread.
+    fn parse_element(&mut self) -> ParseResult<Expr> {
+        if self.match_token(TokenKind::Ellipsis) {
+            return Ok(Expr::Spread(Box::new(self.parse_expression()?)));
+        }
+        self.parse_expression()
+    }
+
+    // A call argument: positional, spread, or `name: value` after all positional ones.
+    fn parse_argument(&mut self, previous: &[Expr]) -> ParseResult<Expr> {
+        if matches!(self.peek().kind, TokenKind::Identifier(_)) && self.peek_next().kind == TokenKind::Colon {
+            let name = self.expect_identifier("Expected argument name")?;
+            self.advance();
+            if previous.iter().any(|arg| matches!(arg, Expr::Keyword { name: other, .. } if *other == name)) {
+                return fail(format!("Keyword argument '{}' repeated on line {}", name, self.previous().line));
+            }
+            return Ok(Expr::Keyword {
+                name,
+                value: Box::new(self.parse_expression()?),
+            });
+        }
+
+        if matches!(previous.last(), Some(Expr::Keyword { .. })) {
+            return fail(format!("Positional argument after keyword arguments on line {}", self.peek().line));
+        }
+        self.parse_element()
+    }
+
+    fn parse_object(&mut self) -> ParseResult<Expr> {
+        let mut fields = Vec::new();
+        while !self.check(&TokenKind::RightBrace) {
+            if self.match_token(TokenKind::Ellipsis) {
+                fields.push((PropertyKey::Spread, self.parse_expression()?));
+                if !self.match_token(TokenKind::Comma) {
+                    break;
                 }
+                continue;
             }
-            TokenKind::StringLiteral(value) => Expr::Literal(value.clone()),
-            TokenKind::TemplateString(value) => Expr::Template(value.clone()),
-            _ => {
-                panic!("Unexpected token in expression: {:?}", token.kind);
+
+            let key = if let TokenKind::StringLiteral(key) = &self.peek().kind {
+                let key = key.clone();
+                self.advance();
+                PropertyKey::Named(key)
+            } else if self.match_token(TokenKind::LeftBracket) {
+                let key = self.parse_expression()?;
+                self.expect(TokenKind::RightBracket, "Expected ']' after computed property name")?;
+                PropertyKey::Computed(key)
+            } else {
+                PropertyKey::Named(self.expect_property_name("Expected property name in object literal")?)
+            };
+            self.expect(TokenKind::Colon, "Expected ':' after property name")?;
+            fields.push((key, self.parse_expression()?));
+
+            if !self.match_token(TokenKind::Comma) {
+                break;
             }
         }
+        self.expect(TokenKind::RightBrace, "Expected '}' after object fields")?;
+
+        Ok(Expr::Object(fields))
+    }
+
+    fn expect_identifier(&mut self, message: &str) -> ParseResult<String> {
+        if let TokenKind::Identifier(name) = &self.peek().kind {
+            let name = name.clone();
+            self.advance();
+            Ok(name)
+        } else {
+            fail(format!("{} (found {:?})", message, self.peek().kind))
+        }
+    }
+
+    fn expect_ident(&mut self, message: &str) -> ParseResult<Ident> {
+        let line = self.peek().line;
+        Ok(Ident::new(self.expect_identifier(message)?, line))
+    }
+
+    fn expect_property_name(&mut self, message: &str) -> ParseResult<String> {
+        if let Some(keyword) = self.peek().kind.keyword_text() {
+            self.advance();
+            return Ok(keyword.to_string());
+        }
+        self.expect_identifier(message)
     }
 
-    fn expect(&mut self, expected: TokenKind, message: &str) {
+    fn expect(&mut self, expected: TokenKind, message: &str) -> ParseResult<()> {
         if !self.check(&expected) {
-            panic!("{} (found {:?})", message, self.peek().kind);
+            return fail(format!("{} (found {:?})", message, self.peek().kind));
         }
         self.advance();
+        Ok(())
     }
 }
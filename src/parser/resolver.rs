@@ -0,0 +1,645 @@
+use crate::parser::ast::{Binding, Expr, Function, Ident, ImportTarget, Pattern, PropertyKey, Slot, Stmt, TemplatePart};
+use crate::parser::tokens::TokenKind;
+
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+use std::rc::Rc;
+
+// Something suspect the resolver noticed. The program still runs; these are
+// shown as warnings.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for Diagnostic {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+// Gives every variable in `program` its slot. The program runs in a top-level
+// scope that already holds `names`, from earlier REPL entries for instance;
+// the variables it adds are appended. Names no scope declares are looked up
+// among the builtins, for which `is_global` says whether one exists; any
+// other name gets a top-level slot too, which a later REPL entry may fill.
+pub fn resolve(program: &mut [Stmt], names: &mut Vec<String>, is_global: &dyn Fn(&str) -> bool) -> Vec<Diagnostic> {
+    let mut resolver = Resolver {
+        scopes: Vec::new(),
+        is_global,
+        free: HashSet::new(),
+        diagnostics: Vec::new(),
+    };
+
+    let mut scope = Scope::default();
+    for name in names.iter() {
+        scope.add(name);
+        scope.defined.insert(name.clone());
+    }
+    let mut bindings = Vec::new();
+    hoist_block(program, &mut bindings);
+    for (name, _) in bindings {
+        scope.add(&name);
+    }
+
+    resolver.scopes.push(scope);
+    resolver.block(program, HashMap::new());
+    if let Some(scope) = resolver.scopes.pop() {
+        *names = scope.names;
+    }
+    resolver.diagnostics.sort_by_key(|diagnostic| diagnostic.line);
+    resolver.diagnostics
+}
+
+// How a statement binds a name.
+#[derive(Clone, Copy, PartialEq)]
+enum Form {
+    // `init`, `enum`, `get`, parameters and `catch` always bind in their own scope.
+    Declared,
+    // Assignments, destructuring and `for` heads update the variable of an
+    // enclosing function when there is one, and bind in their own scope otherwise.
+    Assigned,
+}
+
+#[derive(Default)]
+struct Scope {
+    slots: HashMap<String, u32>,
+    names: Vec<String>,
+    // Variables given a value so far, in source order.
+    defined: HashSet<String>,
+}
+
+impl Scope {
+    fn add(&mut self, name: &str) {
+        if !self.slots.contains_key(name) {
+            self.slots.insert(name.to_string(), self.names.len() as u32);
+            self.names.push(name.to_string());
+        }
+    }
+}
+
+struct Resolver<'a> {
+    // The script or module scope first, then one per function being resolved.
+    scopes: Vec<Scope>,
+    is_global: &'a dyn Fn(&str) -> bool,
+    // Names nothing in the program binds, given top-level slots anyway.
+    free: HashSet<String>,
+    diagnostics: Vec<Diagnostic>,
+}
+
+impl Resolver<'_> {
+    fn report(&mut self, line: usize, message: String) {
+        let diagnostic = Diagnostic { line, message };
+        if !self.diagnostics.contains(&diagnostic) {
+            self.diagnostics.push(diagnostic);
+        }
+    }
+
+    fn current(&mut self) -> &mut Scope {
+        self.scopes.last_mut().expect("resolving outside any scope")
+    }
+
+    fn locate(&self, name: &str) -> Option<Slot> {
+        self.scopes.iter().rev().enumerate().find_map(|(depth, scope)| {
+            scope.slots.get(name).map(|&index| Slot::Local { depth: depth as u32, index })
+        })
+    }
+
+    // Resolves a function body in a scope of its own, and records how many
+    // slots a call needs.
+    fn function(&mut self, function: &mut Rc<Function>) {
+        let Function { params, body, locals, .. } =
+            Rc::get_mut(function).expect("functions are resolved before anything shares them");
+
+        let mut bindings = Vec::new();
+        for binding in &params.positional {
+            hoist_pattern(&binding.target, Form::Declared, &mut bindings);
+        }
+        if let Some(rest) = &params.rest {
+            bindings.push((rest.name.clone(), Form::Declared));
+        }
+        hoist_block(body, &mut bindings);
+
+        // A declaration anywhere in the body makes the name local throughout it.
+        let mut scope = Scope::default();
+        for (name, _) in bindings.iter().filter(|(_, form)| *form == Form::Declared) {
+            scope.add(name);
+        }
+        for (name, _) in bindings.iter().filter(|(_, form)| *form == Form::Assigned) {
+            if !scope.slots.contains_key(name) && self.locate(name).is_none() {
+                scope.add(name);
+            }
+        }
+        self.scopes.push(scope);
+
+        let mut declared = HashMap::new();
+        for binding in &params.positional {
+            for name in pattern_idents(&binding.target) {
+                declared.insert(name.name.clone(), name.line);
+            }
+        }
+        if let Some(rest) = &params.rest {
+            declared.insert(rest.name.clone(), rest.line);
+        }
+
+        // In order, so a default can refer to the parameters before it.
+        for binding in &mut params.positional {
+            self.binding(binding);
+        }
+        if let Some(rest) = &mut params.rest {
+            self.write(rest);
+        }
+        self.block(body, declared);
+
+        *locals = self.scopes.pop().map_or(0, |scope| scope.names.len());
+    }
+
+    // `declared` holds names already declared alongside the block, such as
+    // parameters, with the line each was declared on.
+    fn block(&mut self, body: &mut [Stmt], mut declared: HashMap<String, usize>) {
+        for stmt in body.iter() {
+            for name in declarations(stmt) {
+                match declared.get(&name.name) {
+                    Some(first) => {
+                        let message = format!("'{}' is already declared on line {}", name.name, first);
+                        self.report(name.line, message);
+                    }
+                    None => {
+                        declared.insert(name.name.clone(), name.line);
+                    }
+                }
+            }
+        }
+
+        for stmt in body {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &mut Stmt) {
+        match stmt {
+            Stmt::Init { name, function } => {
+                // Defined before the body is resolved, so the function can call itself.
+                self.write(name);
+                self.function(function);
+            }
+            Stmt::Log { value } | Stmt::Throw { value } | Stmt::ExprStmt(value) => self.expr(value),
+            Stmt::Return { value } => {
+                if let Some(value) = value {
+                    self.expr(value);
+                }
+            }
+            Stmt::Assign { name, value } => {
+                self.expr(value);
+                self.write(name);
+            }
+            Stmt::Destructure { pattern, value } => {
+                self.expr(value);
+                self.pattern(pattern);
+            }
+            Stmt::Get { target, source } => {
+                self.expr(source);
+                match target {
+                    ImportTarget::Namespace(name) => self.write(name),
+                    ImportTarget::Names(names) => {
+                        for name in names {
+                            self.write(name);
+                        }
+                    }
+                }
+            }
+            Stmt::Try { body, catches, finally } => {
+                self.block(body, HashMap::new());
+                for clause in catches {
+                    if let Some(name) = &mut clause.name {
+                        self.write(name);
+                    }
+                    self.block(&mut clause.body, HashMap::new());
+                }
+                if let Some(finally) = finally {
+                    self.block(finally, HashMap::new());
+                }
+            }
+            Stmt::Enum { name, members } => {
+                for (_, value) in members.iter_mut() {
+                    if let Some(value) = value {
+                        self.expr(value);
+                    }
+                }
+                self.write(name);
+            }
+            Stmt::Match { subject, cases, otherwise } => {
+                self.expr(subject);
+                for case in cases {
+                    for pattern in &mut case.patterns {
+                        self.expr(pattern);
+                    }
+                    self.block(&mut case.body, HashMap::new());
+                }
+                if let Some(otherwise) = otherwise {
+                    self.block(otherwise, HashMap::new());
+                }
+            }
+            Stmt::For { pattern, iterable, body } => {
+                self.expr(iterable);
+                self.pattern(pattern);
+                self.block(body, HashMap::new());
+            }
+            Stmt::Share(inner) => self.stmt(inner),
+        }
+    }
+
+    fn pattern(&mut self, pattern: &mut Pattern) {
+        match pattern {
+            Pattern::Name(name) => self.write(name),
+            Pattern::Object { properties, rest } => {
+                for (_, binding) in properties {
+                    self.binding(binding);
+                }
+                if let Some(rest) = rest {
+                    self.write(rest);
+                }
+            }
+            Pattern::Array { items, rest } => {
+                for binding in items {
+                    self.binding(binding);
+                }
+                if let Some(rest) = rest {
+                    self.pattern(rest);
+                }
+            }
+        }
+    }
+
+    fn binding(&mut self, binding: &mut Binding) {
+        if let Some(default) = &mut binding.default {
+            self.expr(default);
+        }
+        self.pattern(&mut binding.target);
+    }
+
+    fn expr(&mut self, expr: &mut Expr) {
+        match expr {
+            Expr::Literal(_) | Expr::Number(_) | Expr::Boolean(_) | Expr::Null | Expr::Undefined => {}
+            Expr::Variable(name) => self.read(name),
+            Expr::Template(parts) => {
+                for part in parts {
+                    if let TemplatePart::Expr(expr) = part {
+                        self.expr(expr);
+                    }
+                }
+            }
+            Expr::Function(function) => self.function(function),
+            Expr::Call { callee, arguments, .. } => {
+                self.expr(callee);
+                for argument in arguments {
+                    self.expr(argument);
+                }
+            }
+            Expr::Member { object, .. } => self.expr(object),
+            Expr::Index { object, index, .. } => {
+                self.expr(object);
+                self.expr(index);
+            }
+            Expr::Await(operand) | Expr::Unary { operand, .. } | Expr::Spread(operand) => self.expr(operand),
+            Expr::Keyword { value, .. } => self.expr(value),
+            Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+                self.expr(left);
+                self.expr(right);
+            }
+            Expr::Assign { target, operator, value } => match &mut **target {
+                Expr::Variable(name) => {
+                    // Compound assignments read the variable first.
+                    if *operator != TokenKind::Equal {
+                        self.read(name);
+                    }
+                    self.expr(value);
+                    self.write(name);
+                }
+                target => {
+                    self.expr(target);
+                    self.expr(value);
+                }
+            },
+            Expr::Update { target, .. } => match &mut **target {
+                Expr::Variable(name) => {
+                    self.read(name);
+                    self.write(name);
+                }
+                target => self.expr(target),
+            },
+            Expr::Array(items) => {
+                for item in items {
+                    self.expr(item);
+                }
+            }
+            Expr::Object(fields) => {
+                for (key, value) in fields {
+                    if let PropertyKey::Computed(key) = key {
+                        self.expr(key);
+                    }
+                    self.expr(value);
+                }
+            }
+        }
+    }
+
+    fn read(&mut self, name: &mut Ident) {
+        let slot = match self.locate(&name.name) {
+            Some(slot) => slot,
+            None if (self.is_global)(&name.name) => {
+                name.slot = Slot::Global;
+                return;
+            }
+            None => self.reserve(&name.name),
+        };
+
+        if self.free.contains(&name.name) {
+            self.report(name.line, format!("'{}' is not defined", name.name));
+        } else if matches!(slot, Slot::Local { depth: 0, .. }) && !self.current().defined.contains(&name.name) {
+            // Only the scope being run can be checked in order; a function
+            // may well be called after the outer variables it uses are set.
+            self.report(name.line, format!("'{}' is used before it is defined", name.name));
+        }
+        name.slot = slot;
+    }
+
+    // Gives a name nothing binds a slot in the top-level scope, so it reads as
+    // not defined until something, such as a later REPL entry, assigns it.
+    fn reserve(&mut self, name: &str) -> Slot {
+        self.free.insert(name.to_string());
+        let depth = self.scopes.len() as u32 - 1;
+        let top = self.scopes.first_mut().expect("resolving outside any scope");
+        top.add(name);
+        Slot::Local { depth, index: top.slots[name] }
+    }
+
+    fn write(&mut self, name: &mut Ident) {
+        let slot = self.locate(&name.name).expect("every bound name is hoisted into a scope");
+        if matches!(slot, Slot::Local { depth: 0, .. }) {
+            self.current().defined.insert(name.name.clone());
+        }
+        name.slot = slot;
+    }
+}
+
+fn pattern_idents(pattern: &Pattern) -> Vec<&Ident> {
+    match pattern {
+        Pattern::Name(name) => vec![name],
+        Pattern::Object { properties, rest } => properties
+            .iter()
+            .flat_map(|(_, binding)| pattern_idents(&binding.target))
+            .chain(rest.as_ref())
+            .collect(),
+        Pattern::Array { items, rest } => items
+            .iter()
+            .flat_map(|binding| pattern_idents(&binding.target))
+            .chain(rest.iter().flat_map(|rest| pattern_idents(rest)))
+            .collect(),
+    }
+}
+
+// Names a statement declares with `init`, `enum` or `get`; declaring one twice
+// in the same block is reported.
+fn declarations(stmt: &Stmt) -> Vec<&Ident> {
+    match stmt {
+        Stmt::Init { name, .. } | Stmt::Enum { name, .. } => vec![name],
+        Stmt::Get { target: ImportTarget::Namespace(name), .. } => vec![name],
+        Stmt::Get { target: ImportTarget::Names(names), .. } => names.iter().collect(),
+        Stmt::Share(inner) => declarations(inner),
+        _ => Vec::new(),
+    }
+}
+
+// Collects the names bound directly in a function body or script, leaving
+// out nested functions, which get scopes of their own.
+fn hoist_block(body: &[Stmt], bindings: &mut Vec<(String, Form)>) {
+    for stmt in body {
+        hoist_stmt(stmt, bindings);
+    }
+}
+
+fn hoist_stmt(stmt: &Stmt, bindings: &mut Vec<(String, Form)>) {
+    match stmt {
+        Stmt::Init { name, .. } => bindings.push((name.name.clone(), Form::Declared)),
+        Stmt::Log { value } | Stmt::Throw { value } | Stmt::ExprStmt(value) => hoist_expr(value, bindings),
+        Stmt::Return { value } => {
+            if let Some(value) = value {
+                hoist_expr(value, bindings);
+            }
+        }
+        Stmt::Assign { name, value } => {
+            hoist_expr(value, bindings);
+            bindings.push((name.name.clone(), Form::Assigned));
+        }
+        Stmt::Destructure { pattern, value } => {
+            hoist_expr(value, bindings);
+            hoist_pattern(pattern, Form::Assigned, bindings);
+        }
+        Stmt::Get { source, .. } => {
+            hoist_expr(source, bindings);
+            for name in stmt.bound_names() {
+                bindings.push((name, Form::Declared));
+            }
+        }
+        Stmt::Try { body, catches, finally } => {
+            hoist_block(body, bindings);
+            for clause in catches {
+                if let Some(name) = &clause.name {
+                    bindings.push((name.name.clone(), Form::Declared));
+                }
+                hoist_block(&clause.body, bindings);
+            }
+            if let Some(finally) = finally {
+                hoist_block(finally, bindings);
+            }
+        }
+        Stmt::Enum { name, members } => {
+            for value in members.iter().filter_map(|(_, value)| value.as_ref()) {
+                hoist_expr(value, bindings);
+            }
+            bindings.push((name.name.clone(), Form::Declared));
+        }
+        Stmt::Match { subject, cases, otherwise } => {
+            hoist_expr(subject, bindings);
+            for case in cases {
+                for pattern in &case.patterns {
+                    hoist_expr(pattern, bindings);
+                }
+                hoist_block(&case.body, bindings);
+            }
+            if let Some(otherwise) = otherwise {
+                hoist_block(otherwise, bindings);
+            }
+        }
+        Stmt::For { pattern, iterable, body } => {
+            hoist_expr(iterable, bindings);
+            hoist_pattern(pattern, Form::Assigned, bindings);
+            hoist_block(body, bindings);
+        }
+        Stmt::Share(inner) => hoist_stmt(inner, bindings),
+    }
+}
+
+fn hoist_pattern(pattern: &Pattern, form: Form, bindings: &mut Vec<(String, Form)>) {
+    match pattern {
+        Pattern::Name(name) => bindings.push((name.name.clone(), form)),
+        Pattern::Object { properties, rest } => {
+            for (_, binding) in properties {
+                hoist_binding(binding, form, bindings);
+            }
+            if let Some(rest) = rest {
+                bindings.push((rest.name.clone(), form));
+            }
+        }
+        Pattern::Array { items, rest } => {
+            for binding in items {
+                hoist_binding(binding, form, bindings);
+            }
+            if let Some(rest) = rest {
+                hoist_pattern(rest, form, bindings);
+            }
+        }
+    }
+}
+
+fn hoist_binding(binding: &Binding, form: Form, bindings: &mut Vec<(String, Form)>) {
+    // Parameter defaults run inside the function, so they belong to its scope too.
+    if let Some(default) = &binding.default {
+        hoist_expr(default, bindings);
+    }
+    hoist_pattern(&binding.target, form, bindings);
+}
+
+// Only assignment expressions bind names; function bodies are skipped.
+fn hoist_expr(expr: &Expr, bindings: &mut Vec<(String, Form)>) {
+    match expr {
+        Expr::Literal(_)
+        | Expr::Number(_)
+        | Expr::Boolean(_)
+        | Expr::Null
+        | Expr::Undefined
+        | Expr::Variable(_)
+        | Expr::Function(_) => {}
+        Expr::Template(parts) => {
+            for part in parts {
+                if let TemplatePart::Expr(expr) = part {
+                    hoist_expr(expr, bindings);
+                }
+            }
+        }
+        Expr::Call { callee, arguments, .. } => {
+            hoist_expr(callee, bindings);
+            for argument in arguments {
+                hoist_expr(argument, bindings);
+            }
+        }
+        Expr::Member { object, .. } => hoist_expr(object, bindings),
+        Expr::Index { object, index, .. } => {
+            hoist_expr(object, bindings);
+            hoist_expr(index, bindings);
+        }
+        Expr::Await(operand) | Expr::Unary { operand, .. } | Expr::Spread(operand) => hoist_expr(operand, bindings),
+        Expr::Keyword { value, .. } => hoist_expr(value, bindings),
+        Expr::Binary { left, right, .. } | Expr::Logical { left, right, .. } => {
+            hoist_expr(left, bindings);
+            hoist_expr(right, bindings);
+        }
+        Expr::Assign { target, value, .. } => {
+            match &**target {
+                Expr::Variable(name) => bindings.push((name.name.clone(), Form::Assigned)),
+                target => hoist_expr(target, bindings),
+            }
+            hoist_expr(value, bindings);
+        }
+        Expr::Update { target, .. } => match &**target {
+            Expr::Variable(name) => bindings.push((name.name.clone(), Form::Assigned)),
+            target => hoist_expr(target, bindings),
+        },
+        Expr::Array(items) => {
+            for item in items {
+                hoist_expr(item, bindings);
+            }
+        }
+        Expr::Object(fields) => {
+            for (key, value) in fields {
+                if let PropertyKey::Computed(key) = key {
+                    hoist_expr(key, bindings);
+                }
+                hoist_expr(value, bindings);
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::resolve;
+    use crate::parser::ast::{Expr, Slot, Stmt};
+    use crate::runtime::evaluator::parse_source;
+
+    fn diagnostics(source: &str) -> Vec<String> {
+        let mut program = parse_source(source);
+        resolve(&mut program, &mut Vec::new(), &|name| name == "Promise")
+            .iter()
+            .map(ToString::to_string)
+            .collect()
+    }
+
+    #[test]
+    fn gives_variables_the_slot_of_the_scope_that_binds_them() {
+        let mut program = parse_source(r#"
+total = 0
+init add(amount):
+    total = total + amount
+    step = amount
+    return Promise
+"#);
+        let mut names = vec!["earlier".to_string()];
+        let found = resolve(&mut program, &mut names, &|name| name == "Promise");
+        assert!(found.is_empty(), "{:?}", found);
+        assert_eq!(names, ["earlier", "total", "add"]);
+
+        let Stmt::Init { function, .. } = &program[1] else {
+            panic!("expected a function");
+        };
+        // `amount` and `step`; `total` belongs to the script.
+        assert_eq!(function.locals, 2);
+        let Stmt::Assign { name, value: Expr::Binary { left, right, .. } } = &function.body[0] else {
+            panic!("expected an assignment");
+        };
+        assert_eq!(name.slot, Slot::Local { depth: 1, index: 1 });
+        assert!(matches!(&**left, Expr::Variable(total) if total.slot == Slot::Local { depth: 1, index: 1 }));
+        assert!(matches!(&**right, Expr::Variable(amount) if amount.slot == Slot::Local { depth: 0, index: 0 }));
+        let Stmt::Return { value: Some(Expr::Variable(promise)) } = &function.body[2] else {
+            panic!("expected a return");
+        };
+        assert_eq!(promise.slot, Slot::Global);
+    }
+
+    #[test]
+    fn reports_use_before_definition_and_duplicate_declarations() {
+        assert_eq!(
+            diagnostics(r#"
+log(early)
+early = 1
+init later():
+    return [early, late, nowhere]
+late = 2
+init later(x):
+    init x():
+        return count
+    count = count + 1
+"#),
+            [
+                "line 2: 'early' is used before it is defined",
+                "line 5: 'nowhere' is not defined",
+                "line 7: 'later' is already declared on line 4",
+                "line 8: 'x' is already declared on line 7",
+                "line 10: 'count' is used before it is defined",
+            ]
+        );
+        assert!(diagnostics("init f(n):\n    return `${n} ${Promise}`\nlog(f(1))\n").is_empty());
+    }
+}
@@ -21,6 +21,10 @@ pub enum TokenKind {
     Log,
     Get,
     From,
+    Share,
+    Await,
+    Enum,
+    Match,
 
     // Symbols
     LeftParen,
@@ -35,9 +39,34 @@ pub enum TokenKind {
     Dot,
     Arrow,        // =>
     Star,         // *
+    StarStar,     // **
     Equal,
+    PlusEqual,    // +=
+    MinusEqual,   // -=
+    StarEqual,    // *=
+    SlashEqual,   // /=
+    PercentEqual, // %=
+    StarStarEqual, // **=
+    AmpEqual,     // &=
+    PipeEqual,    // |=
+    CaretEqual,   // ^=
+    LessLessEqual, // <<=
+    GreaterGreaterEqual, // >>=
+    GreaterGreaterGreaterEqual, // >>>=
+    PlusPlus,     // ++
+    MinusMinus,   // --
     DoubleEqual,
     NotEqual,
+    Bang,         // !
+    AmpAmp,       // &&
+    PipePipe,     // ||
+    Amp,          // &
+    Pipe,         // |
+    Caret,        // ^
+    Tilde,        // ~
+    LessLess,     // <<
+    GreaterGreater, // >>
+    GreaterGreaterGreater, // >>>
     Greater,
     Less,
     GreaterEqual,
@@ -47,6 +76,9 @@ pub enum TokenKind {
     Slash,
     Percent,
     Question,
+    QuestionDot,           // ?.
+    QuestionQuestion,      // ??
+    QuestionQuestionEqual, // ??=
     TernaryColon,
     Ellipsis,     // ...
 
@@ -56,6 +88,8 @@ pub enum TokenKind {
     NumberLiteral(String),
     BooleanLiteral(bool),
     TemplateString(String),
+    // A template string the source ended inside of.
+    UnterminatedTemplate,
     Null,
     Undefined,
 
@@ -67,6 +101,44 @@ pub enum TokenKind {
     Unknown(char),
 }
 
+impl TokenKind {
+    // The source text of a keyword token, so keywords can still be used as
+    // property names (`app.get(...)`, `{from: 1}`).
+    pub fn keyword_text(&self) -> Option<&'static str> {
+        let text = match self {
+            TokenKind::Init => "init",
+            TokenKind::If => "if",
+            TokenKind::Elif => "elif",
+            TokenKind::Else => "else",
+            TokenKind::For => "for",
+            TokenKind::While => "while",
+            TokenKind::In => "in",
+            TokenKind::Try => "try",
+            TokenKind::Catch => "catch",
+            TokenKind::Finally => "finally",
+            TokenKind::Throw => "throw",
+            TokenKind::Class => "class",
+            TokenKind::Extends => "extends",
+            TokenKind::Fixed => "fixed",
+            TokenKind::Secure => "secure",
+            TokenKind::Return => "return",
+            TokenKind::Range => "range",
+            TokenKind::Log => "log",
+            TokenKind::Get => "get",
+            TokenKind::From => "from",
+            TokenKind::Share => "share",
+            TokenKind::Await => "await",
+            TokenKind::Enum => "enum",
+            TokenKind::Match => "match",
+            TokenKind::BooleanLiteral(true) => "true",
+            TokenKind::BooleanLiteral(false) => "false",
+            TokenKind::Null => "null",
+            TokenKind::Undefined => "undefined",
+            _ => return None,
+        };
+        Some(text)
+    }
+}
 
 #[derive(Debug, Clone)]
 pub struct Token {
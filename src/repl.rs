@@ -0,0 +1,312 @@
+use crate::parser::lexer::Lexer;
+use crate::parser::tokens::TokenKind;
+use crate::runtime::environment::Env;
+use crate::runtime::evaluator::{try_parse_source, Backend, Evaluator};
+use crate::runtime::values::Value;
+use crate::utils::errors::Control;
+
+use std::fs;
+use std::io::{self, BufRead, Write};
+use std::mem;
+use std::path::{Path, PathBuf};
+
+const HELP: &str = "\
+Enter statements or expressions; the value of an expression is printed.
+A line ending in ':' opens a block, which runs after an empty line.
+
+  :help           show this message
+  :load <file>    run a file in this session, keeping its definitions
+  :history [n]    list previous entries, or run entry n again
+  :reset          forget every definition and start over
+  :quit           leave (Ctrl-D works too)";
+
+// How many entries the history file keeps.
+const HISTORY_LIMIT: usize = 500;
+
+// An interactive session: one evaluator and one scope shared by every entry.
+pub struct Repl {
+    evaluator: Evaluator,
+    scope: Env,
+    history: Vec<String>,
+    history_file: Option<PathBuf>,
+}
+
+impl Repl {
+    pub fn new() -> Self {
+        Self::with_backend(Backend::default())
+    }
+
+    pub fn with_backend(backend: Backend) -> Self {
+        let evaluator = Evaluator::with_backend(backend);
+        let scope = evaluator.session_scope();
+        Self {
+            evaluator,
+            scope,
+            history: Vec::new(),
+            history_file: None,
+        }
+    }
+
+    // Loads earlier entries from `path` and saves the session's there on exit.
+    pub fn with_history_file(mut self, path: PathBuf) -> Self {
+        if let Ok(saved) = fs::read_to_string(&path) {
+            self.history = saved.lines().map(unescape_entry).collect();
+        }
+        self.history_file = Some(path);
+        self
+    }
+
+    // Reads entries until end of input or `:quit`, and returns the exit code.
+    pub fn run(&mut self, input: impl BufRead, output: &mut impl Write) -> io::Result<i32> {
+        writeln!(output, "Genesix {} - type :help for commands", env!("CARGO_PKG_VERSION"))?;
+
+        let mut lines = input.lines();
+        let mut buffer = String::new();
+        let code = loop {
+            write!(output, "{}", if buffer.is_empty() { "> " } else { "... " })?;
+            output.flush()?;
+
+            let Some(line) = lines.next() else {
+                writeln!(output)?;
+                break 0;
+            };
+            let line = line?;
+
+            if buffer.is_empty() {
+                let trimmed = line.trim();
+                if trimmed.is_empty() {
+                    continue;
+                }
+                if trimmed.starts_with(':') {
+                    if let Some(code) = self.command(trimmed, output)? {
+                        break code;
+                    }
+                    continue;
+                }
+            }
+
+            buffer.push_str(&line);
+            buffer.push('\n');
+            if is_incomplete(&buffer) {
+                continue;
+            }
+
+            let entry = mem::take(&mut buffer);
+            self.history.push(entry.trim_end().to_string());
+            if let Some(code) = self.execute(&entry, output)? {
+                break code;
+            }
+        };
+
+        self.save_history();
+        Ok(code)
+    }
+
+    // Returns an exit code when the entry called `exit`.
+    fn execute(&mut self, source: &str, output: &mut impl Write) -> io::Result<Option<i32>> {
+        let program = match try_parse_source(source) {
+            Ok(program) => program,
+            Err(message) => {
+                writeln!(output, "SyntaxError: {}", message)?;
+                return Ok(None);
+            }
+        };
+
+        match self.evaluator.evaluate_in(program, &self.scope) {
+            Ok(Some(value)) if !matches!(value, Value::Null | Value::Undefined) => {
+                writeln!(output, "{}", self.evaluator.stringify(value))?;
+            }
+            Ok(_) | Err(Control::Return(_)) => {}
+            Err(Control::Throw(error)) => {
+                writeln!(output, "Uncaught {}", self.evaluator.describe_error(error))?;
+            }
+            Err(Control::Exit(code)) => return Ok(Some(code)),
+        }
+
+        for reason in self.evaluator.take_unhandled_rejections() {
+            writeln!(output, "Unhandled promise rejection: {}", self.evaluator.describe_error(reason))?;
+        }
+        Ok(None)
+    }
+
+    fn command(&mut self, line: &str, output: &mut impl Write) -> io::Result<Option<i32>> {
+        let (name, argument) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let argument = argument.trim();
+
+        match name {
+            ":help" => writeln!(output, "{}", HELP)?,
+            ":quit" | ":exit" => return Ok(Some(0)),
+            ":reset" => {
+                self.evaluator = Evaluator::with_backend(self.evaluator.backend());
+                self.scope = self.evaluator.session_scope();
+                writeln!(output, "Session reset")?;
+            }
+            ":load" if !argument.is_empty() => {
+                let path = Path::new(argument);
+                match fs::read_to_string(path) {
+                    Ok(source) => {
+                        self.history.push(line.to_string());
+                        self.evaluator.set_current_dir(path.parent().map(Path::to_path_buf).unwrap_or_default());
+                        return self.execute(&source, output);
+                    }
+                    Err(err) => writeln!(output, "IOError: Could not read {}: {}", path.display(), err)?,
+                }
+            }
+            ":history" if argument.is_empty() => {
+                for (index, entry) in self.history.iter().enumerate() {
+                    writeln!(output, "{:>4}  {}", index + 1, entry.replace('\n', "\n      "))?;
+                }
+            }
+            ":history" => {
+                let entry = argument
+                    .parse::<usize>()
+                    .ok()
+                    .and_then(|number| number.checked_sub(1))
+                    .and_then(|index| self.history.get(index))
+                    .cloned();
+                match entry {
+                    Some(entry) if entry.starts_with(':') => return self.command(&entry, output),
+                    Some(entry) => {
+                        writeln!(output, "{}", entry)?;
+                        self.history.push(entry.clone());
+                        return self.execute(&format!("{}\n", entry), output);
+                    }
+                    None => writeln!(output, "No history entry {}", argument)?,
+                }
+            }
+            ":load" => writeln!(output, "Usage: :load <file>")?,
+            _ => writeln!(output, "Unknown command {}, type :help for a list", name)?,
+        }
+        Ok(None)
+    }
+
+    fn save_history(&self) {
+        let Some(path) = &self.history_file else {
+            return;
+        };
+        let start = self.history.len().saturating_sub(HISTORY_LIMIT);
+        let saved: String = self.history[start..].iter().map(|entry| escape_entry(entry) + "\n").collect();
+        // History is a convenience; failing to write it shouldn't fail the session.
+        let _ = fs::write(path, saved);
+    }
+}
+
+impl Default for Repl {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// True while the entry still needs more lines: brackets or a template string
+// are left open, or a block was opened and no empty line has ended it yet.
+pub fn is_incomplete(source: &str) -> bool {
+    let tokens = Lexer::new(source).tokenize();
+    let mut depth = 0_i32;
+    for token in &tokens {
+        match token.kind {
+            TokenKind::UnterminatedTemplate => return true,
+            TokenKind::LeftParen | TokenKind::LeftBracket | TokenKind::LeftBrace => depth += 1,
+            TokenKind::RightParen | TokenKind::RightBracket | TokenKind::RightBrace => depth -= 1,
+            _ => {}
+        }
+    }
+    if depth > 0 {
+        return true;
+    }
+
+    let opens_block = tokens
+        .windows(2)
+        .any(|pair| pair[0].kind == TokenKind::Colon && pair[1].line > pair[0].line);
+    let ended = source.lines().last().is_none_or(|line| line.trim().is_empty());
+    opens_block && !ended
+}
+
+// The history file holds one entry per line.
+fn escape_entry(entry: &str) -> String {
+    entry.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape_entry(line: &str) -> String {
+    let mut entry = String::new();
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            entry.push(c);
+            continue;
+        }
+        match chars.next() {
+            Some('n') => entry.push('\n'),
+            Some(other) => entry.push(other),
+            None => entry.push('\\'),
+        }
+    }
+    entry
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{escape_entry, is_incomplete, unescape_entry, Repl};
+
+    use std::io::Cursor;
+
+    fn session(input: &str) -> (String, i32) {
+        let mut output = Vec::new();
+        let code = Repl::new().run(Cursor::new(input), &mut output).unwrap();
+        (String::from_utf8(output).unwrap(), code)
+    }
+
+    #[test]
+    fn entries_share_one_scope_and_print_expression_values() {
+        let (output, code) = session(
+            "init double(x):\n    return x * 2\n\ntotal = double(21)\ntotal + 1\nmissing\n(1 +\n2)\n[total, -1]\n:history 5\n:reset\ntotal\n:quit\nnever reached\n",
+        );
+
+        let results: Vec<&str> = output
+            .lines()
+            .map(|line| line.trim_start_matches(['>', '.', ' ']))
+            .filter(|line| !line.is_empty())
+            .collect();
+        assert_eq!(results, [
+            "Genesix 0.1.0 - type :help for commands",
+            "43",
+            "Uncaught ReferenceError: missing is not defined",
+            "3",
+            "[42, -1]",
+            "(1 +",
+            "2)",
+            "3",
+            "Session reset",
+            "Uncaught ReferenceError: total is not defined",
+        ]);
+        assert!(output.contains("... ... "), "block and bracket continuations use the '...' prompt");
+        assert_eq!(code, 0);
+    }
+
+    #[test]
+    fn functions_see_names_that_later_entries_define() {
+        let (output, _) = session("init show():\n    return total\n\nshow()\ntotal = 5\nshow()\n");
+        assert!(output.contains("Uncaught ReferenceError: total is not defined"), "{}", output);
+        assert!(output.ends_with("> 5\n> \n"), "{}", output);
+    }
+
+    #[test]
+    fn syntax_errors_are_reported_and_exit_codes_returned() {
+        let (output, code) = session("x = )\nget os from \"os\"\nos.exit(3)\nlog(\"never reached\")\n");
+        assert!(output.contains("SyntaxError: Unexpected token in expression: RightParen"));
+        assert_eq!(code, 3);
+    }
+
+    #[test]
+    fn detects_incomplete_entries() {
+        assert!(is_incomplete("init f():\n"));
+        assert!(is_incomplete("init f():\n    return 1\n"));
+        assert!(!is_incomplete("init f():\n    return 1\n\n"));
+        assert!(!is_incomplete("add = (x, y) =>: return x + y\n"));
+        assert!(is_incomplete("user = {\n"));
+        assert!(is_incomplete("text = `first\n"));
+        assert!(!is_incomplete("tick = \"`\"\n"));
+        assert!(!is_incomplete("text = `a \\` b`\n"));
+        assert!(!is_incomplete("user = {name: \"Ada\"}\n"));
+        assert_eq!(unescape_entry(&escape_entry("a\\n\nb")), "a\\n\nb");
+    }
+}
@@ -0,0 +1,451 @@
+use std::cmp::Ordering;
+
+// An arbitrary-precision integer: a sign and a little-endian magnitude in
+// base 2^32. The magnitude never has trailing zero limbs, and zero is never
+// negative, so derived equality is numeric equality.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct BigInt {
+    negative: bool,
+    limbs: Vec<u32>,
+}
+
+impl BigInt {
+    pub fn zero() -> Self {
+        Self::default()
+    }
+
+    pub fn from_i64(value: i64) -> Self {
+        let mut result = Self::from_u64(value.unsigned_abs());
+        result.negative = value < 0;
+        result.normalize()
+    }
+
+    fn from_u64(value: u64) -> Self {
+        Self { negative: false, limbs: vec![value as u32, (value >> 32) as u32] }.normalize()
+    }
+
+    // Whole, finite numbers only; fractions and NaN have no BigInt form.
+    pub fn from_f64(value: f64) -> Option<Self> {
+        if !value.is_finite() || value.fract() != 0.0 {
+            return None;
+        }
+
+        let bits = value.abs().to_bits();
+        let exponent = ((bits >> 52) & 0x7ff) as i64 - 1075;
+        let mantissa = if exponent == -1075 { (bits & 0xf_ffff_ffff_ffff) << 1 } else { (bits & 0xf_ffff_ffff_ffff) | (1 << 52) };
+        let mut result = Self::from_u64(mantissa);
+        if exponent < 0 {
+            result = result.shift_right((-exponent) as usize);
+        } else {
+            result = result.shift_left(exponent as usize);
+        }
+        result.negative = value < 0.0;
+        Some(result.normalize())
+    }
+
+    // Nearest float, rounding towards zero beyond 53 bits of precision.
+    pub fn to_f64(&self) -> f64 {
+        let magnitude = self.limbs.iter().rev().fold(0.0, |total, limb| total * 4_294_967_296.0 + *limb as f64);
+        if self.negative { -magnitude } else { magnitude }
+    }
+
+    pub fn is_zero(&self) -> bool {
+        self.limbs.is_empty()
+    }
+
+    pub fn is_negative(&self) -> bool {
+        self.negative
+    }
+
+    // Accepts an optional sign, then digits in `radix`. With radix 10, a
+    // `0x`, `0o` or `0b` prefix selects that base instead. `_` separators are allowed.
+    pub fn parse(text: &str, radix: u32) -> Result<Self, String> {
+        let invalid = || format!("Cannot convert '{}' to a BigInt", text);
+        let trimmed = text.trim();
+        let (negative, digits) = match trimmed.as_bytes().first() {
+            Some(b'-') => (true, &trimmed[1..]),
+            Some(b'+') => (false, &trimmed[1..]),
+            _ => (false, trimmed),
+        };
+
+        let lower = digits.to_ascii_lowercase();
+        let (radix, digits) = match lower.get(..2) {
+            Some("0x") if radix == 10 || radix == 16 => (16, &digits[2..]),
+            Some("0o") if radix == 10 || radix == 8 => (8, &digits[2..]),
+            Some("0b") if radix == 10 || radix == 2 => (2, &digits[2..]),
+            _ => (radix, digits),
+        };
+
+        if digits.is_empty() || digits.starts_with('_') || digits.ends_with('_') {
+            return Err(invalid());
+        }
+
+        let mut result = Self::zero();
+        for c in digits.chars().filter(|c| *c != '_') {
+            let digit = c.to_digit(radix).ok_or_else(invalid)?;
+            result = result.mul_small(radix).add_small(digit);
+        }
+        result.negative = negative;
+        Ok(result.normalize())
+    }
+
+    pub fn to_string_radix(&self, radix: u32) -> String {
+        if self.is_zero() {
+            return "0".to_string();
+        }
+
+        let mut digits = Vec::new();
+        let mut rest = self.abs();
+        while !rest.is_zero() {
+            let (quotient, digit) = rest.divrem_small(radix);
+            digits.push(char::from_digit(digit, radix).unwrap_or('?'));
+            rest = quotient;
+        }
+        if self.negative {
+            digits.push('-');
+        }
+        digits.iter().rev().collect()
+    }
+
+    pub fn abs(&self) -> Self {
+        Self { negative: false, limbs: self.limbs.clone() }
+    }
+
+    pub fn neg(&self) -> Self {
+        Self { negative: !self.negative, limbs: self.limbs.clone() }.normalize()
+    }
+
+    pub fn add(&self, other: &Self) -> Self {
+        if self.negative == other.negative {
+            return Self { negative: self.negative, limbs: add_magnitudes(&self.limbs, &other.limbs) }.normalize();
+        }
+        match compare_magnitudes(&self.limbs, &other.limbs) {
+            Ordering::Equal => Self::zero(),
+            Ordering::Greater => Self { negative: self.negative, limbs: sub_magnitudes(&self.limbs, &other.limbs) }.normalize(),
+            Ordering::Less => Self { negative: other.negative, limbs: sub_magnitudes(&other.limbs, &self.limbs) }.normalize(),
+        }
+    }
+
+    pub fn sub(&self, other: &Self) -> Self {
+        self.add(&other.neg())
+    }
+
+    pub fn mul(&self, other: &Self) -> Self {
+        let mut limbs = vec![0_u32; self.limbs.len() + other.limbs.len()];
+        for (i, a) in self.limbs.iter().enumerate() {
+            let mut carry = 0_u64;
+            for (j, b) in other.limbs.iter().enumerate() {
+                let total = limbs[i + j] as u64 + *a as u64 * *b as u64 + carry;
+                limbs[i + j] = total as u32;
+                carry = total >> 32;
+            }
+            limbs[i + other.limbs.len()] = carry as u32;
+        }
+        Self { negative: self.negative != other.negative, limbs }.normalize()
+    }
+
+    // Truncating division: the quotient rounds towards zero and the
+    // remainder takes the sign of the dividend, as in JavaScript.
+    pub fn divrem(&self, other: &Self) -> Option<(Self, Self)> {
+        if other.is_zero() {
+            return None;
+        }
+
+        let (mut quotient, mut remainder) = if other.limbs.len() == 1 {
+            let (quotient, remainder) = self.abs().divrem_small(other.limbs[0]);
+            (quotient, Self::from_u64(remainder as u64))
+        } else {
+            let divisor = other.abs();
+            let mut quotient = Self::zero();
+            let mut remainder = Self::zero();
+            for bit in (0..self.limbs.len() * 32).rev() {
+                remainder = remainder.shift_left(1);
+                if self.limbs[bit / 32] >> (bit % 32) & 1 == 1 {
+                    remainder = remainder.add_small(1);
+                }
+                if compare_magnitudes(&remainder.limbs, &divisor.limbs) != Ordering::Less {
+                    remainder = remainder.sub(&divisor);
+                    quotient.set_bit(bit);
+                }
+            }
+            (quotient, remainder)
+        };
+
+        quotient.negative = self.negative != other.negative;
+        remainder.negative = self.negative;
+        Some((quotient.normalize(), remainder.normalize()))
+    }
+
+    pub fn pow(&self, exponent: u64) -> Self {
+        let mut result = Self::from_i64(1);
+        let mut base = self.clone();
+        let mut exponent = exponent;
+        while exponent > 0 {
+            if exponent & 1 == 1 {
+                result = result.mul(&base);
+            }
+            exponent >>= 1;
+            if exponent > 0 {
+                base = base.mul(&base);
+            }
+        }
+        result
+    }
+
+    // The exponent as a machine integer, if it fits.
+    // The bitwise operators act as if negative values were in two's
+    // complement with infinitely many leading ones, as in JavaScript.
+    pub fn bitand(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a & b)
+    }
+
+    pub fn bitor(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a | b)
+    }
+
+    pub fn bitxor(&self, other: &Self) -> Self {
+        self.bitwise(other, |a, b| a ^ b)
+    }
+
+    // ~x == -x - 1
+    pub fn not(&self) -> Self {
+        self.neg().sub(&Self::from_i64(1))
+    }
+
+    pub fn shl(&self, bits: usize) -> Self {
+        self.shift_left(bits)
+    }
+
+    // Rounds toward negative infinity, so -5 >> 1 is -3.
+    pub fn shr(&self, bits: usize) -> Self {
+        if self.negative {
+            self.not().shift_right(bits).not()
+        } else {
+            self.shift_right(bits)
+        }
+    }
+
+    pub fn to_u64(&self) -> Option<u64> {
+        if self.negative || self.limbs.len() > 2 {
+            return None;
+        }
+        Some(self.limbs.iter().rev().fold(0, |total, limb| (total << 32) | *limb as u64))
+    }
+
+    // Bits in the magnitude, so 0 has none and 5 has three.
+    pub fn bit_length(&self) -> u64 {
+        match self.limbs.last() {
+            Some(top) => self.limbs.len() as u64 * 32 - top.leading_zeros() as u64,
+            None => 0,
+        }
+    }
+
+    fn shift_left(&self, bits: usize) -> Self {
+        let (whole, part) = (bits / 32, bits % 32);
+        let mut limbs = vec![0_u32; whole];
+        let mut carry = 0_u32;
+        for limb in &self.limbs {
+            if part == 0 {
+                limbs.push(*limb);
+            } else {
+                limbs.push((limb << part) | carry);
+                carry = limb >> (32 - part);
+            }
+        }
+        limbs.push(carry);
+        Self { negative: self.negative, limbs }.normalize()
+    }
+
+    fn shift_right(&self, bits: usize) -> Self {
+        let (whole, part) = (bits / 32, bits % 32);
+        let limbs = self.limbs.get(whole..).unwrap_or(&[]);
+        let shifted = (0..limbs.len())
+            .map(|i| {
+                let high = if part == 0 { 0 } else { limbs.get(i + 1).map_or(0, |next| next << (32 - part)) };
+                (limbs[i] >> part) | high
+            })
+            .collect();
+        Self { negative: self.negative, limbs: shifted }.normalize()
+    }
+
+    fn bitwise(&self, other: &Self, op: impl Fn(u32, u32) -> u32) -> Self {
+        // One spare limb keeps room for the sign bit.
+        let width = self.limbs.len().max(other.limbs.len()) + 1;
+        let limbs = self
+            .twos_complement(width)
+            .into_iter()
+            .zip(other.twos_complement(width))
+            .map(|(a, b)| op(a, b))
+            .collect();
+        Self::from_twos_complement(limbs)
+    }
+
+    fn twos_complement(&self, width: usize) -> Vec<u32> {
+        let (mut limbs, invert) = if self.negative {
+            (self.not().limbs, true)
+        } else {
+            (self.limbs.clone(), false)
+        };
+        limbs.resize(width, 0);
+        if invert {
+            limbs.iter_mut().for_each(|limb| *limb = !*limb);
+        }
+        limbs
+    }
+
+    fn from_twos_complement(limbs: Vec<u32>) -> Self {
+        if limbs.last().is_some_and(|top| top >> 31 == 1) {
+            let inverted = limbs.into_iter().map(|limb| !limb).collect();
+            Self { negative: false, limbs: inverted }.normalize().not()
+        } else {
+            Self { negative: false, limbs }.normalize()
+        }
+    }
+
+    fn set_bit(&mut self, bit: usize) {
+        if self.limbs.len() <= bit / 32 {
+            self.limbs.resize(bit / 32 + 1, 0);
+        }
+        self.limbs[bit / 32] |= 1 << (bit % 32);
+    }
+
+    fn mul_small(&self, factor: u32) -> Self {
+        let mut carry = 0_u64;
+        let mut limbs: Vec<u32> = self
+            .limbs
+            .iter()
+            .map(|limb| {
+                let total = *limb as u64 * factor as u64 + carry;
+                carry = total >> 32;
+                total as u32
+            })
+            .collect();
+        limbs.push(carry as u32);
+        Self { negative: self.negative, limbs }.normalize()
+    }
+
+    fn add_small(&self, addend: u32) -> Self {
+        Self { negative: self.negative, limbs: add_magnitudes(&self.limbs, &[addend]) }.normalize()
+    }
+
+    // Divides the magnitude by a single limb.
+    fn divrem_small(&self, divisor: u32) -> (Self, u32) {
+        let mut remainder = 0_u64;
+        let mut limbs = vec![0_u32; self.limbs.len()];
+        for (i, limb) in self.limbs.iter().enumerate().rev() {
+            let current = (remainder << 32) | *limb as u64;
+            limbs[i] = (current / divisor as u64) as u32;
+            remainder = current % divisor as u64;
+        }
+        (Self { negative: false, limbs }.normalize(), remainder as u32)
+    }
+
+    fn normalize(mut self) -> Self {
+        while self.limbs.last() == Some(&0) {
+            self.limbs.pop();
+        }
+        if self.limbs.is_empty() {
+            self.negative = false;
+        }
+        self
+    }
+}
+
+impl Ord for BigInt {
+    fn cmp(&self, other: &Self) -> Ordering {
+        match (self.negative, other.negative) {
+            (false, true) => Ordering::Greater,
+            (true, false) => Ordering::Less,
+            (false, false) => compare_magnitudes(&self.limbs, &other.limbs),
+            (true, true) => compare_magnitudes(&other.limbs, &self.limbs),
+        }
+    }
+}
+
+impl PartialOrd for BigInt {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+fn compare_magnitudes(a: &[u32], b: &[u32]) -> Ordering {
+    a.len().cmp(&b.len()).then_with(|| a.iter().rev().cmp(b.iter().rev()))
+}
+
+fn add_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len().max(b.len()) + 1);
+    let mut carry = 0_u64;
+    for i in 0..a.len().max(b.len()) {
+        let total = *a.get(i).unwrap_or(&0) as u64 + *b.get(i).unwrap_or(&0) as u64 + carry;
+        limbs.push(total as u32);
+        carry = total >> 32;
+    }
+    limbs.push(carry as u32);
+    limbs
+}
+
+// `a - b` where |a| >= |b|.
+fn sub_magnitudes(a: &[u32], b: &[u32]) -> Vec<u32> {
+    let mut limbs = Vec::with_capacity(a.len());
+    let mut borrow = 0_i64;
+    for (i, limb) in a.iter().enumerate() {
+        let mut difference = *limb as i64 - *b.get(i).unwrap_or(&0) as i64 - borrow;
+        borrow = if difference < 0 { 1 } else { 0 };
+        if difference < 0 {
+            difference += 1 << 32;
+        }
+        limbs.push(difference as u32);
+    }
+    limbs
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BigInt;
+
+    fn big(text: &str) -> BigInt {
+        BigInt::parse(text, 10).unwrap()
+    }
+
+    #[test]
+    fn parses_and_prints_in_several_radixes() {
+        let value = big("90071992547409911234567890");
+        assert_eq!(value.to_string_radix(10), "90071992547409911234567890");
+        assert_eq!(value.to_string_radix(16), "4a817c7ffffffdf58a1ed2");
+        assert_eq!(BigInt::parse("4A817C7FFFFFFDF58A1ED2", 16).unwrap(), value);
+        assert_eq!(big("-0xff").to_string_radix(10), "-255");
+        assert_eq!(big("1_000_000").to_string_radix(10), "1000000");
+        assert_eq!(big("0b1010").to_string_radix(2), "1010");
+        assert_eq!(BigInt::parse("z", 36).unwrap().to_string_radix(10), "35");
+        assert_eq!(big("-0").to_string_radix(10), "0");
+        assert!(BigInt::parse("12a", 10).is_err());
+        assert!(BigInt::parse("", 10).is_err());
+        assert_eq!(BigInt::from_f64(-1e21).unwrap().to_string_radix(10), "-1000000000000000000000");
+        assert!(BigInt::from_f64(1.5).is_none());
+    }
+
+    #[test]
+    fn arithmetic_matches_javascript() {
+        let a = big("123456789012345678901234567890");
+        let b = big("-987654321987654321");
+        assert_eq!(a.add(&b).to_string_radix(10), "123456789011358024579246913569");
+        assert_eq!(b.sub(&a).to_string_radix(10), "-123456789013333333223222222211");
+        assert_eq!(a.mul(&b).to_string_radix(10), "-121932631246761163237311385323609205901126352690");
+
+        let (quotient, remainder) = a.divrem(&b).unwrap();
+        assert_eq!(quotient.to_string_radix(10), "-124999998748");
+        assert_eq!(remainder.to_string_radix(10), "432099904777777782");
+        let (quotient, remainder) = big("-7").divrem(&big("2")).unwrap();
+        assert_eq!((quotient.to_string_radix(10), remainder.to_string_radix(10)), ("-3".into(), "-1".into()));
+        assert!(a.divrem(&BigInt::zero()).is_none());
+
+        assert_eq!(big("2").pow(100).to_string_radix(10), "1267650600228229401496703205376");
+        assert_eq!(big("-12345678901234567890").bitand(&big("0xffffffffffff")).to_string_radix(10), "95052271514926");
+        assert_eq!(big("-12345678901234567890").bitor(&big("255")).to_string_radix(10), "-12345678901234567681");
+        assert_eq!(big("-6").bitxor(&big("3")).to_string_radix(10), "-7");
+        assert_eq!(big("-5").shr(1).to_string_radix(10), "-3");
+        assert_eq!(big("-3").shl(70).to_string_radix(10), "-3541774862152233910272");
+        assert_eq!(big("0").not().to_string_radix(10), "-1");
+        assert!(b < a && big("-5") < big("-4") && big("10") > big("9"));
+    }
+}
@@ -0,0 +1,110 @@
+use crate::runtime::bigint::BigInt;
+use crate::runtime::values::Value;
+use crate::utils::errors::{throw, EvalResult};
+
+// The global `BigInt`: `BigInt("0xff")`, `BigInt("zz", 36)` or `BigInt(42)`.
+pub fn constructor() -> Value {
+    Value::native("BigInt", |_, args| {
+        let radix = radix_arg(args.get(1), "BigInt")?;
+        let value = match args.first() {
+            Some(Value::BigInt(big)) => big.clone(),
+            Some(Value::String(text)) => BigInt::parse(text, radix).or_else(|err| throw("ValueError", err))?,
+            Some(Value::Number(number)) => match BigInt::from_f64(*number) {
+                Some(big) => big,
+                None => return throw("RangeError", format!("{} is not an integer and cannot become a BigInt", number)),
+            },
+            Some(Value::Boolean(flag)) => BigInt::from_i64(*flag as i64),
+            _ => return throw("TypeError", "BigInt: expected a string, an integer number or a BigInt"),
+        };
+        Ok(Value::BigInt(value))
+    })
+}
+
+pub fn method(big: &BigInt, name: &str) -> Option<Value> {
+    let big = big.clone();
+    let method = match name {
+        "toString" => Value::native("bigint.toString", move |_, args| {
+            let radix = radix_arg(args.first(), "bigint.toString")?;
+            Ok(Value::String(big.to_string_radix(radix)))
+        }),
+        // The explicit way back to a (possibly rounded) number.
+        "toNumber" => Value::native("bigint.toNumber", move |_, _| Ok(Value::Number(big.to_f64()))),
+        _ => return None,
+    };
+    Some(method)
+}
+
+fn radix_arg(arg: Option<&Value>, function: &str) -> EvalResult<u32> {
+    match arg {
+        None | Some(Value::Undefined) => Ok(10),
+        Some(Value::Number(radix)) if (2.0..=36.0).contains(radix) && radix.fract() == 0.0 => Ok(*radix as u32),
+        Some(_) => throw("RangeError", format!("{}: radix must be an integer between 2 and 36", function)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::testing::logged;
+
+    #[test]
+    fn bigints_do_exact_arithmetic_and_refuse_to_mix_with_numbers() {
+        let output = logged(r#"
+            big = BigInt("90071992547409911234567890")
+            seven = BigInt(7)
+            try:
+                big + 1
+            catch (TypeError err):
+                mixed = err.message
+            try:
+                big / BigInt(0)
+            catch (RangeError err):
+                divided = err.message
+            try:
+                BigInt(10) ** BigInt(1000000000)
+            catch (RangeError err):
+                huge = err.message
+            log([
+                big * big, -big / seven, -big % seven, BigInt(2) ** BigInt(100),
+                BigInt("zz", 36), BigInt("0b1010_1010"), big.toString(16),
+                big > 100000000000000000000, seven <= 7, big.toNumber(), mixed, divided, huge,
+                BigInt(-1) ** BigInt("99999999999999999999999"), BigInt(0) ** BigInt(0), 2 ** 3 ** 2 - 1
+            ])
+        "#);
+
+        assert_eq!(
+            output,
+            "[8112963841460666590539029140911256631737675019052100n, -12867427506772844462081127n, -1n, \
+             1267650600228229401496703205376n, 1295n, 170n, \"4a817c7ffffffdf58a1ed2\", true, true, \
+             90071992547409900000000000, \"Cannot mix BigInt and other types, use explicit conversions\", \
+             \"Division by zero\", \"Maximum BigInt size exceeded\", -1n, 1n, 511]"
+        );
+    }
+
+    #[test]
+    fn bitwise_operators_work_on_int32_numbers_and_bigints() {
+        let output = logged(r#"
+            mode = 0
+            mode |= 4 | 2
+            mode &= ~2
+            try:
+                broken = 1.5 & 1
+            catch (TypeError err):
+                fraction = err.message
+            try:
+                broken = BigInt(1) >>> BigInt(1)
+            catch (TypeError err):
+                unsigned = err.message
+            log([
+                mode, 6 & 3, 6 ^ 3, 1 << 31, -16 >> 2, -16 >>> 28, 1 + 2 << 1, 1 | 2 ^ 3 & 1, 2 ** 3 ** 2,
+                4294967297 | 0, BigInt(-6) & BigInt(3), BigInt(1) << BigInt(64), BigInt(-9) >> BigInt(1),
+                BigInt(8) << BigInt(-2), ~BigInt(5), fraction, unsigned
+            ])
+        "#);
+
+        assert_eq!(
+            output,
+            "[4, 2, 5, -2147483648, -4, 15, 6, 3, 512, 1, 2n, 18446744073709551616n, -5n, 2n, -6n, \
+             \"Bitwise '&' needs whole numbers, got 1.5\", \"BigInts have no unsigned right shift, use >> instead\"]"
+        );
+    }
+}
@@ -0,0 +1,211 @@
+use crate::runtime::builtins::string_arg;
+use crate::runtime::date::{parse_offset, DateTime, MAX_MILLIS};
+use crate::runtime::values::Value;
+use crate::utils::errors::{throw, EvalResult};
+
+use std::cmp::Ordering;
+
+const OUT_OF_RANGE: &str = "Date is outside the supported range";
+
+// The global `Date`: `Date()` is now, `Date(ms)` a timestamp, `Date(text)` an
+// ISO-8601 string and `Date(year, month, day, ...)` UTC fields, months from 1.
+pub fn constructor() -> Value {
+    Value::native_with_properties("Date", |_, args| {
+        let date = match args.as_slice() {
+            [] => DateTime::now(),
+            [Value::Date(date)] => *date,
+            [Value::Number(millis)] => in_range(DateTime::from_millis(timestamp(*millis)?))?,
+            [Value::String(text)] => parse(text)?,
+            [Value::Number(_), ..] => from_fields(&args)?,
+            _ => return throw("TypeError", "Date: expected a timestamp, an ISO-8601 string or date fields"),
+        };
+        Ok(Value::Date(date))
+    }, vec![
+        ("now", Value::native("Date.now", |_, _| Ok(Value::Date(DateTime::now())))),
+        ("parse", Value::native("Date.parse", |_, args| {
+            let text = string_arg(&args, 0, "Date.parse")?;
+            Ok(Value::Date(parse(&text)?))
+        })),
+    ])
+}
+
+pub fn method(date: DateTime, name: &str) -> Option<Value> {
+    let parts = date.parts();
+    let getter = |value: f64| Some(Value::native(name, move |_, _| Ok(Value::Number(value))));
+
+    match name {
+        "year" => getter(parts.year as f64),
+        "month" => getter(parts.month as f64),
+        "day" => getter(parts.day as f64),
+        "hour" => getter(parts.hour as f64),
+        "minute" => getter(parts.minute as f64),
+        "second" => getter(parts.second as f64),
+        "millisecond" => getter(parts.millisecond as f64),
+        "weekday" => getter(parts.weekday as f64),
+        "timestamp" => getter(date.millis as f64),
+        "offset" => getter(date.offset as f64),
+        "toUTC" => Some(Value::native("date.toUTC", move |_, _| Ok(Value::Date(date.with_offset(0))))),
+        // withOffset("+05:30") or withOffset(330) shows the same instant in another zone.
+        "withOffset" => Some(Value::native("date.withOffset", move |_, args| {
+            let offset = match args.first() {
+                Some(Value::String(text)) => parse_offset(text).or_else(|err| throw("ValueError", err))?,
+                Some(Value::Number(minutes)) if minutes.fract() == 0.0 && minutes.abs() < 24.0 * 60.0 => *minutes as i32,
+                _ => return throw("TypeError", "date.withOffset: expected an offset like \"+05:30\" or minutes"),
+            };
+            Ok(Value::Date(date.with_offset(offset)))
+        })),
+        "add" => Some(Value::native("date.add", move |_, args| {
+            Ok(Value::Date(shift(date, args.first(), 1, "date.add")?))
+        })),
+        "subtract" => Some(Value::native("date.subtract", move |_, args| {
+            Ok(Value::Date(shift(date, args.first(), -1, "date.subtract")?))
+        })),
+        // Milliseconds from `other` to this date.
+        "diff" => Some(Value::native("date.diff", move |_, args| {
+            let other = date_arg(&args, "date.diff")?;
+            Ok(Value::Number((date.millis - other.millis) as f64))
+        })),
+        "compare" => Some(Value::native("date.compare", move |_, args| {
+            let other = date_arg(&args, "date.compare")?;
+            let ordering = match date.millis.cmp(&other.millis) {
+                Ordering::Less => -1.0,
+                Ordering::Equal => 0.0,
+                Ordering::Greater => 1.0,
+            };
+            Ok(Value::Number(ordering))
+        })),
+        "isBefore" => Some(Value::native("date.isBefore", move |_, args| {
+            Ok(Value::Boolean(date.millis < date_arg(&args, "date.isBefore")?.millis))
+        })),
+        "isAfter" => Some(Value::native("date.isAfter", move |_, args| {
+            Ok(Value::Boolean(date.millis > date_arg(&args, "date.isAfter")?.millis))
+        })),
+        "equals" => Some(Value::native("date.equals", move |_, args| {
+            Ok(Value::Boolean(date.millis == date_arg(&args, "date.equals")?.millis))
+        })),
+        "format" => Some(Value::native("date.format", move |_, args| {
+            let pattern = string_arg(&args, 0, "date.format")?;
+            Ok(Value::String(date.format(&pattern)))
+        })),
+        "toISOString" | "toString" => Some(Value::native(name, move |_, _| Ok(Value::String(date.to_iso())))),
+        _ => None,
+    }
+}
+
+fn parse(text: &str) -> EvalResult<DateTime> {
+    DateTime::parse(text).or_else(|err| throw("ValueError", err))
+}
+
+fn timestamp(millis: f64) -> EvalResult<i64> {
+    if !millis.is_finite() {
+        return throw("ValueError", "Date: timestamp must be a finite number");
+    }
+    // Twice the range still lets a duration span from one end to the other.
+    if millis.abs() > 2.0 * MAX_MILLIS as f64 {
+        return throw("RangeError", OUT_OF_RANGE);
+    }
+    Ok(millis as i64)
+}
+
+// Dates further than MAX_MILLIS from the epoch can't be represented.
+fn in_range(date: Option<DateTime>) -> EvalResult<DateTime> {
+    match date {
+        Some(date) => Ok(date),
+        None => throw("RangeError", OUT_OF_RANGE),
+    }
+}
+
+fn from_fields(args: &[Value]) -> EvalResult<DateTime> {
+    let mut fields = [0_i64, 1, 1, 0, 0, 0, 0];
+    for (index, arg) in args.iter().enumerate().take(fields.len()) {
+        match arg {
+            Value::Number(number) if number.is_finite() && number.fract() == 0.0 => fields[index] = *number as i64,
+            _ => return throw("TypeError", format!("Date: field {} must be a whole number", index + 1)),
+        }
+    }
+    in_range(DateTime::from_parts(fields, 0))
+}
+
+fn date_arg(args: &[Value], function: &str) -> EvalResult<DateTime> {
+    match args.first() {
+        Some(Value::Date(date)) => Ok(*date),
+        _ => throw("TypeError", format!("{}: argument 1 must be a date", function)),
+    }
+}
+
+// A duration is a number of milliseconds or an object such as `{months: 1, days: 2}`.
+fn shift(date: DateTime, duration: Option<&Value>, sign: i64, function: &str) -> EvalResult<DateTime> {
+    let object = match duration {
+        Some(Value::Number(millis)) => return in_range(date.add_millis(sign * timestamp(*millis)?)),
+        Some(Value::Object(object)) => object.borrow().clone(),
+        _ => return throw("TypeError", format!("{}: expected milliseconds or a duration object", function)),
+    };
+
+    let mut months = 0_i64;
+    let mut millis = 0_i64;
+    for (unit, amount) in object.entries() {
+        let amount = match amount {
+            Value::Number(amount) if amount.is_finite() => *amount,
+            _ => return throw("TypeError", format!("{}: '{}' must be a number", function, unit)),
+        };
+        match unit.as_str() {
+            "years" => months = months.saturating_add((amount * 12.0) as i64),
+            "months" => months = months.saturating_add(amount as i64),
+            "weeks" => millis = millis.saturating_add((amount * 604_800_000.0) as i64),
+            "days" => millis = millis.saturating_add((amount * 86_400_000.0) as i64),
+            "hours" => millis = millis.saturating_add((amount * 3_600_000.0) as i64),
+            "minutes" => millis = millis.saturating_add((amount * 60_000.0) as i64),
+            "seconds" => millis = millis.saturating_add((amount * 1000.0) as i64),
+            "milliseconds" => millis = millis.saturating_add(amount as i64),
+            other => return throw("ValueError", format!("{}: unknown duration unit '{}'", function, other)),
+        }
+    }
+
+    // Calendar units first, so Jan 31 + {months: 1, days: 1} is Mar 1.
+    let shifted = date.add_months(months.saturating_mul(sign)).and_then(|date| date.add_millis(millis.saturating_mul(sign)));
+    in_range(shifted)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::testing::logged;
+
+    #[test]
+    fn dates_parse_shift_compare_and_format() {
+        let output = logged(r#"
+            start = Date("2024-01-31T09:15:00+02:00")
+            due = start.add({months: 1, hours: 16})
+            epoch = Date(0)
+            fields = Date(2024, 2, 29)
+            try:
+                Date("31/01/2024")
+            catch (ValueError err):
+                invalid = err.message
+            log([
+                start.year(), start.month(), start.hour(), start.toUTC().hour(),
+                due.format("YYYY-MM-DD HH:mm Z"), due.isAfter(start), start.diff(epoch),
+                fields.equals(due.toUTC().subtract({hours: 23, minutes: 15})),
+                epoch.withOffset("-05:00"), invalid
+            ])
+        "#);
+
+        assert_eq!(
+            output,
+            r#"[2024, 1, 9, 7, "2024-03-01 01:15 +02:00", true, 1706685300000, true, 1969-12-31T19:00:00.000-05:00, "Invalid ISO-8601 date '31/01/2024'"]"#
+        );
+    }
+    #[test]
+    fn dates_out_of_range_throw() {
+        let output = logged(r#"
+            caught = 0
+            for (attempt in [() => Date(0).add({years: 300000000}), () => Date(9000000000000000000).add(9000000000000000000), () => Date(0).subtract(9000000000000000), () => Date(300000, 1, 1)]):
+                try:
+                    attempt()
+                catch (RangeError err):
+                    caught += 1
+                    message = err.message
+            log([caught, message, Date(8640000000000000).year()])
+        "#);
+        assert_eq!(output, r#"[4, "Date is outside the supported range", 275760]"#);
+    }
+}
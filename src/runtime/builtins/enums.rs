@@ -0,0 +1,34 @@
+use crate::runtime::values::{Enum, EnumMember, Value};
+use crate::utils::errors::{throw, EvalResult};
+
+use std::rc::Rc;
+
+// `Role.Admin` and `Role.values()`. Unknown names are an error rather than
+// `undefined`, so a misspelt member fails where it is used.
+pub fn property(namespace: &Rc<Enum>, name: &str) -> EvalResult<Value> {
+    if let Some(member) = namespace.member(name) {
+        return Ok(Value::EnumMember(member));
+    }
+
+    let namespace = Rc::clone(namespace);
+    match name {
+        "values" => Ok(Value::native("enum.values", move |_, _| {
+            Ok(Value::array(namespace.members.iter().cloned().map(Value::EnumMember).collect()))
+        })),
+        "name" => Ok(Value::String(namespace.name.clone())),
+        _ => throw("TypeError", format!("enum {} has no member '{}'", namespace.name, name)),
+    }
+}
+
+// `name`, `value` and `toString()` on a single member.
+pub fn member_property(member: &Rc<EnumMember>, name: &str) -> Value {
+    match name {
+        "name" => Value::String(member.name.clone()),
+        "value" => member.value.clone(),
+        "toString" => {
+            let text = member.name.clone();
+            Value::native("member.toString", move |_, _| Ok(Value::String(text.clone())))
+        }
+        _ => Value::Undefined,
+    }
+}
@@ -0,0 +1,193 @@
+use crate::runtime::builtins::{bool_arg, string_arg, text_arg};
+use crate::runtime::modules::Module;
+use crate::runtime::values::{Object, Value};
+use crate::utils::errors::{error_value, Control};
+
+use std::fs::{self, OpenOptions};
+use std::io::{self, Write};
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+pub fn module() -> Module {
+    Module::native("fs", vec![
+        ("read", Value::native("fs.read", |_, args| {
+            let path = string_arg(&args, 0, "fs.read")?;
+            fs::read_to_string(&path)
+                .map(Value::String)
+                .map_err(|err| io_error("fs.read", &path, err))
+        })),
+        ("write", Value::native("fs.write", |evaluator, args| {
+            let path = string_arg(&args, 0, "fs.write")?;
+            let data = text_arg(evaluator, &args, 1);
+            fs::write(&path, data).map_err(|err| io_error("fs.write", &path, err))?;
+            Ok(Value::Null)
+        })),
+        ("append", Value::native("fs.append", |evaluator, args| {
+            let path = string_arg(&args, 0, "fs.append")?;
+            let data = text_arg(evaluator, &args, 1);
+            OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(&path)
+                .and_then(|mut file| file.write_all(data.as_bytes()))
+                .map_err(|err| io_error("fs.append", &path, err))?;
+            Ok(Value::Null)
+        })),
+        ("exists", Value::native("fs.exists", |_, args| {
+            let path = string_arg(&args, 0, "fs.exists")?;
+            Ok(Value::Boolean(Path::new(&path).exists()))
+        })),
+        ("delete", Value::native("fs.delete", |_, args| {
+            let path = string_arg(&args, 0, "fs.delete")?;
+            fs::remove_file(&path).map_err(|err| io_error("fs.delete", &path, err))?;
+            Ok(Value::Null)
+        })),
+        ("list", Value::native("fs.list", |_, args| {
+            let path = string_arg(&args, 0, "fs.list")?;
+            let mut names = Vec::new();
+            for entry in fs::read_dir(&path).map_err(|err| io_error("fs.list", &path, err))? {
+                let entry = entry.map_err(|err| io_error("fs.list", &path, err))?;
+                names.push(entry.file_name().to_string_lossy().into_owned());
+            }
+            names.sort();
+            Ok(Value::array(names.into_iter().map(Value::String).collect()))
+        })),
+        ("mkdir", Value::native("fs.mkdir", |_, args| {
+            let path = string_arg(&args, 0, "fs.mkdir")?;
+            fs::create_dir_all(&path).map_err(|err| io_error("fs.mkdir", &path, err))?;
+            Ok(Value::Null)
+        })),
+        // fs.rmdir(path) only removes empty directories; fs.rmdir(path, true) removes contents too.
+        ("rmdir", Value::native("fs.rmdir", |_, args| {
+            let path = string_arg(&args, 0, "fs.rmdir")?;
+            let result = if bool_arg(&args, 1, false) {
+                fs::remove_dir_all(&path)
+            } else {
+                fs::remove_dir(&path)
+            };
+            result.map_err(|err| io_error("fs.rmdir", &path, err))?;
+            Ok(Value::Null)
+        })),
+        ("copyFile", Value::native("fs.copyFile", |_, args| {
+            let src = string_arg(&args, 0, "fs.copyFile")?;
+            let dest = string_arg(&args, 1, "fs.copyFile")?;
+            fs::copy(&src, &dest).map_err(|err| io_error("fs.copyFile", &src, err))?;
+            Ok(Value::Null)
+        })),
+        ("move", Value::native("fs.move", |_, args| {
+            let from = string_arg(&args, 0, "fs.move")?;
+            let to = string_arg(&args, 1, "fs.move")?;
+            move_path(&from, &to).map_err(|err| io_error("fs.move", &from, err))?;
+            Ok(Value::Null)
+        })),
+        ("rename", Value::native("fs.rename", |_, args| {
+            let from = string_arg(&args, 0, "fs.rename")?;
+            let to = string_arg(&args, 1, "fs.rename")?;
+            fs::rename(&from, &to).map_err(|err| io_error("fs.rename", &from, err))?;
+            Ok(Value::Null)
+        })),
+        ("stat", Value::native("fs.stat", |_, args| {
+            let path = string_arg(&args, 0, "fs.stat")?;
+            let metadata = fs::metadata(&path).map_err(|err| io_error("fs.stat", &path, err))?;
+
+            let mtime = metadata
+                .modified()
+                .ok()
+                .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+                .map(|elapsed| elapsed.as_millis() as f64)
+                .unwrap_or(0.0);
+
+            let mut stat = Object::new();
+            stat.set("size", Value::Number(metadata.len() as f64));
+            stat.set("mtime", Value::Number(mtime));
+            stat.set("isFile", Value::Boolean(metadata.is_file()));
+            stat.set("isDir", Value::Boolean(metadata.is_dir()));
+            stat.set("permissions", Value::String(permissions(&metadata)));
+            Ok(Value::object(stat))
+        })),
+        ("clear", Value::native("fs.clear", |_, args| {
+            let path = string_arg(&args, 0, "fs.clear")?;
+            OpenOptions::new()
+                .write(true)
+                .truncate(true)
+                .open(&path)
+                .map_err(|err| io_error("fs.clear", &path, err))?;
+            Ok(Value::Null)
+        })),
+        ("isDir", Value::native("fs.isDir", |_, args| {
+            let path = string_arg(&args, 0, "fs.isDir")?;
+            Ok(Value::Boolean(Path::new(&path).is_dir()))
+        })),
+    ])
+}
+
+// `rename` can't cross filesystems, so files fall back to copy-then-delete.
+fn move_path(from: &str, to: &str) -> io::Result<()> {
+    match fs::rename(from, to) {
+        Err(_) if Path::new(from).is_file() => {
+            fs::copy(from, to)?;
+            fs::remove_file(from)
+        }
+        result => result,
+    }
+}
+
+#[cfg(unix)]
+fn permissions(metadata: &fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    format!("{:o}", metadata.permissions().mode() & 0o777)
+}
+
+#[cfg(not(unix))]
+fn permissions(metadata: &fs::Metadata) -> String {
+    if metadata.permissions().readonly() { "444" } else { "666" }.to_string()
+}
+
+// IO failures surface as `IOError` objects carrying the OS error kind in `code`.
+fn io_error(function: &str, path: &str, err: io::Error) -> Control {
+    let error = error_value("IOError", format!("{}: {}: {}", function, path, err));
+    if let Value::Object(object) = &error {
+        object.borrow_mut().set("code", Value::String(format!("{:?}", err.kind())));
+    }
+    Control::Throw(error)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::testing::logged;
+
+    #[test]
+    fn writes_copies_and_stats_files() {
+        let dir = std::env::temp_dir().join(format!("genesix-fs-{}", std::process::id()));
+
+        // Each backend gets a fresh directory, so the script removes its own.
+        let output = logged(&format!(r#"
+            get fs from "fs"
+            root = "{}"
+            fs.mkdir(root)
+            fs.write(`${{root}}/a.txt`, "hello")
+            fs.append(`${{root}}/a.txt`, " world")
+            fs.copyFile(`${{root}}/a.txt`, `${{root}}/b.txt`)
+            fs.move(`${{root}}/b.txt`, `${{root}}/c.txt`)
+            info = fs.stat(`${{root}}/c.txt`)
+            log([fs.read(`${{root}}/c.txt`), info.size, info.isFile, info.isDir, fs.list(root)])
+            fs.rmdir(root, true)
+            log(fs.exists(root))
+        "#, dir.display()));
+
+        assert_eq!(output, "[\"hello world\", 11, true, false, [\"a.txt\", \"c.txt\"]]\nfalse");
+    }
+
+    #[test]
+    fn io_failures_are_catchable() {
+        let output = logged(r#"
+            get fs from "fs"
+            try:
+                fs.read("/definitely/not/here.txt")
+            catch (IOError err):
+                log(err.code)
+        "#);
+
+        assert_eq!(output, "NotFound");
+    }
+}
@@ -0,0 +1,641 @@
+use crate::runtime::builtins::http_server;
+use crate::runtime::builtins::string_arg;
+use crate::runtime::json;
+use crate::runtime::evaluator::Evaluator;
+use crate::runtime::modules::Module;
+use crate::runtime::promise::Promise;
+use crate::runtime::values::{Object, Value};
+use crate::utils::errors::{error_value, throw, EvalResult};
+
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{TcpStream, ToSocketAddrs};
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+const DEFAULT_TIMEOUT_MS: u64 = 30_000;
+// Larger response bodies fail rather than filling memory with whatever the server sends.
+const MAX_RESPONSE_BYTES: usize = 64 * 1024 * 1024;
+// The longest line and the most header lines either side of a connection accepts.
+pub(crate) const MAX_LINE_BYTES: usize = 8 * 1024;
+pub(crate) const MAX_HEADERS: usize = 100;
+
+pub fn module() -> Module {
+    Module::native("http", vec![
+        ("get", Value::native("http.get", |evaluator, args| {
+            let url = string_arg(&args, 0, "http.get")?;
+            let request = Request::from_options("GET", url, None, args.get(1))?;
+            fetch(evaluator, request)
+        })),
+        ("delete", Value::native("http.delete", |evaluator, args| {
+            let url = string_arg(&args, 0, "http.delete")?;
+            let request = Request::from_options("DELETE", url, None, args.get(1))?;
+            fetch(evaluator, request)
+        })),
+        ("post", Value::native("http.post", |evaluator, args| {
+            let url = string_arg(&args, 0, "http.post")?;
+            let request = Request::from_options("POST", url, args.get(1), args.get(2))?;
+            fetch(evaluator, request)
+        })),
+        ("put", Value::native("http.put", |evaluator, args| {
+            let url = string_arg(&args, 0, "http.put")?;
+            let request = Request::from_options("PUT", url, args.get(1), args.get(2))?;
+            fetch(evaluator, request)
+        })),
+        // http.request({method, url, headers, body, timeout})
+        ("request", Value::native("http.request", |evaluator, args| {
+            let options = args.first();
+            let url = match option(options, "url") {
+                Some(Value::String(url)) => url,
+                _ => return throw("TypeError", "http.request: options.url must be a string"),
+            };
+            let method = match option(options, "method") {
+                Some(Value::String(method)) => method.to_uppercase(),
+                None | Some(Value::Undefined) => "GET".to_string(),
+                Some(_) => return throw("TypeError", "http.request: options.method must be a string"),
+            };
+            let body = option(options, "body");
+            let request = Request::from_options(&method, url, body.as_ref(), options)?;
+            fetch(evaluator, request)
+        })),
+        ("router", Value::native("http.router", |_, _| Ok(http_server::router()))),
+        ("createServer", Value::native("http.createServer", |_, args| {
+            Ok(http_server::create_server(args.into_iter().next().unwrap_or(Value::Undefined)))
+        })),
+        // http.serve(port, handler, host?, onListen?) keeps the program running until the server is closed.
+        ("serve", Value::native("http.serve", |evaluator, mut args| {
+            let handler = if args.len() > 1 { args.remove(1) } else { Value::Undefined };
+            let server = http_server::create_server(handler);
+            let listen = match &server {
+                Value::Object(object) => object.borrow().get("listen").unwrap_or(Value::Undefined),
+                _ => Value::Undefined,
+            };
+            evaluator.call_function(listen, args)
+        })),
+    ])
+}
+
+// Sends the request on a worker thread and returns a promise of the response.
+fn fetch(evaluator: &mut Evaluator, request: Request) -> EvalResult<Value> {
+    let promise = Promise::new();
+    let pending = Rc::clone(&promise);
+    evaluator.event_loop().spawn_io(move || send(&request), move |evaluator, result| {
+        match result {
+            Ok(Ok(response)) => evaluator.event_loop().resolve(&pending, response_value(response)),
+            Ok(Err(err)) => evaluator.event_loop().reject(&pending, error_value(err.kind, err.message)),
+            Err(panicked) => evaluator.event_loop().reject(&pending, panicked),
+        }
+        Ok(())
+    });
+    Ok(Value::Promise(promise))
+}
+
+// Everything a request needs, as plain data so it can be sent from any thread.
+#[derive(Debug, Clone)]
+pub struct Request {
+    pub method: String,
+    pub url: String,
+    pub headers: Vec<(String, String)>,
+    pub body: Option<String>,
+    pub timeout: Duration,
+}
+
+#[derive(Debug, Clone)]
+pub struct Response {
+    pub url: String,
+    pub status: u16,
+    pub status_text: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+}
+
+#[derive(Debug, Clone)]
+pub struct HttpError {
+    pub kind: &'static str,
+    pub message: String,
+}
+
+impl HttpError {
+    fn new(kind: &'static str, message: impl Into<String>) -> Self {
+        Self { kind, message: message.into() }
+    }
+}
+
+impl Request {
+    // Everything that ends up in the request head is checked here, so a
+    // line break can't slip in extra headers or a second request.
+    fn from_options(method: &str, url: String, body: Option<&Value>, options: Option<&Value>) -> EvalResult<Self> {
+        if !is_token(method) {
+            return throw("TypeError", format!("http: invalid method {:?}", method));
+        }
+        if url.contains(['\r', '\n']) {
+            return throw("TypeError", "http: the URL must not contain line breaks");
+        }
+
+        let mut headers = Vec::new();
+        if let Some(Value::Object(object)) = option(options, "headers") {
+            for (name, value) in object.borrow().entries() {
+                let value = match value {
+                    Value::String(value) => value.clone(),
+                    other => json::stringify(other),
+                };
+                check_header(name, &value, "http")?;
+                headers.push((name.clone(), value));
+            }
+        }
+
+        let timeout = match option(options, "timeout") {
+            Some(Value::Number(ms)) if ms >= 1.0 => Duration::from_millis(ms as u64),
+            None | Some(Value::Undefined) => Duration::from_millis(DEFAULT_TIMEOUT_MS),
+            Some(_) => return throw("TypeError", "http: options.timeout must be a positive number of milliseconds"),
+        };
+
+        // Strings are sent as-is; anything else is encoded as JSON.
+        let body = match body {
+            None | Some(Value::Null) | Some(Value::Undefined) => None,
+            Some(Value::String(text)) => Some(text.clone()),
+            Some(other) => {
+                if !has_header(&headers, "content-type") {
+                    headers.push(("Content-Type".to_string(), "application/json".to_string()));
+                }
+                Some(json::stringify(other))
+            }
+        };
+
+        Ok(Self {
+            method: method.to_string(),
+            url,
+            headers,
+            body,
+            timeout,
+        })
+    }
+}
+
+fn option(options: Option<&Value>, name: &str) -> Option<Value> {
+    match options {
+        Some(Value::Object(object)) => object.borrow().get(name),
+        _ => None,
+    }
+}
+
+// A method or header name: letters, digits and a few symbols, per RFC 9110.
+pub(crate) fn is_token(text: &str) -> bool {
+    !text.is_empty() && text.bytes().all(|byte| byte.is_ascii_alphanumeric() || b"!#$%&'*+-.^_`|~".contains(&byte))
+}
+
+// Throws a TypeError unless the header can be written as one line of a message head.
+pub(crate) fn check_header(name: &str, value: &str, function: &str) -> EvalResult<()> {
+    if !is_token(name) {
+        return throw("TypeError", format!("{}: invalid header name {:?}", function, name));
+    }
+    if value.contains(['\r', '\n']) {
+        return throw("TypeError", format!("{}: the value of header '{}' contains a line break", function, name));
+    }
+    Ok(())
+}
+
+fn has_header(headers: &[(String, String)], name: &str) -> bool {
+    headers.iter().any(|(key, _)| key.eq_ignore_ascii_case(name))
+}
+
+struct Url {
+    host: String,
+    port: u16,
+    target: String,
+}
+
+fn parse_url(url: &str) -> Result<Url, HttpError> {
+    let rest = match url.split_once("://") {
+        Some(("http", rest)) => rest,
+        Some((scheme, _)) => {
+            return Err(HttpError::new("ValueError", format!("Unsupported URL scheme \"{}\" in {}", scheme, url)));
+        }
+        None => return Err(HttpError::new("ValueError", format!("Invalid URL: {}", url))),
+    };
+
+    let (authority, target) = match rest.find(['/', '?']) {
+        Some(index) => (&rest[..index], rest[index..].to_string()),
+        None => (rest, "/".to_string()),
+    };
+    let target = if target.starts_with('?') { format!("/{}", target) } else { target };
+
+    let (host, port) = match authority.rsplit_once(':') {
+        Some((host, port)) => {
+            let port = port
+                .parse()
+                .map_err(|_| HttpError::new("ValueError", format!("Invalid port in URL: {}", url)))?;
+            (host, port)
+        }
+        None => (authority, 80),
+    };
+
+    if host.is_empty() {
+        return Err(HttpError::new("ValueError", format!("Missing host in URL: {}", url)));
+    }
+
+    Ok(Url {
+        host: host.to_string(),
+        port,
+        target,
+    })
+}
+
+// The whole exchange, from resolving the host to the last byte of the body,
+// must finish within `request.timeout`.
+pub fn send(request: &Request) -> Result<Response, HttpError> {
+    let deadline = Instant::now() + request.timeout;
+    let url = parse_url(&request.url)?;
+
+    let address = (url.host.as_str(), url.port)
+        .to_socket_addrs()
+        .map_err(|err| HttpError::new("NetworkError", format!("Could not resolve {}: {}", url.host, err)))?
+        .next()
+        .ok_or_else(|| HttpError::new("NetworkError", format!("Could not resolve {}", url.host)))?;
+
+    let stream = remaining(deadline)
+        .and_then(|timeout| TcpStream::connect_timeout(&address, timeout))
+        .map_err(|err| io_failure(&request.url, err))?;
+
+    let mut head = format!("{} {} HTTP/1.1\r\n", request.method, url.target);
+    head.push_str(&format!("Host: {}\r\n", url.host));
+    head.push_str("Connection: close\r\n");
+    for (name, value) in &request.headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    if !has_header(&request.headers, "user-agent") {
+        head.push_str("User-Agent: genesix\r\n");
+    }
+    let body = request.body.as_deref().unwrap_or("");
+    if request.body.is_some() || matches!(request.method.as_str(), "POST" | "PUT") {
+        head.push_str(&format!("Content-Length: {}\r\n", body.len()));
+    }
+    head.push_str("\r\n");
+
+    let mut writer = &stream;
+    remaining(deadline)
+        .and_then(|timeout| stream.set_write_timeout(Some(timeout)))
+        .and_then(|_| writer.write_all(head.as_bytes()))
+        .and_then(|_| writer.write_all(body.as_bytes()))
+        .map_err(|err| io_failure(&request.url, err))?;
+
+    let mut reader = BufReader::new(DeadlineReader { stream: &stream, deadline });
+    let (status, status_text) = read_status_line(&mut reader, &request.url)?;
+    let headers = read_headers(&mut reader).map_err(|err| io_failure(&request.url, err))?;
+
+    let has_body = request.method != "HEAD" && !matches!(status, 100..=199 | 204 | 304);
+    let body = if !has_body {
+        Vec::new()
+    } else if header_value(&headers, "transfer-encoding").is_some_and(|value| value.eq_ignore_ascii_case("chunked")) {
+        read_chunked(&mut reader, MAX_RESPONSE_BYTES).map_err(|err| io_failure(&request.url, err))?
+    } else if let Some(length) = header_value(&headers, "content-length").and_then(|value| value.parse().ok()) {
+        if length > MAX_RESPONSE_BYTES {
+            return Err(io_failure(&request.url, body_too_large()));
+        }
+        let mut body = vec![0; length];
+        reader.read_exact(&mut body).map_err(|err| io_failure(&request.url, err))?;
+        body
+    } else {
+        let mut body = Vec::new();
+        (&mut reader)
+            .take(MAX_RESPONSE_BYTES as u64 + 1)
+            .read_to_end(&mut body)
+            .map_err(|err| io_failure(&request.url, err))?;
+        if body.len() > MAX_RESPONSE_BYTES {
+            return Err(io_failure(&request.url, body_too_large()));
+        }
+        body
+    };
+
+    Ok(Response {
+        url: request.url.clone(),
+        status,
+        status_text,
+        headers,
+        body: String::from_utf8_lossy(&body).into_owned(),
+    })
+}
+
+// Time left before `deadline`, or a timeout error once it has passed.
+fn remaining(deadline: Instant) -> io::Result<Duration> {
+    let left = deadline.saturating_duration_since(Instant::now());
+    if left.is_zero() {
+        return Err(io::ErrorKind::TimedOut.into());
+    }
+    Ok(left)
+}
+
+// Reads the response, giving each read only the time the request has left,
+// so a server trickling bytes can't stretch it past its timeout.
+struct DeadlineReader<'a> {
+    stream: &'a TcpStream,
+    deadline: Instant,
+}
+
+impl Read for DeadlineReader<'_> {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.stream.set_read_timeout(Some(remaining(self.deadline)?))?;
+        let mut stream = self.stream;
+        stream.read(buf)
+    }
+}
+
+fn io_failure(url: &str, err: io::Error) -> HttpError {
+    match err.kind() {
+        io::ErrorKind::TimedOut | io::ErrorKind::WouldBlock => {
+            HttpError::new("TimeoutError", format!("Request to {} timed out", url))
+        }
+        io::ErrorKind::FileTooLarge => HttpError::new("HttpError", format!("Response from {} is too large: {}", url, err)),
+        io::ErrorKind::InvalidData => HttpError::new("HttpError", format!("Malformed response from {}: {}", url, err)),
+        _ => HttpError::new("NetworkError", format!("Request to {} failed: {}", url, err)),
+    }
+}
+
+fn body_too_large() -> io::Error {
+    io::Error::new(io::ErrorKind::FileTooLarge, format!("the body is over {} bytes", MAX_RESPONSE_BYTES))
+}
+
+// Reads up to and including the next newline, failing with `FileTooLarge`
+// rather than buffering a line longer than MAX_LINE_BYTES.
+pub(crate) fn read_bounded_line(reader: &mut impl BufRead) -> io::Result<String> {
+    let mut line = String::new();
+    reader.take(MAX_LINE_BYTES as u64 + 1).read_line(&mut line)?;
+    if line.len() > MAX_LINE_BYTES {
+        let message = format!("a line is over {} bytes", MAX_LINE_BYTES);
+        return Err(io::Error::new(io::ErrorKind::FileTooLarge, message));
+    }
+    Ok(line)
+}
+
+fn read_status_line(reader: &mut impl BufRead, url: &str) -> Result<(u16, String), HttpError> {
+    let line = read_bounded_line(reader).map_err(|err| io_failure(url, err))?;
+
+    let mut parts = line.trim_end().splitn(3, ' ');
+    let version = parts.next().unwrap_or("");
+    let status = parts.next().and_then(|code| code.parse().ok());
+    match status {
+        Some(status) if version.starts_with("HTTP/") => Ok((status, parts.next().unwrap_or("").to_string())),
+        _ => Err(HttpError::new("HttpError", format!("Malformed response from {}", url))),
+    }
+}
+
+// Fails with `FileTooLarge` past MAX_HEADERS lines or a line over MAX_LINE_BYTES.
+pub(crate) fn read_headers(reader: &mut impl BufRead) -> io::Result<Vec<(String, String)>> {
+    let mut headers = Vec::new();
+    for _ in 0..=MAX_HEADERS {
+        let line = read_bounded_line(reader)?;
+        let line = line.trim_end();
+        if line.is_empty() {
+            return Ok(headers);
+        }
+        if let Some((name, value)) = line.split_once(':') {
+            headers.push((name.trim().to_lowercase(), value.trim().to_string()));
+        }
+    }
+    let message = format!("there are more than {} headers", MAX_HEADERS);
+    Err(io::Error::new(io::ErrorKind::FileTooLarge, message))
+}
+
+pub(crate) fn header_value<'a>(headers: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    headers
+        .iter()
+        .find(|(key, _)| key.eq_ignore_ascii_case(name))
+        .map(|(_, value)| value.as_str())
+}
+
+// Fails with `FileTooLarge` once the chunks add up to more than `limit` bytes.
+fn read_chunked(reader: &mut impl BufRead, limit: usize) -> io::Result<Vec<u8>> {
+    let mut body = Vec::new();
+    loop {
+        let size_line = read_bounded_line(reader)?;
+        let size_text = size_line.trim().split(';').next().unwrap_or("");
+        let size = usize::from_str_radix(size_text, 16)
+            .map_err(|_| io::Error::new(io::ErrorKind::InvalidData, "bad chunk size"))?;
+        if size == 0 {
+            // Trailers, if any, end with a blank line just like headers.
+            read_headers(reader)?;
+            return Ok(body);
+        }
+        let start = body.len();
+        if size > limit - start {
+            return Err(body_too_large());
+        }
+        body.resize(start + size, 0);
+        reader.read_exact(&mut body[start..])?;
+        read_bounded_line(reader)?;
+    }
+}
+
+pub fn response_value(response: Response) -> Value {
+    let mut headers = Object::new();
+    for (name, value) in &response.headers {
+        let combined = match headers.get(name) {
+            Some(Value::String(existing)) => format!("{}, {}", existing, value),
+            _ => value.clone(),
+        };
+        headers.set(name, Value::String(combined));
+    }
+
+    let text = response.body.clone();
+    let body = response.body;
+
+    let mut object = Object::new();
+    object.set("url", Value::String(response.url));
+    object.set("status", Value::Number(response.status as f64));
+    object.set("statusText", Value::String(response.status_text));
+    object.set("ok", Value::Boolean((200..300).contains(&response.status)));
+    object.set("headers", Value::object(headers));
+    object.set("text", Value::native("response.text", move |_, _| Ok(Value::String(text.clone()))));
+    object.set("json", Value::native("response.json", move |_, _| {
+        json::parse(&body).or_else(|err| throw("JSONError", err))
+    }));
+    Value::object(object)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::evaluator::Backend;
+    use crate::runtime::testing::logged;
+
+    use std::io::{BufReader, Read, Write};
+    use std::net::TcpListener;
+    use std::thread;
+    use std::time::Duration;
+
+    // Answers each connection with a JSON echo of what it received; `/slow` stalls past any test timeout,
+    // `/trickle` sends its body a byte at a time, `/huge` promises a body too large to accept and
+    // `/long-header` and `/many-headers` send a head that never ends.
+    fn spawn_echo_server(connections: usize) -> String {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let address = listener.local_addr().unwrap();
+
+        thread::spawn(move || {
+            for stream in listener.incoming().take(connections) {
+                let stream = stream.unwrap();
+                let mut reader = BufReader::new(&stream);
+                let mut request_line = String::new();
+                std::io::BufRead::read_line(&mut reader, &mut request_line).unwrap();
+                let headers = super::read_headers(&mut reader).unwrap();
+                let length = super::header_value(&headers, "content-length")
+                    .map(|value| value.parse().unwrap())
+                    .unwrap_or(0);
+                let mut body = vec![0; length];
+                reader.read_exact(&mut body).unwrap();
+
+                let mut parts = request_line.split_whitespace();
+                let method = parts.next().unwrap().to_string();
+                let path = parts.next().unwrap().to_string();
+                let mut writer = &stream;
+                match path.as_str() {
+                    "/slow" => {
+                        thread::sleep(Duration::from_millis(500));
+                        continue;
+                    }
+                    "/trickle" => {
+                        // The client may hang up part way, so write failures are expected.
+                        let _ = write!(writer, "HTTP/1.1 200 OK\r\nContent-Length: 10\r\n\r\n");
+                        for _ in 0..10 {
+                            thread::sleep(Duration::from_millis(30));
+                            let _ = writer.write_all(b".");
+                        }
+                        continue;
+                    }
+                    "/huge" => {
+                        write!(writer, "HTTP/1.1 200 OK\r\nContent-Length: 1000000000000\r\n\r\n").unwrap();
+                        continue;
+                    }
+                    "/long-header" => {
+                        let _ = write!(writer, "HTTP/1.1 200 OK\r\nX-Filler: ");
+                        let _ = writer.write_all(&[b'a'; 64 * 1024]);
+                        continue;
+                    }
+                    "/many-headers" => {
+                        let _ = write!(writer, "HTTP/1.1 200 OK\r\n");
+                        for _ in 0..1000 {
+                            let _ = write!(writer, "X-Filler: a\r\n");
+                        }
+                        continue;
+                    }
+                    _ => {}
+                }
+
+                let echo = format!(
+                    r#"{{"method":"{}","path":"{}","token":"{}","body":{:?}}}"#,
+                    method,
+                    path,
+                    super::header_value(&headers, "x-token").unwrap_or(""),
+                    String::from_utf8(body).unwrap()
+                );
+                let chunked = format!("{:x}\r\n{}\r\n0\r\n\r\n", echo.len(), echo);
+                write!(
+                    writer,
+                    "HTTP/1.1 201 Created\r\nContent-Type: application/json\r\nTransfer-Encoding: chunked\r\n\r\n{}",
+                    chunked
+                )
+                .unwrap();
+            }
+        });
+
+        format!("http://{}", address)
+    }
+
+    #[test]
+    fn sends_bodies_and_headers_and_parses_json() {
+        let base = spawn_echo_server(Backend::ALL.len());
+
+        let output = logged(&format!(r#"
+            get http from "http"
+            response = await http.post("{base}/users?page=2", {{name: "Joe"}}, {{headers: {{"X-Token": "secret"}}}})
+            log([response.status, response.headers["content-type"], response.json()])
+        "#));
+
+        assert_eq!(
+            output,
+            r#"[201, "application/json", {method: "POST", path: "/users?page=2", token: "secret", body: "{\"name\":\"Joe\"}"}]"#
+        );
+    }
+
+    #[test]
+    fn slow_responses_raise_timeout_errors() {
+        let base = spawn_echo_server(Backend::ALL.len());
+
+        let output = logged(&format!(r#"
+            get http from "http"
+            try:
+                await http.request({{url: "{base}/slow", timeout: 50}})
+            catch (TimeoutError err):
+                log(err.message)
+        "#));
+
+        assert_eq!(output, format!("Request to {base}/slow timed out"));
+    }
+
+    #[test]
+    fn line_breaks_cannot_reach_the_request_head() {
+        let output = logged(r#"
+            get http from "http"
+            attempts = [
+                () => http.request({url: "http://localhost:1/", method: "GET /x HTTP/1.1\nHost: evil\n\nGET"}),
+                () => http.get("http://localhost:1/", {headers: {"X-Token": "a\nX-Admin: yes"}}),
+                () => http.get("http://localhost:1/", {headers: {"X Token": "a"}}),
+                () => http.get("http://localhost:1/a\nX-Admin: yes"),
+            ]
+            for (attempt in attempts):
+                try:
+                    attempt()
+                catch (TypeError err):
+                    log(err.message)
+        "#);
+
+        assert_eq!(
+            output,
+            "http: invalid method \"GET /X HTTP/1.1\\nHOST: EVIL\\n\\nGET\"\n\
+             http: the value of header 'X-Token' contains a line break\n\
+             http: invalid header name \"X Token\"\n\
+             http: the URL must not contain line breaks"
+        );
+    }
+
+    #[test]
+    fn trickling_and_oversized_responses_fail() {
+        let base = spawn_echo_server(2 * Backend::ALL.len());
+
+        let output = logged(&format!(r#"
+            get http from "http"
+            try:
+                await http.request({{url: "{base}/trickle", timeout: 100}})
+            catch (TimeoutError err):
+                log(err.message)
+            try:
+                await http.get("{base}/huge")
+            catch (HttpError err):
+                log(err.message)
+        "#));
+
+        assert_eq!(
+            output,
+            format!("Request to {base}/trickle timed out\nResponse from {base}/huge is too large: the body is over 67108864 bytes")
+        );
+    }
+
+    #[test]
+    fn endless_response_heads_fail() {
+        let base = spawn_echo_server(2 * Backend::ALL.len());
+
+        let output = logged(&format!(r#"
+            get http from "http"
+            for (path in ["long-header", "many-headers"]):
+                try:
+                    await http.get("{base}/" + path)
+                catch (HttpError err):
+                    log(err.message)
+        "#));
+
+        assert_eq!(
+            output,
+            format!(
+                "Response from {base}/long-header is too large: a line is over 8192 bytes\n\
+                 Response from {base}/many-headers is too large: there are more than 100 headers"
+            )
+        );
+    }
+}
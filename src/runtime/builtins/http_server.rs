@@ -0,0 +1,663 @@
+use crate::runtime::builtins::http::{check_header, header_value, read_bounded_line, read_headers};
+use crate::runtime::builtins::promise::to_promise;
+use crate::runtime::builtins::{number_arg, string_arg};
+use crate::runtime::evaluator::Evaluator;
+use crate::runtime::event_loop::Poll;
+use crate::runtime::json;
+use crate::runtime::values::{Object, Value};
+use crate::utils::errors::{throw, Control, EvalResult};
+
+use std::cell::{Cell, RefCell};
+use std::io::{self, BufReader, Read, Write};
+use std::mem;
+use std::net::{TcpListener, TcpStream};
+use std::rc::Rc;
+use std::time::Duration;
+
+// Only this machine can connect unless `listen` is given another host, such as "0.0.0.0".
+const DEFAULT_HOST: &str = "127.0.0.1";
+const READ_TIMEOUT: Duration = Duration::from_secs(10);
+// Requests announcing a bigger body are refused with 413 before any of it is read.
+const MAX_BODY_BYTES: usize = 10 * 1024 * 1024;
+// Connections past this many wait in the listen backlog until one is answered.
+const MAX_CONNECTIONS: usize = 64;
+
+struct Route {
+    method: String,
+    pattern: Vec<String>,
+    handler: Value,
+}
+
+// app = http.router(); app.get("/users/:id", showUser)
+pub fn router() -> Value {
+    let routes: Rc<RefCell<Vec<Route>>> = Rc::new(RefCell::new(Vec::new()));
+    let mut app = Object::new();
+
+    for method in ["get", "post", "put", "delete", "patch", "all"] {
+        let routes = Rc::clone(&routes);
+        let name = format!("router.{}", method);
+        app.set(method, Value::native(&name.clone(), move |_, args| {
+            let path = string_arg(&args, 0, &name)?;
+            let Some(handler) = args.get(1).cloned() else {
+                return throw("TypeError", format!("{}: missing handler", name));
+            };
+            routes.borrow_mut().push(Route {
+                method: method.to_uppercase(),
+                pattern: segments(&path),
+                handler,
+            });
+            Ok(Value::Null)
+        }));
+    }
+
+    // Lets a router be passed anywhere a `(req, res)` handler is expected.
+    app.set("handle", Value::native("router.handle", move |evaluator, args| {
+        let request = args.first().cloned().unwrap_or(Value::Undefined);
+        let response = args.get(1).cloned().unwrap_or(Value::Undefined);
+        let (method, path) = match &request {
+            Value::Object(object) => {
+                let object = object.borrow();
+                (object.get("method"), object.get("path"))
+            }
+            _ => (None, None),
+        };
+        let (Some(Value::String(method)), Some(Value::String(path))) = (method, path) else {
+            return throw("TypeError", "router.handle: expected a request object");
+        };
+
+        let matched = routes.borrow().iter().find_map(|route| {
+            let method_matches = route.method == "ALL" || route.method == method;
+            let params = if method_matches { match_route(&route.pattern, &path) } else { None };
+            params.map(|params| (route.handler.clone(), params))
+        });
+
+        match matched {
+            Some((handler, params)) => {
+                if let Value::Object(object) = &request {
+                    object.borrow_mut().set("params", Value::object(params));
+                }
+                evaluator.call_function(handler, vec![request, response])
+            }
+            None => {
+                let status = member(&response, "status")?;
+                let response = evaluator.call_function(status, vec![Value::Number(404.0)])?;
+                let text = member(&response, "text")?;
+                evaluator.call_function(text, vec![Value::String("Not Found".to_string())])
+            }
+        }
+    }));
+
+    Value::object(app)
+}
+
+// server = http.createServer(handler); server.listen(8080, "0.0.0.0"); server.close()
+pub fn create_server(handler: Value) -> Value {
+    let closing = Rc::new(Cell::new(false));
+    let server = Rc::new(RefCell::new(Object::new()));
+    let weak_server = Rc::downgrade(&server);
+
+    let stop = Rc::clone(&closing);
+    server.borrow_mut().set("close", Value::native("server.close", move |_, _| {
+        stop.set(true);
+        Ok(Value::Null)
+    }));
+
+    server.borrow_mut().set("port", Value::Null);
+    server.borrow_mut().set("listen", Value::native("server.listen", move |evaluator, args| {
+        // listen(port, host?, onListen?)
+        let port = number_arg(&args, 0, "server.listen")?;
+        if port.fract() != 0.0 || !(0.0..=65535.0).contains(&port) {
+            return throw("RangeError", format!("server.listen: port must be a whole number from 0 to 65535, got {}", port));
+        }
+        let port = port as u16;
+        let (host, on_listen) = match args.get(1) {
+            Some(Value::String(host)) => (host.clone(), args.get(2).cloned()),
+            other => (DEFAULT_HOST.to_string(), other.cloned()),
+        };
+        let listener = match TcpListener::bind((host.as_str(), port)) {
+            Ok(listener) => listener,
+            Err(err) => return throw("NetworkError", format!("server.listen: {}:{}: {}", host, port, err)),
+        };
+        let port = listener.local_addr().map(|address| address.port()).unwrap_or(port);
+
+        let server = weak_server.upgrade().map(Value::Object).unwrap_or(Value::Null);
+        if let Value::Object(object) = &server {
+            object.borrow_mut().set("port", Value::Number(port as f64));
+        }
+        closing.set(false);
+
+        if let Some(on_listen) = on_listen {
+            evaluator.call_function(on_listen, vec![server.clone()])?;
+        }
+
+        if let Err(err) = listener.set_nonblocking(true) {
+            return throw("NetworkError", format!("server.listen: {}", err));
+        }
+
+        // The event loop polls for connections; each request is read and its
+        // response written on a worker thread, so a slow client holds up no
+        // one. `close()` stops accepting but lets responses under way finish.
+        let handler = handler.clone();
+        let closing = Rc::clone(&closing);
+        let open = Rc::new(Cell::new(0));
+        evaluator.event_loop().add_poller(Box::new(move |evaluator| {
+            if closing.get() {
+                return Ok(Poll::Done);
+            }
+            if open.get() >= MAX_CONNECTIONS {
+                return Ok(Poll::Idle);
+            }
+            match listener.accept() {
+                Ok((stream, _)) => {
+                    let handler = handler.clone();
+                    let slot = ConnectionSlot::take(&open);
+                    evaluator.event_loop().spawn_io(move || {
+                        stream.set_nonblocking(false).ok();
+                        stream.set_read_timeout(Some(READ_TIMEOUT)).ok();
+                        (read_request(&stream), stream)
+                    }, move |evaluator, read| match read {
+                        Ok((request, stream)) => serve_request(evaluator, &handler, request, Connection { stream, slot }),
+                        // The stream went down with the worker, so there is no one to answer.
+                        Err(_) => Ok(()),
+                    });
+                    Ok(Poll::Progress)
+                }
+                Err(_) => Ok(Poll::Idle),
+            }
+        }));
+
+        Ok(server)
+    }));
+
+    Value::Object(server)
+}
+
+// Counts towards MAX_CONNECTIONS from being accepted until its response is written.
+struct ConnectionSlot(Rc<Cell<usize>>);
+
+impl ConnectionSlot {
+    fn take(open: &Rc<Cell<usize>>) -> Self {
+        open.set(open.get() + 1);
+        Self(Rc::clone(open))
+    }
+}
+
+impl Drop for ConnectionSlot {
+    fn drop(&mut self) {
+        self.0.set(self.0.get() - 1);
+    }
+}
+
+struct Connection {
+    stream: TcpStream,
+    slot: ConnectionSlot,
+}
+
+fn serve_request(
+    evaluator: &mut Evaluator,
+    handler: &Value,
+    request: Result<ParsedRequest, u16>,
+    connection: Connection,
+) -> EvalResult<()> {
+    let request = match request {
+        Ok(request) => request_value(request),
+        Err(status) => {
+            respond(evaluator, connection, status, Vec::new(), reason_phrase(status).to_string());
+            return Ok(());
+        }
+    };
+
+    let state = Rc::new(RefCell::new(ResponseState::new()));
+    let response = response_value(&state);
+
+    let handler = match handler {
+        Value::Object(object) => object.borrow().get("handle").unwrap_or(Value::Undefined),
+        other => other.clone(),
+    };
+
+    let returned = match evaluator.call_function(handler, vec![request, response]) {
+        Ok(value) => value,
+        Err(Control::Throw(error)) => {
+            finish(evaluator, &state, connection, Err(error));
+            return Ok(());
+        }
+        Err(exit) => return Err(exit),
+    };
+
+    // Async handlers are waited for so their response is complete before it's written.
+    let promise = to_promise(evaluator, returned);
+    evaluator.event_loop().subscribe(&promise, Box::new(move |evaluator, outcome| {
+        finish(evaluator, &state, connection, outcome.map(|_| ()));
+        Ok(())
+    }));
+    Ok(())
+}
+
+// Sends what the handler set up, or a 500 if it failed.
+fn finish(evaluator: &mut Evaluator, state: &RefCell<ResponseState>, connection: Connection, outcome: Result<(), Value>) {
+    let mut state = state.borrow_mut();
+    if let Err(error) = outcome {
+        eprintln!("Unhandled error in request handler: {}", evaluator.describe_error(error));
+        *state = ResponseState::new();
+        state.status = 500;
+        state.body = "Internal Server Error".to_string();
+    }
+    respond(evaluator, connection, state.status, mem::take(&mut state.headers), mem::take(&mut state.body));
+}
+
+fn respond(evaluator: &mut Evaluator, connection: Connection, status: u16, headers: Vec<(String, String)>, body: String) {
+    let Connection { stream, slot } = connection;
+    evaluator.event_loop().spawn_io(
+        move || write_response(&stream, status, &headers, &body),
+        move |_, _| {
+            drop(slot);
+            Ok(())
+        },
+    );
+}
+
+// A request as read off the connection, kept as plain data so a worker thread can read it.
+struct ParsedRequest {
+    method: String,
+    target: String,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+// Fails with the status to answer: 400 for a malformed request or an overlong
+// request line, 431 for too many or too long headers, 413 for a body over MAX_BODY_BYTES.
+fn read_request(stream: &TcpStream) -> Result<ParsedRequest, u16> {
+    const BAD_REQUEST: u16 = 400;
+    let mut reader = BufReader::new(stream);
+    let request_line = read_bounded_line(&mut reader).map_err(|_| BAD_REQUEST)?;
+
+    let mut parts = request_line.split_whitespace();
+    let method = parts.next().ok_or(BAD_REQUEST)?.to_uppercase();
+    let target = parts.next().ok_or(BAD_REQUEST)?.to_string();
+
+    let headers = read_headers(&mut reader).map_err(|err| match err.kind() {
+        io::ErrorKind::FileTooLarge => 431,
+        _ => BAD_REQUEST,
+    })?;
+    let length: usize = match header_value(&headers, "content-length") {
+        Some(value) => value.parse().map_err(|_| BAD_REQUEST)?,
+        None => 0,
+    };
+    if length > MAX_BODY_BYTES {
+        return Err(413);
+    }
+    let mut body = Vec::new();
+    match (&mut reader).take(length as u64).read_to_end(&mut body) {
+        Ok(read) if read == length => {}
+        _ => return Err(BAD_REQUEST),
+    }
+    let body = String::from_utf8_lossy(&body).into_owned();
+    Ok(ParsedRequest { method, target, headers, body })
+}
+
+fn request_value(request: ParsedRequest) -> Value {
+    let ParsedRequest { method, target, headers, body } = request;
+    let (path, query_string) = match target.split_once('?') {
+        Some((path, query)) => (path.to_string(), query.to_string()),
+        None => (target.clone(), String::new()),
+    };
+
+    let mut query = Object::new();
+    for pair in query_string.split('&').filter(|pair| !pair.is_empty()) {
+        let (key, value) = pair.split_once('=').unwrap_or((pair, ""));
+        let decode = |text: &str| percent_decode(&text.replace('+', " "));
+        query.set(&decode(key), Value::String(decode(value)));
+    }
+
+    let mut header_object = Object::new();
+    for (name, value) in &headers {
+        header_object.set(name, Value::String(value.clone()));
+    }
+
+    let raw_body = body.clone();
+    let mut request = Object::new();
+    request.set("method", Value::String(method));
+    request.set("path", Value::String(percent_decode(&path)));
+    request.set("params", Value::object(Object::new()));
+    request.set("query", Value::object(query));
+    request.set("headers", Value::object(header_object));
+    request.set("body", Value::String(body));
+    request.set("json", Value::native("request.json", move |_, _| {
+        json::parse(&raw_body).or_else(|err| throw("JSONError", err))
+    }));
+    Value::object(request)
+}
+
+struct ResponseState {
+    status: u16,
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl ResponseState {
+    fn new() -> Self {
+        Self {
+            status: 200,
+            headers: Vec::new(),
+            body: String::new(),
+        }
+    }
+
+    fn set_header(&mut self, name: &str, value: &str) {
+        self.headers.retain(|(key, _)| !key.eq_ignore_ascii_case(name));
+        self.headers.push((name.to_string(), value.to_string()));
+    }
+
+    fn send(&mut self, content_type: &str, body: String) {
+        if !self.headers.iter().any(|(key, _)| key.eq_ignore_ascii_case("content-type")) {
+            self.set_header("Content-Type", content_type);
+        }
+        self.body = body;
+    }
+}
+
+// The `res` object handed to handlers. `status` and `header` return `res`
+// itself so calls can be chained: res.status(201).json(user).
+fn response_value(state: &Rc<RefCell<ResponseState>>) -> Value {
+    let response = Rc::new(RefCell::new(Object::new()));
+
+    let weak = Rc::downgrade(&response);
+    let status_state = Rc::clone(state);
+    response.borrow_mut().set("status", Value::native("response.status", move |_, args| {
+        let code = number_arg(&args, 0, "response.status")?;
+        if code.fract() != 0.0 || !(100.0..=999.0).contains(&code) {
+            return throw("RangeError", format!("response.status: status must be a whole number from 100 to 999, got {}", code));
+        }
+        status_state.borrow_mut().status = code as u16;
+        Ok(weak.upgrade().map(Value::Object).unwrap_or(Value::Null))
+    }));
+
+    let weak = Rc::downgrade(&response);
+    let header_state = Rc::clone(state);
+    response.borrow_mut().set("header", Value::native("response.header", move |_, args| {
+        let name = string_arg(&args, 0, "response.header")?;
+        let value = string_arg(&args, 1, "response.header")?;
+        check_header(&name, &value, "response.header")?;
+        header_state.borrow_mut().set_header(&name, &value);
+        Ok(weak.upgrade().map(Value::Object).unwrap_or(Value::Null))
+    }));
+
+    let text_state = Rc::clone(state);
+    response.borrow_mut().set("text", Value::native("response.text", move |evaluator, args| {
+        let body = args.into_iter().next().map(|value| evaluator.stringify(value)).unwrap_or_default();
+        text_state.borrow_mut().send("text/plain; charset=utf-8", body);
+        Ok(Value::Null)
+    }));
+
+    let json_state = Rc::clone(state);
+    response.borrow_mut().set("json", Value::native("response.json", move |_, args| {
+        let body = json::stringify(args.first().unwrap_or(&Value::Null));
+        json_state.borrow_mut().send("application/json", body);
+        Ok(Value::Null)
+    }));
+
+    // res.send(value): strings go out as text, everything else as JSON.
+    let send_state = Rc::clone(state);
+    response.borrow_mut().set("send", Value::native("response.send", move |_, args| {
+        match args.into_iter().next() {
+            Some(Value::String(text)) => send_state.borrow_mut().send("text/plain; charset=utf-8", text),
+            Some(other) => send_state.borrow_mut().send("application/json", json::stringify(&other)),
+            None => {}
+        }
+        Ok(Value::Null)
+    }));
+
+    Value::Object(response)
+}
+
+fn write_response(stream: &TcpStream, status: u16, headers: &[(String, String)], body: &str) {
+    let mut head = format!("HTTP/1.1 {} {}\r\n", status, reason_phrase(status));
+    for (name, value) in headers {
+        head.push_str(&format!("{}: {}\r\n", name, value));
+    }
+    head.push_str(&format!("Content-Length: {}\r\nConnection: close\r\n\r\n", body.len()));
+
+    let mut writer = stream;
+    // The client may already have gone away; there is no one left to report that to.
+    let _ = writer.write_all(head.as_bytes()).and_then(|_| writer.write_all(body.as_bytes()));
+}
+
+fn reason_phrase(status: u16) -> &'static str {
+    match status {
+        200 => "OK",
+        201 => "Created",
+        204 => "No Content",
+        301 => "Moved Permanently",
+        302 => "Found",
+        304 => "Not Modified",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        409 => "Conflict",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        500 => "Internal Server Error",
+        503 => "Service Unavailable",
+        _ => "",
+    }
+}
+
+fn segments(path: &str) -> Vec<String> {
+    path.split('/').filter(|segment| !segment.is_empty()).map(str::to_string).collect()
+}
+
+// `:name` segments capture one path segment; a trailing `*` matches the rest.
+fn match_route(pattern: &[String], path: &str) -> Option<Object> {
+    let parts = segments(path);
+    let mut params = Object::new();
+
+    for (index, segment) in pattern.iter().enumerate() {
+        if segment == "*" {
+            params.set("*", Value::String(parts.get(index..).unwrap_or_default().join("/")));
+            return Some(params);
+        }
+        let part = parts.get(index)?;
+        if let Some(name) = segment.strip_prefix(':') {
+            params.set(name, Value::String(part.clone()));
+        } else if segment != part {
+            return None;
+        }
+    }
+
+    (parts.len() == pattern.len()).then_some(params)
+}
+
+fn member(value: &Value, name: &str) -> EvalResult<Value> {
+    match value {
+        Value::Object(object) => Ok(object.borrow().get(name).unwrap_or(Value::Undefined)),
+        _ => throw("TypeError", format!("Cannot read property '{}' of a non-object", name)),
+    }
+}
+
+fn percent_decode(text: &str) -> String {
+    let bytes = text.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        let escaped = (bytes[index] == b'%' && index + 2 < bytes.len())
+            .then(|| std::str::from_utf8(&bytes[index + 1..index + 3]).ok())
+            .flatten()
+            .and_then(|hex| u8::from_str_radix(hex, 16).ok());
+        match escaped {
+            Some(byte) => {
+                out.push(byte);
+                index += 3;
+            }
+            None => {
+                out.push(bytes[index]);
+                index += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::evaluator::{parse_source, Backend, Evaluator};
+    use crate::runtime::testing::logged;
+
+    use std::io::{Read, Write};
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+    use std::time::{Duration, Instant};
+
+    fn free_port() -> u16 {
+        TcpListener::bind("127.0.0.1:0").unwrap().local_addr().unwrap().port()
+    }
+
+    fn send(port: u16, request: &str) -> String {
+        for _ in 0..200 {
+            if let Ok(mut stream) = TcpStream::connect(("127.0.0.1", port)) {
+                stream.write_all(request.as_bytes()).unwrap();
+                let mut response = String::new();
+                stream.read_to_string(&mut response).unwrap();
+                return response;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("server never started on port {}", port);
+    }
+
+    #[test]
+    fn routes_requests_until_closed() {
+        for backend in Backend::ALL {
+            let port = free_port();
+
+            let client = thread::spawn(move || {
+                vec![
+                    send(port, "GET /users/42?active=yes%21 HTTP/1.1\r\nHost: x\r\n\r\n"),
+                    send(port, "POST /echo HTTP/1.1\r\nContent-Length: 5\r\n\r\nhello"),
+                    send(port, "GET /missing HTTP/1.1\r\n\r\n"),
+                    send(port, "GET /boom HTTP/1.1\r\n\r\n"),
+                    send(port, "GET /split HTTP/1.1\r\n\r\n"),
+                    send(port, "GET /status HTTP/1.1\r\n\r\n"),
+                    send(port, "POST /echo HTTP/1.1\r\nContent-Length: 99999999999\r\n\r\n"),
+                    send(port, &format!("GET /echo HTTP/1.1\r\n{}\r\n", "X-Filler: a\r\n".repeat(101))),
+                    send(port, "GET /shutdown HTTP/1.1\r\n\r\n"),
+                ]
+            });
+
+            let result = Evaluator::with_backend(backend).evaluate(parse_source(&format!(r#"
+                get http from "http"
+
+                init showUser(req, res):
+                    res.json({{id: req.params.id, active: req.query.active}})
+
+                init echo(req, res):
+                    res.status(201).header("X-Echo", "1").text(req.body)
+
+                init boom(req, res):
+                    throw "nope"
+
+                init split(req, res):
+                    try:
+                        res.header("X-Next", "a\nSet-Cookie: admin=1")
+                    catch (TypeError err):
+                        res.header("Bad Name", "b")
+
+                init status(req, res):
+                    try:
+                        res.status(70000)
+                    catch (RangeError err):
+                        res.status(999).text(err.message)
+
+                init shutdown(req, res):
+                    res.send("bye")
+                    server.close()
+
+                app = http.router()
+                app.get("/users/:id", showUser)
+                app.post("/echo", echo)
+                app.get("/boom", boom)
+                app.get("/split", split)
+                app.get("/status", status)
+                app.get("/shutdown", shutdown)
+
+                server = http.createServer(app)
+                server.listen({port})
+            "#)));
+            assert!(result.is_ok(), "{:?} server script failed: {:?}", backend, result);
+
+            let responses = client.join().unwrap();
+            assert!(responses[0].starts_with("HTTP/1.1 200 OK\r\n"));
+            assert!(responses[0].contains("Content-Type: application/json\r\n"));
+            assert!(responses[0].ends_with(r#"{"id":"42","active":"yes!"}"#));
+            assert!(responses[1].starts_with("HTTP/1.1 201 Created\r\n"));
+            assert!(responses[1].contains("X-Echo: 1\r\n"));
+            assert!(responses[1].ends_with("\r\n\r\nhello"));
+            assert!(responses[2].starts_with("HTTP/1.1 404 Not Found\r\n"));
+            assert!(responses[3].starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+            assert!(responses[4].starts_with("HTTP/1.1 500 Internal Server Error\r\n"));
+            assert!(!responses[4].contains("Set-Cookie") && !responses[4].contains("Bad Name"));
+            assert!(responses[5].starts_with("HTTP/1.1 999 \r\n"));
+            assert!(responses[5].ends_with("status must be a whole number from 100 to 999, got 70000"));
+            assert!(responses[6].starts_with("HTTP/1.1 413 Payload Too Large\r\n"));
+            assert!(responses[7].starts_with("HTTP/1.1 431 Request Header Fields Too Large\r\n"));
+            assert!(responses[8].ends_with("\r\n\r\nbye"));
+        }
+    }
+    #[test]
+    fn ports_out_of_range_are_refused() {
+        let output = logged(r#"
+            get http from "http"
+            for (port in [70000, -1, 80.5]):
+                try:
+                    http.createServer((req, res) => res.send("hi")).listen(port)
+                catch (RangeError err):
+                    log(err.message)
+        "#);
+
+        assert_eq!(
+            output,
+            "server.listen: port must be a whole number from 0 to 65535, got 70000\n\
+             server.listen: port must be a whole number from 0 to 65535, got -1\n\
+             server.listen: port must be a whole number from 0 to 65535, got 80.5"
+        );
+    }
+
+    #[test]
+    fn a_slow_client_does_not_hold_up_others() {
+        for backend in Backend::ALL {
+            let port = free_port();
+
+            let client = thread::spawn(move || {
+                // Never finishes its request until the fast one has been answered.
+                let stalled = (0..200)
+                    .find_map(|_| TcpStream::connect(("127.0.0.1", port)).ok().or_else(|| {
+                        thread::sleep(Duration::from_millis(10));
+                        None
+                    }))
+                    .unwrap();
+                (&stalled).write_all(b"GET /stalled HTTP/1.1\r\n").unwrap();
+                let started = Instant::now();
+                let response = send(port, "GET /fast HTTP/1.1\r\n\r\n");
+                (response, started.elapsed())
+            });
+
+            let result = Evaluator::with_backend(backend).evaluate(parse_source(&format!(r#"
+                get http from "http"
+
+                init handle(req, res):
+                    res.send(req.path)
+                    server.close()
+
+                server = http.createServer(handle)
+                server.listen({port}, "127.0.0.1")
+            "#)));
+            assert!(result.is_ok(), "{:?} server script failed: {:?}", backend, result);
+
+            let (response, elapsed) = client.join().unwrap();
+            assert!(response.ends_with("\r\n\r\n/fast"), "{}", response);
+            assert!(elapsed < Duration::from_secs(5), "fast request waited {:?}", elapsed);
+        }
+    }
+}
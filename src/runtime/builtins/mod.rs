@@ -0,0 +1,84 @@
+pub mod bigint;
+pub mod date;
+pub mod enums;
+pub mod fs;
+pub mod http;
+pub mod http_server;
+pub mod object;
+pub mod os;
+pub mod promise;
+pub mod symbol;
+pub mod timers;
+
+use crate::runtime::environment::Env;
+use crate::runtime::evaluator::Evaluator;
+use crate::runtime::modules::Module;
+use crate::runtime::values::Value;
+use crate::utils::errors::{throw, EvalResult};
+
+use std::io::{self, BufRead, Write};
+
+pub fn define_globals(globals: &Env) {
+    let mut globals = globals.borrow_mut();
+
+    globals.define("input", Value::native("input", |evaluator, args| {
+        if let Some(prompt) = args.into_iter().next() {
+            print!("{}", evaluator.stringify(prompt));
+            io::stdout().flush().ok();
+        }
+
+        let mut line = String::new();
+        match io::stdin().lock().read_line(&mut line) {
+            Ok(0) | Err(_) => Ok(Value::Null),
+            Ok(_) => Ok(Value::String(line.trim_end_matches(['\n', '\r']).to_string())),
+        }
+    }));
+
+    globals.define("Promise", promise::constructor());
+    globals.define("Date", date::constructor());
+    globals.define("Symbol", symbol::constructor());
+    globals.define("BigInt", bigint::constructor());
+    for (name, value) in timers::globals() {
+        globals.define(name, value);
+    }
+}
+
+// Modules that `get` resolves by bare name instead of by path.
+pub fn module(name: &str) -> Option<Module> {
+    match name {
+        "fs" => Some(fs::module()),
+        "os" => Some(os::module()),
+        "http" => Some(http::module()),
+        _ => None,
+    }
+}
+
+pub fn string_arg(args: &[Value], index: usize, function: &str) -> EvalResult<String> {
+    match args.get(index) {
+        Some(Value::String(value)) => Ok(value.clone()),
+        Some(_) => throw("TypeError", format!("{}: argument {} must be a string", function, index + 1)),
+        None => throw("TypeError", format!("{}: missing argument {}", function, index + 1)),
+    }
+}
+
+pub fn number_arg(args: &[Value], index: usize, function: &str) -> EvalResult<f64> {
+    match args.get(index) {
+        Some(Value::Number(value)) => Ok(*value),
+        Some(_) => throw("TypeError", format!("{}: argument {} must be a number", function, index + 1)),
+        None => throw("TypeError", format!("{}: missing argument {}", function, index + 1)),
+    }
+}
+
+pub fn bool_arg(args: &[Value], index: usize, default: bool) -> bool {
+    match args.get(index) {
+        Some(Value::Boolean(value)) => *value,
+        _ => default,
+    }
+}
+
+// Accepts any value and converts it the same way `log` would.
+pub fn text_arg(evaluator: &Evaluator, args: &[Value], index: usize) -> String {
+    args.get(index)
+        .map(|value| evaluator.stringify(value.clone()))
+        .unwrap_or_default()
+}
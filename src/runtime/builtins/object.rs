@@ -0,0 +1,44 @@
+use crate::runtime::json;
+use crate::runtime::values::{Object, Value};
+use crate::utils::errors::throw;
+
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Methods every object has unless it defines a property of the same name.
+// Only string keys are visible here; symbol keys stay hidden.
+pub fn method(object: &Rc<RefCell<Object>>, name: &str) -> Option<Value> {
+    let object = Rc::clone(object);
+    let method = match name {
+        "keys" => Value::native("object.keys", move |_, _| {
+            let keys = object.borrow().entries().map(|(key, _)| Value::String(key.clone())).collect();
+            Ok(Value::array(keys))
+        }),
+        "values" => Value::native("object.values", move |_, _| {
+            let values = object.borrow().entries().map(|(_, value)| value.clone()).collect();
+            Ok(Value::array(values))
+        }),
+        "entries" => Value::native("object.entries", move |_, _| {
+            let entries = object
+                .borrow()
+                .entries()
+                .map(|(key, value)| Value::array(vec![Value::String(key.clone()), value.clone()]))
+                .collect();
+            Ok(Value::array(entries))
+        }),
+        "hasKey" => Value::native("object.hasKey", move |_, args| {
+            let object = object.borrow();
+            let found = match args.first() {
+                Some(Value::String(key)) => object.get(key).is_some(),
+                Some(Value::Symbol(symbol)) => object.get_symbol(symbol).is_some(),
+                _ => return throw("TypeError", "object.hasKey: key must be a string or symbol"),
+            };
+            Ok(Value::Boolean(found))
+        }),
+        "toJSON" => Value::native("object.toJSON", move |_, _| {
+            Ok(Value::String(json::stringify(&Value::Object(Rc::clone(&object)))))
+        }),
+        _ => return None,
+    };
+    Some(method)
+}
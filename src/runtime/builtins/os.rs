@@ -0,0 +1,119 @@
+use crate::runtime::builtins::{number_arg, string_arg, text_arg};
+use crate::runtime::modules::Module;
+use crate::runtime::values::{Object, Value};
+use crate::utils::errors::{throw, Control};
+
+use std::env;
+use std::fs;
+use std::process;
+
+pub fn module() -> Module {
+    Module::native("os", vec![
+        ("platform", Value::native("os.platform", |_, _| {
+            Ok(Value::String(env::consts::OS.to_string()))
+        })),
+        ("arch", Value::native("os.arch", |_, _| {
+            Ok(Value::String(env::consts::ARCH.to_string()))
+        })),
+        ("env", Value::native("os.env", |evaluator, _| {
+            let mut object = Object::new();
+            for (key, value) in evaluator.env_vars() {
+                object.set(&key, Value::String(value));
+            }
+            Ok(Value::object(object))
+        })),
+        ("getEnv", Value::native("os.getEnv", |evaluator, args| {
+            let name = string_arg(&args, 0, "os.getEnv")?;
+            Ok(evaluator.env_var(&name).map(Value::String).unwrap_or(Value::Null))
+        })),
+        ("setEnv", Value::native("os.setEnv", |evaluator, args| {
+            let name = string_arg(&args, 0, "os.setEnv")?;
+            if name.is_empty() || name.contains(['=', '\0']) {
+                return throw("ValueError", format!("os.setEnv: invalid variable name \"{}\"", name));
+            }
+            let value = text_arg(evaluator, &args, 1);
+            evaluator.set_env_var(name, value);
+            Ok(Value::Null)
+        })),
+        ("cwd", Value::native("os.cwd", |_, _| {
+            match env::current_dir() {
+                Ok(dir) => Ok(Value::String(dir.to_string_lossy().into_owned())),
+                Err(err) => throw("IOError", format!("os.cwd: {}", err)),
+            }
+        })),
+        ("args", Value::native("os.args", |evaluator, _| {
+            let args = evaluator.args().iter().cloned().map(Value::String).collect();
+            Ok(Value::array(args))
+        })),
+        ("hostname", Value::native("os.hostname", |_, _| {
+            Ok(Value::String(hostname()))
+        })),
+        ("homeDir", Value::native("os.homeDir", |evaluator, _| {
+            Ok(evaluator.env_var("HOME")
+                .or_else(|| evaluator.env_var("USERPROFILE"))
+                .map(Value::String)
+                .unwrap_or(Value::Null))
+        })),
+        ("tmpDir", Value::native("os.tmpDir", |_, _| {
+            Ok(Value::String(env::temp_dir().to_string_lossy().into_owned()))
+        })),
+        ("pid", Value::native("os.pid", |_, _| {
+            Ok(Value::Number(process::id() as f64))
+        })),
+        ("exit", Value::native("os.exit", |_, args| {
+            let code = if args.is_empty() { 0.0 } else { number_arg(&args, 0, "os.exit")? };
+            Err(Control::Exit(code as i32))
+        })),
+    ])
+}
+
+fn hostname() -> String {
+    fs::read_to_string("/proc/sys/kernel/hostname")
+        .or_else(|_| fs::read_to_string("/etc/hostname"))
+        .map(|name| name.trim().to_string())
+        .ok()
+        .filter(|name| !name.is_empty())
+        .or_else(|| env::var("HOSTNAME").ok())
+        .or_else(|| env::var("COMPUTERNAME").ok())
+        .unwrap_or_else(|| "localhost".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::evaluator::{Backend, Evaluator};
+    use crate::runtime::testing::{capture, logged};
+    use crate::utils::errors::Control;
+
+    #[test]
+    fn exit_runs_finally_blocks_and_skips_catch() {
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            evaluator.set_args(vec!["--fast".to_string()]);
+            let (result, output) = capture(&mut evaluator, r#"
+                get os from "os"
+                try:
+                    os.exit(3)
+                catch (err):
+                    log("caught")
+                finally:
+                    log(os.args())
+                log("kept running")
+            "#);
+
+            assert!(matches!(result, Err(Control::Exit(3))));
+            assert_eq!(output, r#"["--fast"]"#);
+        }
+    }
+
+    #[test]
+    fn set_env_shadows_the_process_environment() {
+        let output = logged(r#"
+            get os from "os"
+            os.setEnv("GENESIX_TEST_SET_ENV", "on")
+            log([os.getEnv("GENESIX_TEST_SET_ENV"), os.env()["GENESIX_TEST_SET_ENV"]])
+        "#);
+
+        assert_eq!(output, r#"["on", "on"]"#);
+        assert!(std::env::var("GENESIX_TEST_SET_ENV").is_err());
+    }
+}
@@ -0,0 +1,343 @@
+use crate::runtime::evaluator::Evaluator;
+use crate::runtime::promise::{Promise, PromiseRef, Settled};
+use crate::runtime::values::{Object, Value};
+use crate::utils::errors::{error_value, throw, Control, EvalResult};
+
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+
+// The global `Promise`: calling it with an executor creates a promise, and it
+// carries `resolve`, `reject` and the combinators as static members.
+pub fn constructor() -> Value {
+    Value::native_with_properties("Promise", |evaluator, args| {
+        let executor = args.into_iter().next().unwrap_or(Value::Undefined);
+        if !executor.is_callable() {
+            return throw("TypeError", "Promise: executor must be a function");
+        }
+
+        let promise = Promise::new();
+        let (resolve, reject) = resolving_functions(&promise);
+        match evaluator.call_function(executor, vec![resolve, reject]) {
+            Ok(_) => {}
+            // A throwing executor rejects, unless it already settled the promise.
+            Err(Control::Throw(reason)) => evaluator.event_loop().reject(&promise, reason),
+            Err(other) => return Err(other),
+        }
+        Ok(Value::Promise(promise))
+    }, vec![
+        ("resolve", Value::native("Promise.resolve", |evaluator, args| {
+            let value = args.into_iter().next().unwrap_or(Value::Undefined);
+            Ok(Value::Promise(to_promise(evaluator, value)))
+        })),
+        ("reject", Value::native("Promise.reject", |evaluator, args| {
+            let promise = Promise::new();
+            evaluator.event_loop().reject(&promise, args.into_iter().next().unwrap_or(Value::Undefined));
+            Ok(Value::Promise(promise))
+        })),
+        ("all", Value::native("Promise.all", all)),
+        ("allSettled", Value::native("Promise.allSettled", all_settled)),
+        ("race", Value::native("Promise.race", race)),
+        ("any", Value::native("Promise.any", any)),
+    ])
+}
+
+// `then`, `catch` and `finally`, bound to `promise`.
+pub fn method(promise: &PromiseRef, name: &str) -> Option<Value> {
+    let promise = Rc::clone(promise);
+    let method = match name {
+        "then" => Value::native("promise.then", move |evaluator, args| {
+            let mut args = args.into_iter();
+            let on_fulfilled = args.next().unwrap_or(Value::Undefined);
+            let on_rejected = args.next().unwrap_or(Value::Undefined);
+            Ok(Value::Promise(then(evaluator, &promise, on_fulfilled, on_rejected)))
+        }),
+        "catch" => Value::native("promise.catch", move |evaluator, args| {
+            let on_rejected = args.into_iter().next().unwrap_or(Value::Undefined);
+            Ok(Value::Promise(then(evaluator, &promise, Value::Undefined, on_rejected)))
+        }),
+        "finally" => Value::native("promise.finally", move |evaluator, args| {
+            let callback = args.into_iter().next().unwrap_or(Value::Undefined);
+            Ok(Value::Promise(finally(evaluator, &promise, callback)))
+        }),
+        _ => return None,
+    };
+    Some(method)
+}
+
+// `Promise.resolve` semantics: promises pass through, anything else is wrapped.
+pub fn to_promise(evaluator: &mut Evaluator, value: Value) -> PromiseRef {
+    if let Value::Promise(promise) = value {
+        return promise;
+    }
+    let promise = Promise::new();
+    evaluator.event_loop().resolve(&promise, value);
+    promise
+}
+
+fn resolving_functions(promise: &PromiseRef) -> (Value, Value) {
+    let target = Rc::clone(promise);
+    let resolve = Value::native("resolve", move |evaluator, args| {
+        let value = args.into_iter().next().unwrap_or(Value::Undefined);
+        evaluator.event_loop().resolve(&target, value);
+        Ok(Value::Undefined)
+    });
+
+    let target = Rc::clone(promise);
+    let reject = Value::native("reject", move |evaluator, args| {
+        let reason = args.into_iter().next().unwrap_or(Value::Undefined);
+        evaluator.event_loop().reject(&target, reason);
+        Ok(Value::Undefined)
+    });
+
+    (resolve, reject)
+}
+
+fn then(evaluator: &mut Evaluator, promise: &PromiseRef, on_fulfilled: Value, on_rejected: Value) -> PromiseRef {
+    let derived = Promise::new();
+    let target = Rc::clone(&derived);
+
+    evaluator.event_loop().subscribe(promise, Box::new(move |evaluator, outcome| {
+        // A missing handler passes the outcome straight through.
+        let (handler, argument) = match outcome {
+            Ok(value) if on_fulfilled.is_callable() => (on_fulfilled, value),
+            Err(reason) if on_rejected.is_callable() => (on_rejected, reason),
+            outcome => {
+                evaluator.event_loop().settle(&target, outcome);
+                return Ok(());
+            }
+        };
+
+        match evaluator.call_function(handler, vec![argument]) {
+            Ok(value) => evaluator.event_loop().resolve(&target, value),
+            Err(Control::Throw(reason)) => evaluator.event_loop().reject(&target, reason),
+            Err(other) => return Err(other),
+        }
+        Ok(())
+    }));
+
+    derived
+}
+
+fn finally(evaluator: &mut Evaluator, promise: &PromiseRef, callback: Value) -> PromiseRef {
+    let derived = Promise::new();
+    let target = Rc::clone(&derived);
+
+    evaluator.event_loop().subscribe(promise, Box::new(move |evaluator, outcome| {
+        if !callback.is_callable() {
+            evaluator.event_loop().settle(&target, outcome);
+            return Ok(());
+        }
+
+        let result = match evaluator.call_function(callback, Vec::new()) {
+            Ok(value) => value,
+            Err(Control::Throw(reason)) => {
+                evaluator.event_loop().reject(&target, reason);
+                return Ok(());
+            }
+            Err(other) => return Err(other),
+        };
+
+        // Waits for a returned promise, then keeps the original outcome
+        // unless the callback's promise rejected.
+        let waited = to_promise(evaluator, result);
+        evaluator.event_loop().subscribe(&waited, Box::new(move |evaluator, waited| {
+            let outcome = match waited {
+                Ok(_) => outcome,
+                Err(reason) => Err(reason),
+            };
+            evaluator.event_loop().settle(&target, outcome);
+            Ok(())
+        }));
+        Ok(())
+    }));
+
+    derived
+}
+
+fn iterable_arg(evaluator: &mut Evaluator, args: Vec<Value>, function: &str) -> EvalResult<Vec<PromiseRef>> {
+    let Some(iterable) = args.into_iter().next() else {
+        return throw("TypeError", format!("{}: argument 1 must be iterable", function));
+    };
+    let items = evaluator.iterate(iterable)?;
+    Ok(items.into_iter().map(|item| to_promise(evaluator, item)).collect())
+}
+
+// Calls `on_settled(index, outcome)` as each input promise settles.
+fn each_settled(
+    evaluator: &mut Evaluator,
+    promises: &[PromiseRef],
+    on_settled: impl Fn(&mut Evaluator, usize, Settled) + 'static,
+) {
+    let on_settled = Rc::new(on_settled);
+    for (index, promise) in promises.iter().enumerate() {
+        let on_settled = Rc::clone(&on_settled);
+        evaluator.event_loop().subscribe(promise, Box::new(move |evaluator, outcome| {
+            on_settled(evaluator, index, outcome);
+            Ok(())
+        }));
+    }
+}
+
+fn all(evaluator: &mut Evaluator, args: Vec<Value>) -> EvalResult<Value> {
+    let promises = iterable_arg(evaluator, args, "Promise.all")?;
+    let combined = Promise::new();
+    if promises.is_empty() {
+        evaluator.event_loop().resolve(&combined, Value::array(Vec::new()));
+        return Ok(Value::Promise(combined));
+    }
+
+    let results = Rc::new(RefCell::new(vec![Value::Undefined; promises.len()]));
+    let remaining = Rc::new(Cell::new(promises.len()));
+    let target = Rc::clone(&combined);
+
+    each_settled(evaluator, &promises, move |evaluator, index, outcome| match outcome {
+        Ok(value) => {
+            results.borrow_mut()[index] = value;
+            remaining.set(remaining.get() - 1);
+            if remaining.get() == 0 {
+                let values = results.borrow().clone();
+                evaluator.event_loop().resolve(&target, Value::array(values));
+            }
+        }
+        Err(reason) => evaluator.event_loop().reject(&target, reason),
+    });
+
+    Ok(Value::Promise(combined))
+}
+
+fn all_settled(evaluator: &mut Evaluator, args: Vec<Value>) -> EvalResult<Value> {
+    let promises = iterable_arg(evaluator, args, "Promise.allSettled")?;
+    let combined = Promise::new();
+    if promises.is_empty() {
+        evaluator.event_loop().resolve(&combined, Value::array(Vec::new()));
+        return Ok(Value::Promise(combined));
+    }
+
+    let results = Rc::new(RefCell::new(vec![Value::Undefined; promises.len()]));
+    let remaining = Rc::new(Cell::new(promises.len()));
+    let target = Rc::clone(&combined);
+
+    each_settled(evaluator, &promises, move |evaluator, index, outcome| {
+        let mut result = Object::new();
+        match outcome {
+            Ok(value) => {
+                result.set("status", Value::String("fulfilled".to_string()));
+                result.set("value", value);
+            }
+            Err(reason) => {
+                result.set("status", Value::String("rejected".to_string()));
+                result.set("reason", reason);
+            }
+        }
+        results.borrow_mut()[index] = Value::object(result);
+
+        remaining.set(remaining.get() - 1);
+        if remaining.get() == 0 {
+            let values = results.borrow().clone();
+            evaluator.event_loop().resolve(&target, Value::array(values));
+        }
+    });
+
+    Ok(Value::Promise(combined))
+}
+
+// An empty race never settles, as in JavaScript.
+fn race(evaluator: &mut Evaluator, args: Vec<Value>) -> EvalResult<Value> {
+    let promises = iterable_arg(evaluator, args, "Promise.race")?;
+    let combined = Promise::new();
+    let target = Rc::clone(&combined);
+
+    each_settled(evaluator, &promises, move |evaluator, _, outcome| {
+        evaluator.event_loop().settle(&target, outcome);
+    });
+
+    Ok(Value::Promise(combined))
+}
+
+fn any(evaluator: &mut Evaluator, args: Vec<Value>) -> EvalResult<Value> {
+    let promises = iterable_arg(evaluator, args, "Promise.any")?;
+    let combined = Promise::new();
+    if promises.is_empty() {
+        evaluator.event_loop().reject(&combined, aggregate_error(Vec::new()));
+        return Ok(Value::Promise(combined));
+    }
+
+    let errors = Rc::new(RefCell::new(vec![Value::Undefined; promises.len()]));
+    let remaining = Rc::new(Cell::new(promises.len()));
+    let target = Rc::clone(&combined);
+
+    each_settled(evaluator, &promises, move |evaluator, index, outcome| match outcome {
+        Ok(value) => evaluator.event_loop().resolve(&target, value),
+        Err(reason) => {
+            errors.borrow_mut()[index] = reason;
+            remaining.set(remaining.get() - 1);
+            if remaining.get() == 0 {
+                let errors = errors.borrow().clone();
+                evaluator.event_loop().reject(&target, aggregate_error(errors));
+            }
+        }
+    });
+
+    Ok(Value::Promise(combined))
+}
+
+fn aggregate_error(errors: Vec<Value>) -> Value {
+    let error = error_value("AggregateError", "All promises were rejected");
+    if let Value::Object(object) = &error {
+        object.borrow_mut().set("errors", Value::array(errors));
+    }
+    error
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::testing::logged;
+
+    #[test]
+    fn executors_and_then_chains_settle_in_order() {
+        let written = logged(r#"
+init executor(resolve, reject):
+    resolve(1)
+    reject("ignored")
+init double(n):
+    return `${n}${n}`
+init fail(n):
+    throw `bad ${n}`
+init recover(err):
+    return `recovered ${err}`
+init save(value):
+    log(value)
+
+Promise(executor).then(double).then(fail).then(double).catch(recover).finally(null).then(save)
+"#);
+        assert_eq!(written, "recovered bad 11");
+    }
+
+    #[test]
+    fn combinators_follow_javascript_semantics() {
+        let written = logged(r#"
+init boom(resolve, reject):
+    reject("boom")
+
+ok = Promise.resolve("a")
+failed = Promise(boom)
+all = await Promise.all([ok, "b"])
+settled = await Promise.allSettled([ok, failed])
+first = await Promise.race([ok, failed])
+try:
+    await Promise.all([ok, failed])
+catch (err):
+    allError = err
+winner = await Promise.any([failed, ok])
+try:
+    await Promise.any([failed, Promise.reject("again")])
+catch (AggregateError err):
+    anyError = err.errors
+log([all, settled, first, allError, winner, anyError])
+"#);
+        assert_eq!(
+            written,
+            r#"[["a", "b"], [{status: "fulfilled", value: "a"}, {status: "rejected", reason: "boom"}], "a", "boom", "a", ["boom", "again"]]"#
+        );
+    }
+}
@@ -0,0 +1,111 @@
+use crate::runtime::values::{Symbol, Value};
+
+use std::rc::Rc;
+
+thread_local! {
+    // Well-known symbols are shared by every evaluator on the thread so the
+    // runtime can look them up without going through the `Symbol` global.
+    static ITERATOR: Rc<Symbol> = well_known("Symbol.iterator");
+    static TO_PRIMITIVE: Rc<Symbol> = well_known("Symbol.toPrimitive");
+}
+
+fn well_known(description: &str) -> Rc<Symbol> {
+    Rc::new(Symbol { description: Some(description.to_string()) })
+}
+
+// `[Symbol.iterator]()` returns an object whose `next()` gives `{value, done}`.
+pub fn iterator() -> Rc<Symbol> {
+    ITERATOR.with(Rc::clone)
+}
+
+// `[Symbol.toPrimitive]("string")` decides how an object is shown by `log` and templates.
+pub fn to_primitive() -> Rc<Symbol> {
+    TO_PRIMITIVE.with(Rc::clone)
+}
+
+pub fn constructor() -> Value {
+    Value::native_with_properties("Symbol", |evaluator, args| {
+        let description = match args.into_iter().next() {
+            None | Some(Value::Undefined) => None,
+            Some(value) => Some(evaluator.stringify(value)),
+        };
+        Ok(Value::Symbol(Rc::new(Symbol { description })))
+    }, vec![
+        ("iterator", Value::Symbol(iterator())),
+        ("toPrimitive", Value::Symbol(to_primitive())),
+    ])
+}
+
+pub fn describe(symbol: &Symbol) -> String {
+    format!("Symbol({})", symbol.description.as_deref().unwrap_or(""))
+}
+
+pub fn method(symbol: &Rc<Symbol>, name: &str) -> Value {
+    match name {
+        "description" => symbol.description.clone().map_or(Value::Undefined, Value::String),
+        "toString" => {
+            let text = describe(symbol);
+            Value::native("symbol.toString", move |_, _| Ok(Value::String(text.clone())))
+        }
+        _ => Value::Undefined,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::iterator;
+    use crate::runtime::evaluator::{Backend, Evaluator};
+    use crate::runtime::testing::logged;
+    use crate::runtime::values::{Object, Value};
+
+    use std::cell::Cell;
+    use std::rc::Rc;
+
+    #[test]
+    fn symbols_are_unique_hidden_keys() {
+        let output = logged(r#"
+            init label(hint):
+                return `custom ${hint}`
+            id = Symbol("id")
+            other = Symbol("id")
+            user = {name: "Joe", [id]: 42}
+            shown = {[Symbol.toPrimitive]: label}
+            log([
+                id == id, id == other, id.description, id, user[id], user["name"],
+                user.keys(), user.toJSON(), user.hasKey(id), user, `${shown}`
+            ])
+        "#);
+
+        assert_eq!(
+            output,
+            r#"[true, false, "id", Symbol(id), 42, "Joe", ["name"], "{\"name\":\"Joe\"}", true, {name: "Joe"}, "custom string"]"#
+        );
+    }
+
+    #[test]
+    fn objects_with_symbol_iterator_are_iterable() {
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            let make_iterator = Value::native("countdown", |_, _| {
+                let remaining = Rc::new(Cell::new(3_u32));
+                let mut iterator = Object::new();
+                iterator.set("next", Value::native("next", move |_, _| {
+                    let mut step = Object::new();
+                    step.set("done", Value::Boolean(remaining.get() == 0));
+                    step.set("value", Value::Number(remaining.get() as f64));
+                    remaining.set(remaining.get().saturating_sub(1));
+                    Ok(Value::object(step))
+                }));
+                Ok(Value::object(iterator))
+            });
+
+            let mut countdown = Object::new();
+            countdown.set_symbol(&iterator(), make_iterator);
+            let items = evaluator.iterate(Value::object(countdown)).unwrap();
+            assert_eq!(evaluator.stringify(Value::array(items)), "[3, 2, 1]");
+
+            let error = evaluator.iterate(Value::object(Object::new())).unwrap_err();
+            assert!(matches!(error, crate::utils::errors::Control::Throw(_)));
+        }
+    }
+}
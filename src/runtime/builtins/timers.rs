@@ -0,0 +1,106 @@
+use crate::runtime::builtins::number_arg;
+use crate::runtime::evaluator::Evaluator;
+use crate::runtime::promise::Promise;
+use crate::runtime::values::Value;
+use crate::utils::errors::{throw, EvalResult};
+
+use std::rc::Rc;
+
+pub fn globals() -> Vec<(&'static str, Value)> {
+    vec![
+        // setTimeout(callback, ms, ...args) runs the callback once, with `args`.
+        ("setTimeout", Value::native("setTimeout", |evaluator, args| {
+            let (callback, delay, extra) = timer_args(args, "setTimeout")?;
+            let id = evaluator.event_loop().set_timer(delay, move |evaluator| {
+                evaluator.call_function(callback, extra).map(|_| ())
+            });
+            Ok(Value::Number(id as f64))
+        })),
+        ("setInterval", Value::native("setInterval", |evaluator, args| {
+            let (callback, delay, extra) = timer_args(args, "setInterval")?;
+            // A zero interval would starve everything else on the loop.
+            let id = evaluator.event_loop().set_interval(delay.max(1), Rc::new(move |evaluator: &mut Evaluator| {
+                evaluator.call_function(callback.clone(), extra.clone()).map(|_| ())
+            }));
+            Ok(Value::Number(id as f64))
+        })),
+        ("clearTimeout", Value::native("clearTimeout", clear)),
+        ("clearInterval", Value::native("clearInterval", clear)),
+        // sleep(ms) returns a promise that fulfils once the delay has passed.
+        ("sleep", Value::native("sleep", |evaluator, args| {
+            let delay = delay_arg(&args, 0, "sleep")?;
+            let promise = Promise::new();
+            let pending = Rc::clone(&promise);
+            evaluator.event_loop().set_timer(delay, move |evaluator| {
+                evaluator.event_loop().resolve(&pending, Value::Undefined);
+                Ok(())
+            });
+            Ok(Value::Promise(promise))
+        })),
+    ]
+}
+
+fn timer_args(args: Vec<Value>, function: &str) -> EvalResult<(Value, u64, Vec<Value>)> {
+    let delay = delay_arg(&args, 1, function)?;
+    let mut args = args.into_iter();
+    let callback = args.next().unwrap_or(Value::Undefined);
+    if !callback.is_callable() {
+        return throw("TypeError", format!("{}: callback must be a function", function));
+    }
+    Ok((callback, delay, args.skip(1).collect()))
+}
+
+// A missing, negative or NaN delay means "as soon as possible", as in JavaScript.
+fn delay_arg(args: &[Value], index: usize, function: &str) -> EvalResult<u64> {
+    match args.get(index) {
+        None | Some(Value::Undefined) => Ok(0),
+        Some(_) => {
+            let delay = number_arg(args, index, function)?;
+            Ok(if delay.is_nan() || delay < 0.0 { 0 } else { delay as u64 })
+        }
+    }
+}
+
+// Clearing an unknown or already-finished timer does nothing.
+fn clear(evaluator: &mut Evaluator, args: Vec<Value>) -> EvalResult<Value> {
+    if let Some(Value::Number(id)) = args.first() {
+        evaluator.event_loop().clear_timer(*id as u64);
+    }
+    Ok(Value::Undefined)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::evaluator::{Backend, Evaluator};
+    use crate::runtime::testing::capture;
+
+    use std::time::Instant;
+
+    #[test]
+    fn timers_fire_in_due_order_on_a_virtual_clock() {
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            evaluator.event_loop().use_virtual_clock();
+            let started = Instant::now();
+
+            let (result, output) = capture(&mut evaluator, r#"
+                init record(name):
+                    log(name)
+
+                ticker = setInterval(record, 1000, "tick")
+                setTimeout(clearInterval, 3500, ticker)
+                setTimeout(record, 60000, "late")
+                cancelled = setTimeout(record, 10, "cancelled")
+                clearTimeout(cancelled)
+                setTimeout(record, 1500, "timeout")
+                await sleep(2500)
+                record("slept")
+            "#);
+
+            assert!(result.is_ok(), "{:?}", result);
+            assert_eq!(output, "tick\ntimeout\ntick\nslept\ntick\nlate");
+            assert_eq!(evaluator.event_loop().now(), 60000);
+            assert!(started.elapsed().as_secs() < 5);
+        }
+    }
+}
@@ -0,0 +1,214 @@
+use crate::parser::ast::{Ident, ImportTarget, Params};
+use crate::parser::tokens::TokenKind;
+
+use std::rc::Rc;
+
+// Operators the VM hands to `operators::binary`, indexed by `Op::Binary`.
+pub const BINARY_OPERATORS: [TokenKind; 16] = [
+    TokenKind::Plus,
+    TokenKind::Minus,
+    TokenKind::Star,
+    TokenKind::Slash,
+    TokenKind::Percent,
+    TokenKind::StarStar,
+    TokenKind::Greater,
+    TokenKind::GreaterEqual,
+    TokenKind::Less,
+    TokenKind::LessEqual,
+    TokenKind::Amp,
+    TokenKind::Pipe,
+    TokenKind::Caret,
+    TokenKind::LessLess,
+    TokenKind::GreaterGreater,
+    TokenKind::GreaterGreaterGreater,
+];
+
+// Operators the VM hands to `operators::unary`, indexed by `Op::Unary`.
+pub const UNARY_OPERATORS: [TokenKind; 3] = [TokenKind::Minus, TokenKind::Bang, TokenKind::Tilde];
+
+// One VM instruction. Operands are indices into the function's constant pool
+// or positions in its code, so every instruction is small and `Copy`.
+// Comments describe the stack before and after, top on the right.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Op {
+    // -> constants[index]
+    Constant(u32),
+    Undefined,
+    Null,
+    True,
+    False,
+    // value ->
+    Pop,
+    // value -> value value
+    Dup,
+    // a b -> a b a b
+    Dup2,
+    // a b value -> value; drops the given number of values under the top.
+    Nip(u32),
+
+    // Variables are indices into the chunk's `variables`, which say where
+    // the resolver placed each one.
+    GetVar(u32),
+    // value ->
+    SetVar(u32),
+    // ++x, x--: -> result
+    UpdateVar { variable: u32, increment: bool, prefix: bool },
+
+    // object -> object.name
+    GetMember(u32),
+    // object index -> object[index]
+    GetIndex,
+    // object key -> value; reads an assignment target
+    GetProperty,
+    // object key value -> value
+    SetProperty,
+    // object key -> result
+    UpdateProperty { increment: bool, prefix: bool },
+
+    // left right -> result
+    Binary(u8),
+    // operand -> result
+    Unary(u8),
+    Equal,
+    NotEqual,
+
+    // Jumps go to an absolute position in the function's code.
+    Jump(u32),
+    // condition ->
+    JumpIfFalse(u32),
+    // Keep the value when jumping, and pop it otherwise.
+    JumpIfFalsy(u32),
+    JumpIfTruthy(u32),
+    JumpIfNotNullish(u32),
+    // Peeks at the value and jumps without popping it.
+    JumpIfNotUndefined(u32),
+    // Ends a `?.` chain early: a `null` or `undefined` value becomes `undefined`.
+    JumpIfNullish(u32),
+
+    // items... -> array
+    Array(u32),
+    // array value -> array
+    Push,
+    // array iterable -> array
+    SpreadArray,
+    // -> {}
+    Object,
+    // object key value -> object
+    InitProperty,
+    // object value -> object
+    SpreadObject,
+    // parts... -> text; each part is shown as `log` would show it.
+    Template(u32),
+    // -> closure over the current scope for the function at the constant index
+    Closure(u32),
+
+    // callee args... -> result
+    Call(u32),
+    // callee [args] keyword values... -> result; the constant lists keyword names.
+    CallWith(u32),
+    Return,
+    // A `return` outside any function, which stops the script.
+    ReturnFromScript,
+    Await,
+
+    // iterable -> items 0
+    Iterate,
+    // items index -> items index+1 item, or jumps past the loop when done.
+    ForNext(u32),
+
+    // Handlers cover code until popped; a throw jumps to the handler's target.
+    PushCatch(u32),
+    PushFinally(u32),
+    PopHandlers(u32),
+    // error -> bool; whether the error's `type` is the name at the constant index
+    ErrorType(u32),
+    // value ->
+    Throw,
+    // Resumes whatever was unwinding when a `finally` block was entered.
+    EndFinally,
+
+    // value -> object; copies the object being destructured
+    DestructureObject,
+    // object -> object object.name
+    OwnProperty(u32),
+    // object -> object rest; the constant lists the keys already taken
+    ObjectRest(u32),
+    // value -> items
+    DestructureArray,
+    // items -> items items[index]
+    Element(u32),
+    // items -> items items[index..]
+    ElementsFrom(u32),
+
+    // value ->
+    Log,
+    // spec -> ; the constant is the import target
+    Import(u32),
+    // The constant lists names the module shares.
+    Share(u32),
+    // member values... -> enum
+    Enum(u32),
+    // subject patterns... -> ; jumps to the matching case
+    Match(u32),
+}
+
+#[derive(Debug)]
+pub enum Constant {
+    Number(f64),
+    String(String),
+    Function(Rc<CompiledFunction>),
+    Names(Vec<String>),
+    Import(ImportTarget),
+    Enum {
+        name: String,
+        members: Vec<String>,
+    },
+    // How many patterns each case has and where its body starts.
+    Match {
+        cases: Vec<(u32, u32)>,
+        otherwise: Option<u32>,
+        end: u32,
+    },
+}
+
+#[derive(Debug, Default)]
+pub struct Chunk {
+    pub code: Vec<Op>,
+    pub constants: Vec<Constant>,
+    pub variables: Vec<Ident>,
+}
+
+impl Chunk {
+    pub fn name(&self, index: u32) -> &str {
+        match &self.constants[index as usize] {
+            Constant::String(name) => name,
+            other => unreachable!("constant {} is not a name: {:?}", index, other),
+        }
+    }
+
+    pub fn variable(&self, index: u32) -> &Ident {
+        &self.variables[index as usize]
+    }
+
+    pub fn names(&self, index: u32) -> &[String] {
+        match &self.constants[index as usize] {
+            Constant::Names(names) => names,
+            other => unreachable!("constant {} is not a name list: {:?}", index, other),
+        }
+    }
+}
+
+// A function body, or a whole script, compiled once and shared by every
+// closure created from it.
+#[derive(Debug)]
+pub struct CompiledFunction {
+    // Kept to match keyword arguments and count positional ones.
+    pub params: Params,
+    pub chunk: Chunk,
+    pub is_async: bool,
+    // Where the body starts after the code binding parameters. Errors before
+    // it are thrown to the caller even when the function is async.
+    pub body_start: u32,
+    // Slots a call needs, as counted by the resolver.
+    pub locals: usize,
+}
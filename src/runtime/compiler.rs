@@ -0,0 +1,701 @@
+use crate::parser::ast::{Binding, Expr, Function, Ident, Params, Pattern, PropertyKey, Slot, Stmt, TemplatePart};
+use crate::parser::tokens::TokenKind;
+use crate::runtime::bytecode::{Chunk, CompiledFunction, Constant, Op, BINARY_OPERATORS, UNARY_OPERATORS};
+use crate::runtime::operators;
+
+use std::collections::HashMap;
+use std::rc::Rc;
+
+// Compiles a whole program. Running it gives `undefined`.
+pub fn compile_script(program: &[Stmt]) -> Rc<CompiledFunction> {
+    let mut compiler = Compiler::new(false);
+    compiler.block(program);
+    compiler.emit(Op::Undefined);
+    compiler.emit(Op::Return);
+    compiler.finish(Params::default(), false, 0, 0)
+}
+
+// Compiles a lone expression, such as the last entry typed into the REPL.
+// Running it gives the expression's value.
+pub fn compile_expression(expr: &Expr) -> Rc<CompiledFunction> {
+    let mut compiler = Compiler::new(false);
+    compiler.expr(expr);
+    compiler.emit(Op::Return);
+    compiler.finish(Params::default(), false, 0, 0)
+}
+
+// Compiles one function; the tree-walker uses it to hand async functions to
+// the VM, which can suspend them at each `await`. Arguments arrive on the
+// stack with the first parameter on top, so the prologue binds them in order
+// and defaults can see earlier parameters.
+pub fn compile_function(function: &Function) -> Rc<CompiledFunction> {
+    let Function { params, body, is_async, locals } = function;
+    let mut compiler = Compiler::new(true);
+    for binding in &params.positional {
+        compiler.bind_with_default(binding);
+    }
+    if let Some(rest) = &params.rest {
+        let rest = compiler.variable(rest);
+        compiler.emit(Op::SetVar(rest));
+    }
+
+    let body_start = compiler.here();
+    compiler.block(body);
+    compiler.emit(Op::Null);
+    compiler.emit(Op::Return);
+    compiler.finish(params.clone(), *is_async, body_start, *locals)
+}
+
+struct Compiler<'a> {
+    chunk: Chunk,
+    // Constants already in the pool, so each name or number is stored once.
+    strings: HashMap<String, u32>,
+    numbers: HashMap<u64, u32>,
+    variables: HashMap<(String, Slot), u32>,
+    // `try` handlers open at this point, innermost last: the `finally` body,
+    // or `None` for a handler that only catches.
+    handlers: Vec<Option<&'a [Stmt]>>,
+    in_function: bool,
+}
+
+impl<'a> Compiler<'a> {
+    fn new(in_function: bool) -> Self {
+        Self {
+            chunk: Chunk::default(),
+            strings: HashMap::new(),
+            numbers: HashMap::new(),
+            variables: HashMap::new(),
+            handlers: Vec::new(),
+            in_function,
+        }
+    }
+
+    fn finish(self, params: Params, is_async: bool, body_start: u32, locals: usize) -> Rc<CompiledFunction> {
+        Rc::new(CompiledFunction {
+            params,
+            chunk: self.chunk,
+            is_async,
+            body_start,
+            locals,
+        })
+    }
+
+    fn emit(&mut self, op: Op) -> usize {
+        self.chunk.code.push(op);
+        self.chunk.code.len() - 1
+    }
+
+    fn here(&self) -> u32 {
+        self.chunk.code.len() as u32
+    }
+
+    // Points the jump at `at` to the next instruction to be emitted.
+    fn patch(&mut self, at: usize) {
+        let target = self.here();
+        match &mut self.chunk.code[at] {
+            Op::Jump(to)
+            | Op::JumpIfFalse(to)
+            | Op::JumpIfFalsy(to)
+            | Op::JumpIfTruthy(to)
+            | Op::JumpIfNotNullish(to)
+            | Op::JumpIfNotUndefined(to)
+            | Op::JumpIfNullish(to)
+            | Op::ForNext(to)
+            | Op::PushCatch(to)
+            | Op::PushFinally(to) => *to = target,
+            other => unreachable!("{:?} has no jump target", other),
+        }
+    }
+
+    fn constant(&mut self, constant: Constant) -> u32 {
+        self.chunk.constants.push(constant);
+        (self.chunk.constants.len() - 1) as u32
+    }
+
+    fn string(&mut self, text: &str) -> u32 {
+        if let Some(&index) = self.strings.get(text) {
+            return index;
+        }
+        let index = self.constant(Constant::String(text.to_string()));
+        self.strings.insert(text.to_string(), index);
+        index
+    }
+
+    fn number(&mut self, number: f64) -> u32 {
+        if let Some(&index) = self.numbers.get(&number.to_bits()) {
+            return index;
+        }
+        let index = self.constant(Constant::Number(number));
+        self.numbers.insert(number.to_bits(), index);
+        index
+    }
+
+    fn variable(&mut self, variable: &Ident) -> u32 {
+        let key = (variable.name.clone(), variable.slot);
+        if let Some(&index) = self.variables.get(&key) {
+            return index;
+        }
+        self.chunk.variables.push(variable.clone());
+        let index = (self.chunk.variables.len() - 1) as u32;
+        self.variables.insert(key, index);
+        index
+    }
+
+    fn names(&mut self, names: Vec<String>) -> u32 {
+        self.constant(Constant::Names(names))
+    }
+
+    fn function(&mut self, function: &Function) -> u32 {
+        let function = compile_function(function);
+        self.constant(Constant::Function(function))
+    }
+
+    fn block(&mut self, body: &'a [Stmt]) {
+        for stmt in body {
+            self.stmt(stmt);
+        }
+    }
+
+    fn stmt(&mut self, stmt: &'a Stmt) {
+        match stmt {
+            Stmt::Init { name, function } => {
+                let function = self.function(function);
+                self.emit(Op::Closure(function));
+                let name = self.variable(name);
+                self.emit(Op::SetVar(name));
+            }
+
+            Stmt::Log { value } => {
+                self.expr(value);
+                self.emit(Op::Log);
+            }
+
+            Stmt::Assign { name, value } => {
+                self.expr(value);
+                let name = self.variable(name);
+                self.emit(Op::SetVar(name));
+            }
+
+            Stmt::Destructure { pattern, value } => {
+                self.expr(value);
+                self.bind(pattern);
+            }
+
+            Stmt::For { pattern, iterable, body } => {
+                self.expr(iterable);
+                self.emit(Op::Iterate);
+                let top = self.here();
+                let next = self.emit(Op::ForNext(0));
+                self.bind(pattern);
+                self.block(body);
+                self.emit(Op::Jump(top));
+                self.patch(next);
+            }
+
+            Stmt::Get { target, source } => {
+                self.expr(source);
+                let target = self.constant(Constant::Import(target.clone()));
+                self.emit(Op::Import(target));
+            }
+
+            Stmt::Share(inner) => {
+                let names = self.names(inner.bound_names());
+                self.emit(Op::Share(names));
+                self.stmt(inner);
+            }
+
+            Stmt::Try { body, catches, finally } => {
+                let finally_handler = finally.as_deref().map(|finally| {
+                    self.handlers.push(Some(finally));
+                    self.emit(Op::PushFinally(0))
+                });
+
+                if catches.is_empty() {
+                    self.block(body);
+                } else {
+                    self.handlers.push(None);
+                    let handler = self.emit(Op::PushCatch(0));
+                    self.block(body);
+                    self.emit(Op::PopHandlers(1));
+                    self.handlers.pop();
+                    let mut exits = vec![self.emit(Op::Jump(0))];
+
+                    // The error is on the stack; clauses that don't match pass it on.
+                    self.patch(handler);
+                    for clause in catches {
+                        let next = clause.error_type.as_ref().map(|error_type| {
+                            self.emit(Op::Dup);
+                            let error_type = self.string(error_type);
+                            self.emit(Op::ErrorType(error_type));
+                            self.emit(Op::JumpIfFalse(0))
+                        });
+                        match &clause.name {
+                            Some(name) => {
+                                let name = self.variable(name);
+                                self.emit(Op::SetVar(name));
+                            }
+                            None => {
+                                self.emit(Op::Pop);
+                            }
+                        }
+                        self.block(&clause.body);
+                        exits.push(self.emit(Op::Jump(0)));
+                        if let Some(next) = next {
+                            self.patch(next);
+                        }
+                    }
+                    self.emit(Op::Throw);
+                    for exit in exits {
+                        self.patch(exit);
+                    }
+                }
+
+                // `finally` is compiled twice: inline for when the body completes, and
+                // at the handler for when something unwinds through it.
+                if let (Some(handler), Some(finally)) = (finally_handler, finally) {
+                    self.emit(Op::PopHandlers(1));
+                    self.handlers.pop();
+                    self.block(finally);
+                    let done = self.emit(Op::Jump(0));
+                    self.patch(handler);
+                    self.block(finally);
+                    self.emit(Op::EndFinally);
+                    self.patch(done);
+                }
+            }
+
+            Stmt::Throw { value } => {
+                self.expr(value);
+                self.emit(Op::Throw);
+            }
+
+            Stmt::Enum { name, members } => {
+                for (index, (_, value)) in members.iter().enumerate() {
+                    match value {
+                        Some(value) => self.expr(value),
+                        None => {
+                            let index = self.number(index as f64);
+                            self.emit(Op::Constant(index));
+                        }
+                    }
+                }
+                let members = members.iter().map(|(member, _)| member.clone()).collect();
+                let definition = self.constant(Constant::Enum { name: name.name.clone(), members });
+                self.emit(Op::Enum(definition));
+                let name = self.variable(name);
+                self.emit(Op::SetVar(name));
+            }
+
+            Stmt::Match { subject, cases, otherwise } => {
+                self.expr(subject);
+                for case in cases {
+                    for pattern in &case.patterns {
+                        self.expr(pattern);
+                    }
+                }
+                let table = self.constant(Constant::Match { cases: Vec::new(), otherwise: None, end: 0 });
+                self.emit(Op::Match(table));
+
+                let mut arms = Vec::new();
+                let mut exits = Vec::new();
+                for case in cases {
+                    arms.push((case.patterns.len() as u32, self.here()));
+                    self.block(&case.body);
+                    exits.push(self.emit(Op::Jump(0)));
+                }
+                let otherwise_start = otherwise.as_ref().map(|body| {
+                    let start = self.here();
+                    self.block(body);
+                    start
+                });
+                for exit in exits {
+                    self.patch(exit);
+                }
+
+                self.chunk.constants[table as usize] = Constant::Match {
+                    cases: arms,
+                    otherwise: otherwise_start,
+                    end: self.here(),
+                };
+            }
+
+            Stmt::Return { value } => {
+                match value {
+                    Some(value) => self.expr(value),
+                    None => {
+                        self.emit(Op::Null);
+                    }
+                }
+                self.run_finally_blocks();
+                self.emit(if self.in_function { Op::Return } else { Op::ReturnFromScript });
+            }
+
+            Stmt::ExprStmt(expr) => {
+                self.expr(expr);
+                self.emit(Op::Pop);
+            }
+        }
+    }
+
+    // A `return` leaves every open `try`, so their `finally` blocks run first,
+    // innermost out, each outside the handlers it was guarding.
+    fn run_finally_blocks(&mut self) {
+        let open = self.handlers.clone();
+        let mut pops = 0;
+        while let Some(handler) = self.handlers.pop() {
+            pops += 1;
+            if let Some(finally) = handler {
+                self.emit(Op::PopHandlers(pops));
+                pops = 0;
+                self.block(finally);
+            }
+        }
+        self.handlers = open;
+    }
+
+    // Binds the value on top of the stack and pops it.
+    fn bind(&mut self, pattern: &Pattern) {
+        match pattern {
+            Pattern::Name(name) => {
+                let name = self.variable(name);
+                self.emit(Op::SetVar(name));
+            }
+
+            Pattern::Object { properties, rest } => {
+                self.emit(Op::DestructureObject);
+                for (key, binding) in properties {
+                    let key = self.string(key);
+                    self.emit(Op::OwnProperty(key));
+                    self.bind_with_default(binding);
+                }
+                if let Some(rest) = rest {
+                    let taken = self.names(properties.iter().map(|(key, _)| key.clone()).collect());
+                    self.emit(Op::ObjectRest(taken));
+                    let rest = self.variable(rest);
+                    self.emit(Op::SetVar(rest));
+                }
+                self.emit(Op::Pop);
+            }
+
+            Pattern::Array { items, rest } => {
+                self.emit(Op::DestructureArray);
+                for (index, binding) in items.iter().enumerate() {
+                    self.emit(Op::Element(index as u32));
+                    self.bind_with_default(binding);
+                }
+                if let Some(rest) = rest {
+                    self.emit(Op::ElementsFrom(items.len() as u32));
+                    self.bind(rest);
+                }
+                self.emit(Op::Pop);
+            }
+        }
+    }
+
+    fn bind_with_default(&mut self, binding: &Binding) {
+        if let Some(default) = &binding.default {
+            let given = self.emit(Op::JumpIfNotUndefined(0));
+            self.emit(Op::Pop);
+            self.expr(default);
+            self.patch(given);
+        }
+        self.bind(&binding.target);
+    }
+
+    fn expr(&mut self, expr: &Expr) {
+        match expr {
+            Expr::Literal(text) => {
+                let text = self.string(text);
+                self.emit(Op::Constant(text));
+            }
+            Expr::Number(number) => {
+                let number = self.number(*number);
+                self.emit(Op::Constant(number));
+            }
+            Expr::Boolean(true) => {
+                self.emit(Op::True);
+            }
+            Expr::Boolean(false) => {
+                self.emit(Op::False);
+            }
+            Expr::Null => {
+                self.emit(Op::Null);
+            }
+            Expr::Undefined => {
+                self.emit(Op::Undefined);
+            }
+            Expr::Template(template) => self.template(template),
+
+            Expr::Function(function) => {
+                let function = self.function(function);
+                self.emit(Op::Closure(function));
+            }
+
+            Expr::Await(value) => {
+                self.expr(value);
+                self.emit(Op::Await);
+            }
+
+            chain @ (Expr::Member { .. } | Expr::Index { .. } | Expr::Call { .. }) => {
+                let mut exits = Vec::new();
+                self.chain(chain, &mut exits);
+                for exit in exits {
+                    self.patch(exit);
+                }
+            }
+
+            Expr::Unary { operator, operand } => {
+                self.expr(operand);
+                self.emit(Op::Unary(operator_index(&UNARY_OPERATORS, operator)));
+            }
+
+            Expr::Binary { left, operator, right } => {
+                self.expr(left);
+                self.expr(right);
+                self.emit(match operator {
+                    TokenKind::DoubleEqual => Op::Equal,
+                    TokenKind::NotEqual => Op::NotEqual,
+                    operator => Op::Binary(operator_index(&BINARY_OPERATORS, operator)),
+                });
+            }
+
+            Expr::Logical { left, operator, right } => {
+                self.expr(left);
+                let skip = self.emit(match operator {
+                    TokenKind::QuestionQuestion => Op::JumpIfNotNullish(0),
+                    TokenKind::AmpAmp => Op::JumpIfFalsy(0),
+                    TokenKind::PipePipe => Op::JumpIfTruthy(0),
+                    other => unreachable!("parser produced unsupported logical operator {:?}", other),
+                });
+                self.expr(right);
+                self.patch(skip);
+            }
+
+            Expr::Assign { target, operator, value } => self.assign(target, operator, value),
+
+            Expr::Update { target, operator, prefix } => {
+                let increment = *operator == TokenKind::PlusPlus;
+                match &**target {
+                    Expr::Variable(variable) => {
+                        let variable = self.variable(variable);
+                        self.emit(Op::UpdateVar { variable, increment, prefix: *prefix });
+                    }
+                    target => {
+                        self.place(target);
+                        self.emit(Op::UpdateProperty { increment, prefix: *prefix });
+                    }
+                }
+            }
+
+            Expr::Array(items) => self.elements(items),
+
+            Expr::Spread(_) => unreachable!("parser only allows spread in arrays, objects and calls"),
+            Expr::Keyword { .. } => unreachable!("parser only allows keyword arguments in calls"),
+
+            Expr::Object(fields) => {
+                self.emit(Op::Object);
+                for (key, value) in fields {
+                    match key {
+                        PropertyKey::Named(name) => {
+                            let name = self.string(name);
+                            self.emit(Op::Constant(name));
+                        }
+                        PropertyKey::Computed(key) => self.expr(key),
+                        PropertyKey::Spread => {
+                            self.expr(value);
+                            self.emit(Op::SpreadObject);
+                            continue;
+                        }
+                    }
+                    self.expr(value);
+                    self.emit(Op::InitProperty);
+                }
+            }
+
+            Expr::Variable(variable) => {
+                let variable = self.variable(variable);
+                self.emit(Op::GetVar(variable));
+            }
+        }
+    }
+
+    // Member, index and call chains. Every `?.` link adds an exit that skips
+    // the rest of the chain when the value before it is `null` or `undefined`.
+    fn chain(&mut self, expr: &Expr, exits: &mut Vec<usize>) {
+        match expr {
+            Expr::Member { object, property, optional } => {
+                self.link(object, *optional, exits);
+                let property = self.string(property);
+                self.emit(Op::GetMember(property));
+            }
+
+            Expr::Index { object, index, optional } => {
+                self.link(object, *optional, exits);
+                self.expr(index);
+                self.emit(Op::GetIndex);
+            }
+
+            Expr::Call { callee, arguments, optional } => {
+                self.link(callee, *optional, exits);
+                self.arguments(arguments);
+            }
+
+            other => self.expr(other),
+        }
+    }
+
+    fn link(&mut self, target: &Expr, optional: bool, exits: &mut Vec<usize>) {
+        self.chain(target, exits);
+        if optional {
+            exits.push(self.emit(Op::JumpIfNullish(0)));
+        }
+    }
+
+    // Plain calls push their arguments one by one; calls with spreads or
+    // keywords gather the positional ones into an array first.
+    fn arguments(&mut self, arguments: &[Expr]) {
+        let simple = arguments.iter().all(|arg| !matches!(arg, Expr::Spread(_) | Expr::Keyword { .. }));
+        if simple {
+            for arg in arguments {
+                self.expr(arg);
+            }
+            self.emit(Op::Call(arguments.len() as u32));
+            return;
+        }
+
+        // The parser puts keyword arguments after every positional one.
+        let split = arguments
+            .iter()
+            .position(|arg| matches!(arg, Expr::Keyword { .. }))
+            .unwrap_or(arguments.len());
+        let (positional, keywords) = arguments.split_at(split);
+        self.elements(positional);
+
+        let mut names = Vec::new();
+        for keyword in keywords {
+            if let Expr::Keyword { name, value } = keyword {
+                names.push(name.clone());
+                self.expr(value);
+            }
+        }
+        let names = self.names(names);
+        self.emit(Op::CallWith(names));
+    }
+
+    // Array items or positional arguments, expanding spreads in place.
+    fn elements(&mut self, elements: &[Expr]) {
+        if !elements.iter().any(|element| matches!(element, Expr::Spread(_))) {
+            for element in elements {
+                self.expr(element);
+            }
+            self.emit(Op::Array(elements.len() as u32));
+            return;
+        }
+
+        self.emit(Op::Array(0));
+        for element in elements {
+            match element {
+                Expr::Spread(iterable) => {
+                    self.expr(iterable);
+                    self.emit(Op::SpreadArray);
+                }
+                element => {
+                    self.expr(element);
+                    self.emit(Op::Push);
+                }
+            }
+        }
+    }
+
+    // Pushes the object and key an assignment writes to.
+    fn place(&mut self, target: &Expr) {
+        match target {
+            Expr::Member { object, property, .. } => {
+                self.expr(object);
+                let property = self.string(property);
+                self.emit(Op::Constant(property));
+            }
+            Expr::Index { object, index, .. } => {
+                self.expr(object);
+                self.expr(index);
+            }
+            other => unreachable!("parser only allows variables, members and indices as targets, got {:?}", other),
+        }
+    }
+
+    fn assign(&mut self, target: &Expr, operator: &TokenKind, value: &Expr) {
+        if let Expr::Variable(name) = target {
+            let name = self.variable(name);
+            match operator {
+                TokenKind::Equal => self.expr(value),
+                TokenKind::QuestionQuestionEqual => {
+                    self.emit(Op::GetVar(name));
+                    let keep = self.emit(Op::JumpIfNotNullish(0));
+                    self.expr(value);
+                    self.emit(Op::Dup);
+                    self.emit(Op::SetVar(name));
+                    self.patch(keep);
+                    return;
+                }
+                operator => {
+                    self.emit(Op::GetVar(name));
+                    self.expr(value);
+                    self.emit(Op::Binary(operator_index(&BINARY_OPERATORS, &operators::compound(operator))));
+                }
+            }
+            self.emit(Op::Dup);
+            self.emit(Op::SetVar(name));
+            return;
+        }
+
+        self.place(target);
+        match operator {
+            TokenKind::Equal => self.expr(value),
+            TokenKind::QuestionQuestionEqual => {
+                self.emit(Op::Dup2);
+                self.emit(Op::GetProperty);
+                let keep = self.emit(Op::JumpIfNotNullish(0));
+                self.expr(value);
+                self.emit(Op::SetProperty);
+                let done = self.emit(Op::Jump(0));
+                self.patch(keep);
+                self.emit(Op::Nip(2));
+                self.patch(done);
+                return;
+            }
+            operator => {
+                self.emit(Op::Dup2);
+                self.emit(Op::GetProperty);
+                self.expr(value);
+                self.emit(Op::Binary(operator_index(&BINARY_OPERATORS, &operators::compound(operator))));
+            }
+        }
+        self.emit(Op::SetProperty);
+    }
+
+    // Each part goes on the stack, then `Template` joins them.
+    fn template(&mut self, parts: &[TemplatePart]) {
+        for part in parts {
+            match part {
+                TemplatePart::Text(text) => {
+                    let text = self.string(text);
+                    self.emit(Op::Constant(text));
+                }
+                TemplatePart::Expr(expr) => self.expr(expr),
+            }
+        }
+        if parts.is_empty() {
+            let empty = self.string("");
+            self.emit(Op::Constant(empty));
+        }
+        self.emit(Op::Template(parts.len().max(1) as u32));
+    }
+}
+
+// The position of `operator` in one of the VM's operator tables.
+fn operator_index(table: &[TokenKind], operator: &TokenKind) -> u8 {
+    match table.iter().position(|candidate| candidate == operator) {
+        Some(index) => index as u8,
+        None => unreachable!("parser produced unsupported operator {:?}", operator),
+    }
+}
@@ -0,0 +1,372 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+const MS_PER_MINUTE: i64 = 60_000;
+const MS_PER_DAY: i64 = 86_400_000;
+// Dates reach 100 million days either side of the epoch, as in JavaScript.
+pub const MAX_MILLIS: i64 = 100_000_000 * MS_PER_DAY;
+// Comfortably past MAX_MILLIS, and small enough that the calendar maths can't overflow.
+const MAX_YEAR: i64 = 1_000_000;
+
+const MONTH_NAMES: [&str; 12] = [
+    "January", "February", "March", "April", "May", "June",
+    "July", "August", "September", "October", "November", "December",
+];
+const WEEKDAY_NAMES: [&str; 7] = ["Sunday", "Monday", "Tuesday", "Wednesday", "Thursday", "Friday", "Saturday"];
+
+// An instant in time plus the fixed UTC offset it is displayed in. Two dates
+// are the same instant when their `millis` match, whatever their offsets.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct DateTime {
+    // Milliseconds since 1970-01-01T00:00:00Z.
+    pub millis: i64,
+    // Minutes east of UTC, e.g. 330 for +05:30.
+    pub offset: i32,
+}
+
+// Calendar fields as seen in the date's own offset.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Parts {
+    pub year: i64,
+    pub month: u32,
+    pub day: u32,
+    pub hour: u32,
+    pub minute: u32,
+    pub second: u32,
+    pub millisecond: u32,
+    // 0 is Sunday.
+    pub weekday: u32,
+}
+
+impl DateTime {
+    pub fn now() -> Self {
+        let millis = match SystemTime::now().duration_since(UNIX_EPOCH) {
+            Ok(elapsed) => elapsed.as_millis() as i64,
+            Err(before) => -(before.duration().as_millis() as i64),
+        };
+        Self { millis, offset: 0 }
+    }
+
+    // None when the instant is more than MAX_MILLIS from the epoch.
+    pub fn from_millis(millis: i64) -> Option<Self> {
+        (millis.abs() <= MAX_MILLIS).then_some(Self { millis, offset: 0 })
+    }
+
+    // Builds a date from wall-clock `[year, month, day, hour, minute, second,
+    // millisecond]` in the given offset. Out-of-range fields roll over, so
+    // month 13 is January of the next year.
+    pub fn from_parts(fields: [i64; 7], offset: i32) -> Option<Self> {
+        let [year, month, day, hour, minute, second, millisecond] = fields.map(i128::from);
+        let months = year * 12 + (month - 1);
+        let year = months.div_euclid(12);
+        if year.abs() > MAX_YEAR as i128 {
+            return None;
+        }
+        let days = days_from_civil(year as i64, (months.rem_euclid(12) + 1) as u32, 1) as i128 + (day - 1);
+        let local = days * MS_PER_DAY as i128 + ((hour * 60 + minute) * 60 + second) * 1000 + millisecond;
+        let millis = local - (offset as i64 * MS_PER_MINUTE) as i128;
+        if millis.abs() > MAX_MILLIS as i128 {
+            return None;
+        }
+        Some(Self { millis: millis as i64, offset })
+    }
+
+    pub fn parts(&self) -> Parts {
+        let local = self.millis + self.offset as i64 * MS_PER_MINUTE;
+        let days = local.div_euclid(MS_PER_DAY);
+        let time = local.rem_euclid(MS_PER_DAY);
+        let (year, month, day) = civil_from_days(days);
+        Parts {
+            year,
+            month,
+            day,
+            hour: (time / 3_600_000) as u32,
+            minute: (time / 60_000 % 60) as u32,
+            second: (time / 1000 % 60) as u32,
+            millisecond: (time % 1000) as u32,
+            weekday: (days + 4).rem_euclid(7) as u32,
+        }
+    }
+
+    // The same instant, displayed in another offset.
+    pub fn with_offset(self, offset: i32) -> Self {
+        Self { offset, ..self }
+    }
+
+    // None when the result falls outside the supported range, as for add_months.
+    pub fn add_millis(self, millis: i64) -> Option<Self> {
+        let millis = Self::from_millis(self.millis.checked_add(millis)?)?.millis;
+        Some(Self { millis, ..self })
+    }
+
+    // Calendar months, clamping the day so Jan 31 + 1 month is the end of February.
+    pub fn add_months(self, months: i64) -> Option<Self> {
+        let parts = self.parts();
+        let total = (parts.year * 12 + (parts.month as i64 - 1)).checked_add(months)?;
+        let (year, month) = (total.div_euclid(12), total.rem_euclid(12) as u32 + 1);
+        let day = parts.day.min(days_in_month(year, month));
+        let time = [parts.hour, parts.minute, parts.second, parts.millisecond].map(i64::from);
+        Self::from_parts([year, month as i64, day as i64, time[0], time[1], time[2], time[3]], self.offset)
+    }
+
+    // Accepts `YYYY-MM-DD`, optionally followed by `THH:MM[:SS[.fff]]` and
+    // `Z` or an offset such as `+05:30`. Times without an offset are UTC.
+    pub fn parse(text: &str) -> Result<Self, String> {
+        let invalid = || format!("Invalid ISO-8601 date '{}'", text);
+        let mut reader = DigitReader { bytes: text.trim().as_bytes(), position: 0 };
+
+        let year = reader.digits(4).ok_or_else(invalid)?;
+        reader.expect(b'-').ok_or_else(invalid)?;
+        let month = reader.digits(2).ok_or_else(invalid)?;
+        reader.expect(b'-').ok_or_else(invalid)?;
+        let day = reader.digits(2).ok_or_else(invalid)?;
+
+        let (mut hour, mut minute, mut second, mut millisecond, mut offset) = (0, 0, 0, 0, 0);
+        if !reader.is_at_end() {
+            if !matches!(reader.next(), Some(b'T' | b't' | b' ')) {
+                return Err(invalid());
+            }
+            hour = reader.digits(2).ok_or_else(invalid)?;
+            reader.expect(b':').ok_or_else(invalid)?;
+            minute = reader.digits(2).ok_or_else(invalid)?;
+            if reader.peek() == Some(b':') {
+                reader.next();
+                second = reader.digits(2).ok_or_else(invalid)?;
+                if matches!(reader.peek(), Some(b'.' | b',')) {
+                    reader.next();
+                    millisecond = reader.fraction_millis().ok_or_else(invalid)?;
+                }
+            }
+            let rest = &text.trim()[reader.position..];
+            if !rest.is_empty() {
+                offset = parse_offset(rest).map_err(|_| invalid())?;
+            }
+        }
+
+        let valid_day = (1..=12).contains(&month) && day >= 1 && day <= days_in_month(year, month as u32) as i64;
+        if !valid_day || hour > 23 || minute > 59 || second > 59 {
+            return Err(invalid());
+        }
+        Self::from_parts([year, month, day, hour, minute, second, millisecond], offset).ok_or_else(invalid)
+    }
+
+    // `2024-03-09T14:05:00.000Z`, or with the offset in place of `Z`.
+    pub fn to_iso(&self) -> String {
+        self.format("YYYY-MM-DD[T]HH:mm:ss.SSSZ")
+    }
+
+    // Moment-style tokens; text inside `[...]` is copied as-is.
+    pub fn format(&self, pattern: &str) -> String {
+        const TOKENS: [&str; 22] = [
+            "YYYY", "YY", "MMMM", "MMM", "MM", "M", "DD", "D", "dddd", "ddd", "HH",
+            "H", "hh", "h", "mm", "m", "ss", "s", "SSS", "A", "ZZ", "Z",
+        ];
+
+        let parts = self.parts();
+        let hour12 = if parts.hour.is_multiple_of(12) { 12 } else { parts.hour % 12 };
+        let mut out = String::new();
+        let mut rest = pattern;
+
+        while let Some(c) = rest.chars().next() {
+            if c == '[' {
+                let end = rest.find(']').unwrap_or(rest.len());
+                out.push_str(&rest[1..end]);
+                rest = rest.get(end + 1..).unwrap_or("");
+                continue;
+            }
+
+            let Some(token) = TOKENS.iter().find(|token| rest.starts_with(*token)) else {
+                out.push(c);
+                rest = &rest[c.len_utf8()..];
+                continue;
+            };
+
+            let text = match *token {
+                "YYYY" => format!("{:04}", parts.year),
+                "YY" => format!("{:02}", parts.year.rem_euclid(100)),
+                "MMMM" => MONTH_NAMES[parts.month as usize - 1].to_string(),
+                "MMM" => MONTH_NAMES[parts.month as usize - 1][..3].to_string(),
+                "MM" => format!("{:02}", parts.month),
+                "M" => parts.month.to_string(),
+                "DD" => format!("{:02}", parts.day),
+                "D" => parts.day.to_string(),
+                "dddd" => WEEKDAY_NAMES[parts.weekday as usize].to_string(),
+                "ddd" => WEEKDAY_NAMES[parts.weekday as usize][..3].to_string(),
+                "HH" => format!("{:02}", parts.hour),
+                "H" => parts.hour.to_string(),
+                "hh" => format!("{:02}", hour12),
+                "h" => hour12.to_string(),
+                "mm" => format!("{:02}", parts.minute),
+                "m" => parts.minute.to_string(),
+                "ss" => format!("{:02}", parts.second),
+                "s" => parts.second.to_string(),
+                "SSS" => format!("{:03}", parts.millisecond),
+                "A" => if parts.hour < 12 { "AM" } else { "PM" }.to_string(),
+                "ZZ" => format_offset(self.offset, ""),
+                _ if self.offset == 0 => "Z".to_string(),
+                _ => format_offset(self.offset, ":"),
+            };
+            out.push_str(&text);
+            rest = &rest[token.len()..];
+        }
+        out
+    }
+}
+
+// `Z`, `+05:30`, `-0800` or `+05`, as minutes east of UTC.
+pub fn parse_offset(text: &str) -> Result<i32, String> {
+    let invalid = || format!("Invalid UTC offset '{}'", text);
+    if text.eq_ignore_ascii_case("z") {
+        return Ok(0);
+    }
+
+    let sign = match text.as_bytes().first() {
+        Some(b'+') => 1,
+        Some(b'-') => -1,
+        _ => return Err(invalid()),
+    };
+    let mut reader = DigitReader { bytes: &text.as_bytes()[1..], position: 0 };
+    let hours = reader.digits(2).ok_or_else(invalid)?;
+    if reader.peek() == Some(b':') {
+        reader.next();
+    }
+    let minutes = if reader.is_at_end() { 0 } else { reader.digits(2).ok_or_else(invalid)? };
+    if !reader.is_at_end() || hours > 23 || minutes > 59 {
+        return Err(invalid());
+    }
+    Ok(sign * (hours * 60 + minutes) as i32)
+}
+
+fn format_offset(offset: i32, separator: &str) -> String {
+    let sign = if offset < 0 { '-' } else { '+' };
+    let offset = offset.abs();
+    format!("{}{:02}{}{:02}", sign, offset / 60, separator, offset % 60)
+}
+
+pub fn days_in_month(year: i64, month: u32) -> u32 {
+    match month {
+        2 if year % 4 == 0 && (year % 100 != 0 || year % 400 == 0) => 29,
+        2 => 28,
+        4 | 6 | 9 | 11 => 30,
+        _ => 31,
+    }
+}
+
+// Days since 1970-01-01 for a proleptic Gregorian date (Howard Hinnant's algorithm).
+fn days_from_civil(year: i64, month: u32, day: u32) -> i64 {
+    let year = if month <= 2 { year - 1 } else { year };
+    let era = year.div_euclid(400);
+    let year_of_era = year - era * 400;
+    let month = month as i64;
+    let day_of_year = (153 * (if month > 2 { month - 3 } else { month + 9 }) + 2) / 5 + day as i64 - 1;
+    let day_of_era = year_of_era * 365 + year_of_era / 4 - year_of_era / 100 + day_of_year;
+    era * 146_097 + day_of_era - 719_468
+}
+
+fn civil_from_days(days: i64) -> (i64, u32, u32) {
+    let days = days + 719_468;
+    let era = days.div_euclid(146_097);
+    let day_of_era = days - era * 146_097;
+    let year_of_era = (day_of_era - day_of_era / 1460 + day_of_era / 36_524 - day_of_era / 146_096) / 365;
+    let day_of_year = day_of_era - (365 * year_of_era + year_of_era / 4 - year_of_era / 100);
+    let month_index = (5 * day_of_year + 2) / 153;
+    let day = (day_of_year - (153 * month_index + 2) / 5 + 1) as u32;
+    let month = if month_index < 10 { month_index + 3 } else { month_index - 9 } as u32;
+    let year = year_of_era + era * 400 + if month <= 2 { 1 } else { 0 };
+    (year, month, day)
+}
+
+struct DigitReader<'a> {
+    bytes: &'a [u8],
+    position: usize,
+}
+
+impl DigitReader<'_> {
+    fn is_at_end(&self) -> bool {
+        self.position >= self.bytes.len()
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.position).copied()
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+        self.position += 1;
+        Some(byte)
+    }
+
+    fn expect(&mut self, expected: u8) -> Option<()> {
+        (self.next()? == expected).then_some(())
+    }
+
+    fn digits(&mut self, count: usize) -> Option<i64> {
+        let mut value = 0;
+        for _ in 0..count {
+            let byte = self.next().filter(u8::is_ascii_digit)?;
+            value = value * 10 + (byte - b'0') as i64;
+        }
+        Some(value)
+    }
+
+    // Reads any number of fraction digits, keeping millisecond precision.
+    fn fraction_millis(&mut self) -> Option<i64> {
+        let mut millis = 0;
+        let mut count = 0;
+        while let Some(byte) = self.peek().filter(u8::is_ascii_digit) {
+            self.position += 1;
+            if count < 3 {
+                millis = millis * 10 + (byte - b'0') as i64;
+            }
+            count += 1;
+        }
+        if count == 0 {
+            return None;
+        }
+        Some(millis * 10_i64.pow(3_u32.saturating_sub(count)))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_iso_dates_with_offsets() {
+        let date = DateTime::parse("2024-02-29T23:30:15.25+05:30").unwrap();
+        assert_eq!(date.offset, 330);
+        assert_eq!(date.millis, 1_709_229_615_250);
+        assert_eq!(date.to_iso(), "2024-02-29T23:30:15.250+05:30");
+        assert_eq!(date.with_offset(0).to_iso(), "2024-02-29T18:00:15.250Z");
+
+        assert_eq!(DateTime::parse("1969-12-31").unwrap().millis, -MS_PER_DAY);
+        assert_eq!(DateTime::parse("2024-01-02 03:04Z").unwrap().to_iso(), "2024-01-02T03:04:00.000Z");
+
+        for invalid in ["2023-02-29", "2024-13-01", "2024-01-01T24:00", "2024-1-01", "2024-01-01T10:00+5"] {
+            assert!(DateTime::parse(invalid).is_err(), "{} should not parse", invalid);
+        }
+    }
+
+    #[test]
+    fn formats_and_adds_calendar_months() {
+        let date = DateTime::parse("2024-01-31T15:04:05.006-08:00").unwrap();
+        assert_eq!(date.format("YYYY-MM-DD"), "2024-01-31");
+        assert_eq!(date.format("ddd, D MMM YY h:mm A ZZ [at] SSS"), "Wed, 31 Jan 24 3:04 PM -0800 at 006");
+        assert_eq!(date.parts().weekday, 3);
+
+        assert_eq!(date.add_months(1).unwrap().format("YYYY-MM-DD HH:mm"), "2024-02-29 15:04");
+        assert_eq!(date.add_months(-13).unwrap().format("YYYY-MM-DD"), "2022-12-31");
+        assert_eq!(date.add_millis(9 * 3_600_000).unwrap().format("YYYY-MM-DD HH"), "2024-02-01 00");
+    }
+
+    #[test]
+    fn dates_stay_within_the_supported_range() {
+        let epoch = DateTime::from_millis(0).unwrap();
+        assert_eq!(DateTime::from_millis(MAX_MILLIS).unwrap().to_iso(), "275760-09-13T00:00:00.000Z");
+        assert_eq!(DateTime::from_millis(-MAX_MILLIS - 1), None);
+        assert_eq!(epoch.add_millis(i64::MAX), None);
+        assert_eq!(epoch.add_months(300_000_000 * 12), None);
+        assert_eq!(epoch.add_months(i64::MIN), None);
+        assert_eq!(DateTime::from_parts([i64::MAX, i64::MIN, 1, 0, 0, 0, 0], 0), None);
+        assert_eq!(DateTime::from_parts([275_760, 9, 13, 0, 0, 0, 1], 0), None);
+    }
+}
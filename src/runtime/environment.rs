@@ -0,0 +1,122 @@
+use crate::parser::ast::{Ident, Slot};
+use crate::runtime::values::Value;
+
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
+
+pub type Env = Rc<RefCell<Environment>>;
+
+// Variables live in the slots the resolver gave them: one scope per function
+// call, one for the script or module, and the builtins outermost.
+pub struct Environment {
+    // `None` until the variable is first assigned.
+    slots: Vec<Option<Value>>,
+    // What each slot is called, kept only for top-level scopes: modules export
+    // by name, and each REPL entry can add more.
+    names: Vec<String>,
+    // Builtins, found by name. Only the outermost scope has any.
+    globals: HashMap<String, Value>,
+    parent: Option<Env>,
+}
+
+impl Environment {
+    // The outermost scope, which holds the builtins.
+    pub fn new() -> Env {
+        Self::with_parent(None, 0)
+    }
+
+    // A script, module or REPL session scope; `declare` sizes it.
+    pub fn top_level(globals: &Env) -> Env {
+        Self::with_parent(Some(Rc::clone(globals)), 0)
+    }
+
+    // The scope for one call of a function that needs `size` slots.
+    pub fn child(parent: &Env, size: usize) -> Env {
+        Self::with_parent(Some(Rc::clone(parent)), size)
+    }
+
+    fn with_parent(parent: Option<Env>, size: usize) -> Env {
+        Rc::new(RefCell::new(Self {
+            slots: vec![None; size],
+            names: Vec::new(),
+            globals: HashMap::new(),
+            parent,
+        }))
+    }
+
+    // Defines a builtin.
+    pub fn define(&mut self, name: &str, value: Value) {
+        self.globals.insert(name.to_string(), value);
+    }
+
+    pub fn get(&self, variable: &Ident) -> Option<Value> {
+        match variable.slot {
+            Slot::Local { depth, index } => self.get_slot(depth, index),
+            Slot::Global => self.global(&variable.name),
+            Slot::Unresolved => unreachable!("'{}' was not resolved before running", variable.name),
+        }
+    }
+
+    pub fn assign(&mut self, variable: &Ident, value: Value) {
+        match variable.slot {
+            Slot::Local { depth, index } => self.set_slot(depth, index, value),
+            // Assignments always bind in some scope of the program.
+            Slot::Global | Slot::Unresolved => unreachable!("'{}' has no slot to assign to", variable.name),
+        }
+    }
+
+    fn get_slot(&self, depth: u32, index: u32) -> Option<Value> {
+        if depth == 0 {
+            return self.slots[index as usize].clone();
+        }
+        self.parent.as_ref().and_then(|parent| parent.borrow().get_slot(depth - 1, index))
+    }
+
+    fn set_slot(&mut self, depth: u32, index: u32, value: Value) {
+        if depth == 0 {
+            self.slots[index as usize] = Some(value);
+        } else if let Some(parent) = &self.parent {
+            parent.borrow_mut().set_slot(depth - 1, index, value);
+        }
+    }
+
+    pub fn global(&self, name: &str) -> Option<Value> {
+        match &self.parent {
+            Some(parent) => parent.borrow().global(name),
+            None => self.globals.get(name).cloned(),
+        }
+    }
+
+    // The names of a top-level scope's slots, in order.
+    pub fn names(&self) -> &[String] {
+        &self.names
+    }
+
+    // Gives a top-level scope a slot for each of `names`, which the resolver
+    // extends from the scope's current ones.
+    pub fn declare(&mut self, names: Vec<String>) {
+        self.slots.resize(names.len(), None);
+        self.names = names;
+    }
+
+    // Variables of a top-level scope that have been given a value.
+    pub fn locals(&self) -> Vec<(String, Value)> {
+        self.names
+            .iter()
+            .zip(&self.slots)
+            .filter_map(|(name, value)| value.clone().map(|value| (name.clone(), value)))
+            .collect()
+    }
+}
+
+impl fmt::Debug for Environment {
+    // Closures capture the scope they are defined in, so printing values would recurse forever.
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Environment")
+            .field("names", &self.names)
+            .field("slots", &self.slots.len())
+            .finish()
+    }
+}
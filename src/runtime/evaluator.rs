@@ -1,122 +1,1492 @@
-use crate::parser::ast::{Stmt, Expr};
-use crate::runtime::values::Value;
+use crate::parser::ast::{Stmt, Expr, ImportTarget, CatchClause, MatchCase, PropertyKey, Pattern, Binding, Params, Ident, TemplatePart, Function};
+use crate::parser::tokens::TokenKind;
+use crate::parser::lexer::Lexer;
+use crate::parser::parser::{ParseError, Parser};
+use crate::parser::resolver;
+use crate::runtime::builtins;
+use crate::runtime::bytecode::CompiledFunction;
+use crate::runtime::compiler;
+use crate::runtime::environment::{Env, Environment};
+use crate::runtime::event_loop::{self, EventLoop};
+use crate::runtime::modules::{Module, ModuleLoader};
+use crate::runtime::operators;
+use crate::runtime::promise::{PromiseRef, PromiseState};
+use crate::runtime::values::{Enum, Object, Value};
+use crate::runtime::vm;
+use crate::utils::errors::{throw, Control, EvalResult};
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::env;
+use std::fs;
+use std::mem;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
 
-pub struct Evaluator {
-    globals: HashMap<String, Value>,
+// Where an assignment writes: a variable, or a key on an object or array.
+enum Place<'a> {
+    Variable(&'a Ident),
+    Property(Value, Value),
+}
+
+// How programs are run: by walking the syntax tree, or by compiling them to
+// bytecode for the VM. Both share the builtins, the event loop and modules.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum Backend {
+    #[default]
+    TreeWalker,
+    Bytecode,
 }
 
-type Env = HashMap<String, Value>;
+impl Backend {
+    pub const ALL: [Backend; 2] = [Backend::TreeWalker, Backend::Bytecode];
+}
+
+pub struct Evaluator {
+    backend: Backend,
+    globals: Env,
+    modules: ModuleLoader,
+    // Directory that relative `get` paths are resolved against.
+    current_dir: PathBuf,
+    // Names marked with `share` in the module currently being executed.
+    shared: Vec<String>,
+    // Command-line arguments after the script path, exposed as `os.args()`.
+    args: Vec<String>,
+    // Variables set with `os.setEnv`. They shadow the process environment
+    // rather than change it, since worker threads may be reading it.
+    env_overrides: BTreeMap<String, String>,
+    event_loop: EventLoop,
+    // What `log` wrote while output is being captured instead of printed.
+    captured: Option<Vec<String>>,
+    // Bytecode for the async functions the tree-walker hands to the VM, keyed
+    // by the syntax it came from, which is kept alive so the key stays unique.
+    compiled: HashMap<*const Function, (Rc<Function>, Rc<CompiledFunction>)>,
+}
 
 impl Evaluator {
     pub fn new() -> Self {
+        Self::with_backend(Backend::default())
+    }
+
+    pub fn with_backend(backend: Backend) -> Self {
+        let globals = Environment::new();
+        builtins::define_globals(&globals);
+
         Self {
-            globals: HashMap::new(),
+            backend,
+            globals,
+            modules: ModuleLoader::new(),
+            current_dir: PathBuf::from("."),
+            shared: Vec::new(),
+            args: Vec::new(),
+            env_overrides: BTreeMap::new(),
+            event_loop: EventLoop::new(),
+            captured: None,
+            compiled: HashMap::new(),
         }
     }
 
-    pub fn evaluate(&mut self, program: Vec<Stmt>) {
-        let mut env = self.globals.clone();
+    pub fn backend(&self) -> Backend {
+        self.backend
+    }
+
+    pub fn event_loop(&mut self) -> &mut EventLoop {
+        &mut self.event_loop
+    }
+
+    // Rejected promises nobody awaited or attached a handler to.
+    pub fn take_unhandled_rejections(&mut self) -> Vec<Value> {
+        self.event_loop.take_unhandled_rejections()
+    }
+
+    pub fn set_args(&mut self, args: Vec<String>) {
+        self.args = args;
+    }
 
-        for stmt in program {
-            self.execute(stmt, &mut env);
+    pub fn args(&self) -> &[String] {
+        &self.args
+    }
+
+    pub fn env_var(&self, name: &str) -> Option<String> {
+        match self.env_overrides.get(name) {
+            Some(value) => Some(value.clone()),
+            None => env::var(name).ok(),
         }
     }
 
-    fn execute(&mut self, stmt: Stmt, env: &mut Env) {
+    // The process environment with `os.setEnv` overrides applied, sorted by name.
+    pub fn env_vars(&self) -> Vec<(String, String)> {
+        let mut vars: BTreeMap<String, String> = env::vars().collect();
+        vars.extend(self.env_overrides.clone());
+        vars.into_iter().collect()
+    }
+
+    pub fn set_env_var(&mut self, name: String, value: String) {
+        self.env_overrides.insert(name, value);
+    }
+
+    // What a child process needs on top of the inherited environment, e.g. via `Command::envs`.
+    pub fn env_overrides(&self) -> &BTreeMap<String, String> {
+        &self.env_overrides
+    }
+
+    // Makes `log` collect its lines for `take_output` instead of printing them.
+    pub fn capture_output(&mut self) {
+        self.captured = Some(Vec::new());
+    }
+
+    // Lines logged since capturing began or output was last taken.
+    pub fn take_output(&mut self) -> Vec<String> {
+        self.captured.as_mut().map(mem::take).unwrap_or_default()
+    }
+
+    // Writes one line for `log`.
+    pub(crate) fn write_line(&mut self, text: String) {
+        match &mut self.captured {
+            Some(lines) => lines.push(text),
+            None => println!("{}", text),
+        }
+    }
+
+    // Runs the program, then keeps the event loop going until no timers or IO remain.
+    pub fn evaluate(&mut self, mut program: Vec<Stmt>) -> EvalResult<()> {
+        let env = Environment::top_level(&self.globals);
+        self.resolve(&mut program, &env);
+        self.run_program(program, &env)?;
+        event_loop::run(self, None)
+    }
+
+    // A scope for an interactive session, so what one entry defines stays
+    // visible to the next.
+    pub fn session_scope(&self) -> Env {
+        Environment::top_level(&self.globals)
+    }
+
+    // Runs one REPL entry in `scope` followed by the event loop. When the
+    // entry ends with a bare expression, its value is given back.
+    pub fn evaluate_in(&mut self, mut program: Vec<Stmt>, scope: &Env) -> EvalResult<Option<Value>> {
+        self.resolve(&mut program, scope);
+        let last = match program.last() {
+            Some(Stmt::ExprStmt(_)) => program.pop(),
+            _ => None,
+        };
+
+        self.run_program(program, scope)?;
+        let value = match last {
+            Some(Stmt::ExprStmt(expr)) if self.backend == Backend::Bytecode => {
+                Some(vm::run(self, compiler::compile_expression(&expr), scope)?)
+            }
+            Some(Stmt::ExprStmt(expr)) => Some(self.eval_expr(&expr, scope)?),
+            _ => None,
+        };
+        event_loop::run(self, None)?;
+        Ok(value)
+    }
+
+    pub fn set_current_dir(&mut self, dir: PathBuf) {
+        self.current_dir = dir;
+    }
+
+    pub fn run_file(&mut self, path: &Path) -> EvalResult<()> {
+        let source = match fs::read_to_string(path) {
+            Ok(source) => source,
+            Err(err) => return throw("IOError", format!("Could not read {}: {}", path.display(), err)),
+        };
+
+        let program = try_parse_source(&source).or_else(|err| throw("SyntaxError", err.message))?;
+        self.current_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        self.evaluate(program)
+    }
+
+    // Gives the program's variables slots in `env`, a top-level scope, and
+    // warns about anything suspect the resolver found.
+    fn resolve(&self, program: &mut [Stmt], env: &Env) {
+        let mut names = env.borrow().names().to_vec();
+        let globals = self.globals.borrow();
+        let diagnostics = resolver::resolve(program, &mut names, &|name| globals.global(name).is_some());
+        env.borrow_mut().declare(names);
+
+        for diagnostic in diagnostics {
+            eprintln!("warning: {}", diagnostic);
+        }
+    }
+
+    // Runs a program the resolver has been through.
+    fn run_program(&mut self, program: Vec<Stmt>, env: &Env) -> EvalResult<()> {
+        match self.backend {
+            Backend::TreeWalker => self.execute_block(&program, env),
+            Backend::Bytecode => vm::run(self, compiler::compile_script(&program), env).map(|_| ()),
+        }
+    }
+
+    fn execute_block(&mut self, body: &[Stmt], env: &Env) -> EvalResult<()> {
+        for stmt in body {
+            self.execute(stmt, env)?;
+        }
+        Ok(())
+    }
+
+    fn execute(&mut self, stmt: &Stmt, env: &Env) -> EvalResult<()> {
         match stmt {
-            Stmt::Init { name, params, body } => {
-                env.insert(name.clone(), Value::Function { params, body });
+            Stmt::Init { name, function } => {
+                let function = Value::Function {
+                    function: Rc::clone(function),
+                    closure: Rc::clone(env),
+                };
+                env.borrow_mut().assign(name, function);
             }
 
             Stmt::Log { value } => {
-                let result = self.eval_expr(value, env);
-                println!("{}", self.stringify(result));
+                let result = self.eval_expr(value, env)?;
+                let text = self.display(result)?;
+                self.write_line(text);
+            }
+
+            Stmt::Assign { name, value } => {
+                let value = self.eval_expr(value, env)?;
+                env.borrow_mut().assign(name, value);
+            }
+
+            Stmt::Destructure { pattern, value } => {
+                let value = self.eval_expr(value, env)?;
+                self.bind(pattern, value, env)?;
+            }
+
+            Stmt::For { pattern, iterable, body } => {
+                let iterable = self.eval_expr(iterable, env)?;
+                for item in self.iterate(iterable)? {
+                    self.bind(pattern, item, env)?;
+                    self.execute_block(body, env)?;
+                }
+            }
+
+            Stmt::Get { target, source } => {
+                let spec = self.eval_expr(source, env)?;
+                self.import(target, spec, env)?;
+            }
+
+            Stmt::Share(inner) => {
+                self.share(&inner.bound_names());
+                self.execute(inner, env)?;
+            }
+
+            Stmt::Try { body, catches, finally } => {
+                let result = match self.execute_block(body, env) {
+                    Err(Control::Throw(error)) => self.handle_catch(error, catches, env),
+                    other => other,
+                };
+
+                if let Some(finally) = finally {
+                    self.execute_block(finally, env)?;
+                }
+
+                result?;
+            }
+
+            Stmt::Throw { value } => {
+                let value = self.eval_expr(value, env)?;
+                return Err(Control::Throw(value));
+            }
+
+            Stmt::Enum { name, members } => {
+                let mut values = Vec::new();
+                for (index, (member, value)) in members.iter().enumerate() {
+                    let value = match value {
+                        Some(value) => self.eval_expr(value, env)?,
+                        None => Value::Number(index as f64),
+                    };
+                    values.push((member.clone(), value));
+                }
+                env.borrow_mut().assign(name, Value::Enum(Enum::new(&name.name, values)));
+            }
+
+            Stmt::Match { subject, cases, otherwise } => {
+                let subject = self.eval_expr(subject, env)?;
+                self.execute_match(subject, cases, otherwise.as_deref(), env)?;
+            }
+
+            Stmt::Return { value } => {
+                let value = match value {
+                    Some(value) => self.eval_expr(value, env)?,
+                    None => Value::Null,
+                };
+                return Err(Control::Return(value));
             }
 
             Stmt::ExprStmt(expr) => {
-                self.eval_expr(expr, env);
+                self.eval_expr(expr, env)?;
             }
         }
+
+        Ok(())
     }
 
-    fn eval_expr(&mut self, expr: Expr, env: &mut Env) -> Value {
-        match expr {
-            Expr::Literal(val) => Value::String(val),
-            Expr::Template(template) => {
-                let interpolated = self.interpolate_template(template, env);
-                Value::String(interpolated)
+    pub(crate) fn import(&mut self, target: &ImportTarget, spec: Value, env: &Env) -> EvalResult<()> {
+        let spec = match spec {
+            Value::String(spec) => spec,
+            other => {
+                return throw("TypeError", format!(
+                    "Module path must be a string, got {}",
+                    self.stringify(other)
+                ));
             }
+        };
 
-            Expr::Variable(name) => {
-                env.get(&name)
-                    .cloned()
-                    .unwrap_or(Value::String(format!("<undefined {}>", name)))
+        let module = self.load_module(&spec)?;
+        match target {
+            ImportTarget::Namespace(name) => {
+                env.borrow_mut().assign(name, Value::Module(module));
             }
+            ImportTarget::Names(names) => {
+                for name in names {
+                    let value = module.export(&name.name).or_else(|err| throw("ImportError", err))?;
+                    env.borrow_mut().assign(name, value);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    pub(crate) fn share(&mut self, names: &[String]) {
+        self.shared.extend(names.iter().cloned());
+    }
+
+    fn execute_match(
+        &mut self,
+        subject: Value,
+        cases: &[MatchCase],
+        otherwise: Option<&[Stmt]>,
+        env: &Env,
+    ) -> EvalResult<()> {
+        let mut arms = Vec::new();
+        for case in cases {
+            let mut patterns = Vec::new();
+            for pattern in &case.patterns {
+                patterns.push(self.eval_expr(pattern, env)?);
+            }
+            arms.push((patterns, &case.body));
+        }
+
+        if otherwise.is_none() {
+            let patterns: Vec<Value> = arms.iter().flat_map(|(patterns, _)| patterns.clone()).collect();
+            self.check_exhaustive(&subject, &patterns)?;
+        }
+
+        for (patterns, body) in arms {
+            if patterns.iter().any(|pattern| pattern.equals(&subject)) {
+                return self.execute_block(body, env);
+            }
+        }
+
+        match otherwise {
+            Some(body) => self.execute_block(body, env),
+            None => Ok(()),
+        }
+    }
+
+    // Without an `else`, a match on an enum member has to name every member.
+    pub(crate) fn check_exhaustive(&self, subject: &Value, patterns: &[Value]) -> EvalResult<()> {
+        let Value::EnumMember(member) = subject else {
+            return Ok(());
+        };
+        let owner = member.owner();
+        let missing: Vec<&str> = owner
+            .members
+            .iter()
+            .filter(|candidate| {
+                let candidate = Value::EnumMember(Rc::clone(candidate));
+                !patterns.iter().any(|pattern| pattern.equals(&candidate))
+            })
+            .map(|candidate| candidate.name.as_str())
+            .collect();
+
+        if missing.is_empty() {
+            return Ok(());
+        }
+        throw("MatchError", format!(
+            "Non-exhaustive match on enum {}: missing {}",
+            owner.name,
+            missing.join(", ")
+        ))
+    }
+
+    // Binds `value` to the names in `pattern`, for parameters, destructuring
+    // assignments and loop heads alike.
+    fn bind(&mut self, pattern: &Pattern, value: Value, env: &Env) -> EvalResult<()> {
+        match pattern {
+            Pattern::Name(name) => env.borrow_mut().assign(name, value),
+
+            Pattern::Object { properties, rest } => {
+                let object = match value {
+                    Value::Object(object) => object.borrow().clone(),
+                    other => {
+                        return throw("TypeError", format!(
+                            "Cannot destructure {} as it is not an object",
+                            self.stringify(other)
+                        ));
+                    }
+                };
 
-            Expr::Call { callee, arguments } => {
-                let function = env.get(&callee).cloned();
-                match function {
-                    Some(Value::Function { params, body }) => {
-                        let mut new_env = self.globals.clone();
+                for (key, binding) in properties {
+                    let value = object.get(key).unwrap_or(Value::Undefined);
+                    self.bind_with_default(binding, value, env)?;
+                }
 
-                        for (i, param_name) in params.iter().enumerate() {
-                            if let Some(arg) = arguments.get(i) {
-                                let val = self.eval_expr(arg.clone(), env);
-                                new_env.insert(param_name.clone(), val);
-                            }
+                if let Some(rest) = rest {
+                    let mut remaining = Object::new();
+                    for (key, value) in object.entries() {
+                        if !properties.iter().any(|(taken, _)| taken == key) {
+                            remaining.set(key, value.clone());
                         }
+                    }
+                    env.borrow_mut().assign(rest, Value::object(remaining));
+                }
+            }
+
+            Pattern::Array { items, rest } => {
+                let values = match value {
+                    value @ (Value::Array(_) | Value::String(_) | Value::Object(_)) => self.iterate(value)?,
+                    other => {
+                        return throw("TypeError", format!(
+                            "Cannot destructure {} as it is not iterable",
+                            self.stringify(other)
+                        ));
+                    }
+                };
+
+                let mut values = values.into_iter();
+                for binding in items {
+                    let value = values.next().unwrap_or(Value::Undefined);
+                    self.bind_with_default(binding, value, env)?;
+                }
+
+                if let Some(rest) = rest {
+                    self.bind(rest, Value::array(values.collect()), env)?;
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    fn bind_with_default(&mut self, binding: &Binding, value: Value, env: &Env) -> EvalResult<()> {
+        let value = match (value, &binding.default) {
+            (Value::Undefined, Some(default)) => self.eval_expr(default, env)?,
+            (value, _) => value,
+        };
+        self.bind(&binding.target, value, env)
+    }
+
+    fn handle_catch(&mut self, error: Value, catches: &[CatchClause], env: &Env) -> EvalResult<()> {
+        for clause in catches {
+            if let Some(error_type) = &clause.error_type
+                && !self.error_has_type(&error, error_type)
+            {
+                continue;
+            }
+
+            if let Some(name) = &clause.name {
+                env.borrow_mut().assign(name, error);
+            }
+            return self.execute_block(&clause.body, env);
+        }
 
-                        for stmt in body {
-                            self.execute(stmt.clone(), &mut new_env);
+        Err(Control::Throw(error))
+    }
+
+    pub(crate) fn error_has_type(&self, error: &Value, error_type: &str) -> bool {
+        match error {
+            Value::Object(object) => {
+                matches!(object.borrow().get("type"), Some(Value::String(kind)) if kind == error_type)
+            }
+            _ => false,
+        }
+    }
+
+    fn eval_expr(&mut self, expr: &Expr, env: &Env) -> EvalResult<Value> {
+        match expr {
+            Expr::Literal(val) => Ok(Value::String(val.clone())),
+            Expr::Number(number) => Ok(Value::Number(*number)),
+            Expr::Boolean(value) => Ok(Value::Boolean(*value)),
+            Expr::Null => Ok(Value::Null),
+            Expr::Undefined => Ok(Value::Undefined),
+            Expr::Template(parts) => {
+                let mut text = String::new();
+                for part in parts {
+                    match part {
+                        TemplatePart::Text(part) => text.push_str(part),
+                        TemplatePart::Expr(expr) => {
+                            let value = self.eval_expr(expr, env)?;
+                            text.push_str(&self.display(value)?);
                         }
+                    }
+                }
+                Ok(Value::String(text))
+            }
+
+            Expr::Function(function) => Ok(Value::Function {
+                function: Rc::clone(function),
+                closure: Rc::clone(env),
+            }),
+
+            Expr::Await(value) => {
+                let value = self.eval_expr(value, env)?;
+                self.await_value(value)
+            }
+
+            chain @ (Expr::Member { .. } | Expr::Index { .. } | Expr::Call { .. }) => {
+                Ok(self.eval_chain(chain, env)?.unwrap_or(Value::Undefined))
+            }
+
+            Expr::Unary { operator, operand } => {
+                let operand = self.eval_expr(operand, env)?;
+                operators::unary(self, operator, operand)
+            }
+
+            Expr::Binary { left, operator, right } => {
+                let left = self.eval_expr(left, env)?;
+                let right = self.eval_expr(right, env)?;
+                match operator {
+                    TokenKind::DoubleEqual => Ok(Value::Boolean(left.equals(&right))),
+                    TokenKind::NotEqual => Ok(Value::Boolean(!left.equals(&right))),
+                    operator => operators::binary(self, operator, left, right),
+                }
+            }
+
+            Expr::Logical { left, operator, right } => {
+                let left = self.eval_expr(left, env)?;
+                match operator {
+                    TokenKind::QuestionQuestion => match left {
+                        Value::Null | Value::Undefined => self.eval_expr(right, env),
+                        left => Ok(left),
+                    },
+                    // Both give back one of their operands, not a boolean.
+                    TokenKind::AmpAmp if left.is_truthy() => self.eval_expr(right, env),
+                    TokenKind::PipePipe if !left.is_truthy() => self.eval_expr(right, env),
+                    TokenKind::AmpAmp | TokenKind::PipePipe => Ok(left),
+                    other => unreachable!("parser produced unsupported logical operator {:?}", other),
+                }
+            }
 
-                        Value::Null
+            Expr::Assign { target, operator, value } => {
+                let place = self.place(target, env)?;
+                let value = match operator {
+                    TokenKind::Equal => self.eval_expr(value, env)?,
+                    TokenKind::QuestionQuestionEqual => match self.read_place(&place, env)? {
+                        Value::Null | Value::Undefined => self.eval_expr(value, env)?,
+                        current => return Ok(current),
+                    },
+                    operator => {
+                        let current = self.read_place(&place, env)?;
+                        let value = self.eval_expr(value, env)?;
+                        operators::binary(self, &operators::compound(operator), current, value)?
                     }
-                    Some(_) => panic!("{} is not a function", callee),
-                    None => panic!("Function not found: {}", callee),
+                };
+                self.write_place(place, value.clone(), env)?;
+                Ok(value)
+            }
+
+            Expr::Update { target, operator, prefix } => {
+                let place = self.place(target, env)?;
+                let current = self.read_place(&place, env)?;
+                let updated = operators::step(self, operator, current.clone())?;
+                self.write_place(place, updated.clone(), env)?;
+                Ok(if *prefix { updated } else { current })
+            }
+
+            Expr::Array(items) => Ok(Value::array(self.eval_elements(items, env)?)),
+
+            Expr::Spread(_) => unreachable!("parser only allows spread in arrays, objects and calls"),
+            Expr::Keyword { .. } => unreachable!("parser only allows keyword arguments in calls"),
+
+            Expr::Object(fields) => {
+                let mut object = Object::new();
+                for (key, value) in fields {
+                    let key = match key {
+                        PropertyKey::Named(name) => Value::String(name.clone()),
+                        PropertyKey::Computed(key) => self.eval_expr(key, env)?,
+                        PropertyKey::Spread => {
+                            let value = self.eval_expr(value, env)?;
+                            self.spread_into(&mut object, value)?;
+                            continue;
+                        }
+                    };
+                    let value = self.eval_expr(value, env)?;
+                    self.define_property(&mut object, key, value)?;
                 }
+                Ok(Value::object(object))
             }
+
+            Expr::Variable(name) => lookup(name, env),
+
         }
     }
 
-    fn interpolate_template(&self, template: String, env: &Env) -> String {
-        let mut result = String::new();
-        let mut chars = template.chars().peekable();
+    // Evaluates a member, index or call chain. `None` means a `?.` link met
+    // `null` or `undefined`, which skips everything after it in the chain.
+    fn eval_chain(&mut self, expr: &Expr, env: &Env) -> EvalResult<Option<Value>> {
+        match expr {
+            Expr::Member { object, property, optional } => {
+                let Some(object) = self.eval_link(object, *optional, env)? else {
+                    return Ok(None);
+                };
+                self.member(object, property).map(Some)
+            }
 
-        while let Some(c) = chars.next() {
-            if c == '$' && chars.peek() == Some(&'{') {
-                chars.next(); // consume {
-                let mut name = String::new();
+            Expr::Index { object, index, optional } => {
+                let Some(object) = self.eval_link(object, *optional, env)? else {
+                    return Ok(None);
+                };
+                let index = self.eval_expr(index, env)?;
+                self.index(object, index).map(Some)
+            }
 
-                while let Some(&next) = chars.peek() {
-                    if next == '}' {
-                        chars.next();
-                        break;
+            Expr::Call { callee, arguments, optional } => {
+                let Some(function) = self.eval_link(callee, *optional, env)? else {
+                    return Ok(None);
+                };
+                // The parser puts keyword arguments after every positional one.
+                let split = arguments
+                    .iter()
+                    .position(|arg| matches!(arg, Expr::Keyword { .. }))
+                    .unwrap_or(arguments.len());
+                let (positional, keywords) = arguments.split_at(split);
+                let args = self.eval_elements(positional, env)?;
+                let mut named = Vec::new();
+                for keyword in keywords {
+                    if let Expr::Keyword { name, value } = keyword {
+                        named.push((name.clone(), self.eval_expr(value, env)?));
                     }
-                    name.push(next);
-                    chars.next();
                 }
+                self.invoke(function, args, named, true).map(Some)
+            }
+
+            other => self.eval_expr(other, env).map(Some),
+        }
+    }
+
+    fn eval_link(&mut self, target: &Expr, optional: bool, env: &Env) -> EvalResult<Option<Value>> {
+        match self.eval_chain(target, env)? {
+            Some(Value::Null | Value::Undefined) if optional => Ok(None),
+            value => Ok(value),
+        }
+    }
 
-                let value = env
-                    .get(&name)
-                    .map(|v| self.stringify(v.clone()))
-                    .unwrap_or("".to_string());
-                result.push_str(&value);
+    // `{[key]: value}` in an object literal.
+    pub(crate) fn define_property(&self, object: &mut Object, key: Value, value: Value) -> EvalResult<()> {
+        match key {
+            Value::String(name) => object.set(&name, value),
+            Value::Number(number) => object.set(&format_number(number), value),
+            Value::Symbol(symbol) => object.set_symbol(&symbol, value),
+            other => return throw("TypeError", format!(
+                "{} cannot be used as a property key",
+                self.stringify(other)
+            )),
+        }
+        Ok(())
+    }
+
+    pub(crate) fn member(&mut self, object: Value, property: &str) -> EvalResult<Value> {
+        match object {
+            Value::Module(module) => module.export(property).or_else(|err| throw("ImportError", err)),
+            Value::Object(object) => {
+                let own = object.borrow().get(property);
+                Ok(own.or_else(|| builtins::object::method(&object, property)).unwrap_or(Value::Undefined))
+            }
+            Value::Symbol(symbol) => Ok(builtins::symbol::method(&symbol, property)),
+            Value::BigInt(big) => Ok(builtins::bigint::method(&big, property).unwrap_or(Value::Undefined)),
+            Value::NativeFunction(native) => Ok(native.properties.get(property).unwrap_or(Value::Undefined)),
+            Value::Promise(promise) => Ok(builtins::promise::method(&promise, property).unwrap_or(Value::Undefined)),
+            Value::Date(date) => Ok(builtins::date::method(date, property).unwrap_or(Value::Undefined)),
+            Value::Enum(namespace) => builtins::enums::property(&namespace, property),
+            Value::EnumMember(member) => Ok(builtins::enums::member_property(&member, property)),
+            other => throw("TypeError", format!(
+                "Cannot read property '{}' of {}",
+                property,
+                self.stringify(other)
+            )),
+        }
+    }
+
+    // Resolves an assignment target once, so `list[next()] += 1` calls `next` once.
+    fn place<'a>(&mut self, target: &'a Expr, env: &Env) -> EvalResult<Place<'a>> {
+        match target {
+            Expr::Variable(name) => Ok(Place::Variable(name)),
+            Expr::Member { object, property, .. } => {
+                let object = self.eval_expr(object, env)?;
+                Ok(Place::Property(object, Value::String(property.clone())))
+            }
+            Expr::Index { object, index, .. } => {
+                let object = self.eval_expr(object, env)?;
+                let index = self.eval_expr(index, env)?;
+                Ok(Place::Property(object, index))
+            }
+            other => unreachable!("parser only allows variables, members and indices as targets, got {:?}", other),
+        }
+    }
+
+    fn read_place(&mut self, place: &Place, env: &Env) -> EvalResult<Value> {
+        match place {
+            Place::Variable(name) => lookup(name, env),
+            Place::Property(object, key) => self.get_property(object.clone(), key.clone()),
+        }
+    }
+
+    fn write_place(&mut self, place: Place, value: Value, env: &Env) -> EvalResult<()> {
+        match place {
+            Place::Variable(name) => {
+                env.borrow_mut().assign(name, value);
+                Ok(())
+            }
+            Place::Property(object, key) => self.set_property(object, key, value),
+        }
+    }
+
+    // Reads `object.key` or `object[key]` as the target of an assignment.
+    pub(crate) fn get_property(&mut self, object: Value, key: Value) -> EvalResult<Value> {
+        match key {
+            Value::String(key) => self.member(object, &key),
+            index => self.index(object, index),
+        }
+    }
+
+    pub(crate) fn set_property(&mut self, object: Value, key: Value, value: Value) -> EvalResult<()> {
+        match (&object, key) {
+            (Value::Object(object), Value::String(key)) => object.borrow_mut().set(&key, value),
+            (Value::Object(object), Value::Number(number)) => object.borrow_mut().set(&format_number(number), value),
+            (Value::Object(object), Value::Symbol(symbol)) => object.borrow_mut().set_symbol(&symbol, value),
+            (Value::Array(items), Value::Number(position)) if position.fract() == 0.0 && position >= 0.0 => {
+                // Writing past the end pads the gap with `undefined`.
+                let mut items = items.borrow_mut();
+                let position = position as usize;
+                if position >= items.len() {
+                    items.resize(position + 1, Value::Undefined);
+                }
+                items[position] = value;
+            }
+            (_, key) => {
+                return throw("TypeError", format!(
+                    "Cannot set {} on {}",
+                    self.inspect(key),
+                    self.stringify(object)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Evaluates array items or call arguments left to right, expanding spreads in place.
+    fn eval_elements(&mut self, elements: &[Expr], env: &Env) -> EvalResult<Vec<Value>> {
+        let mut values = Vec::new();
+        for element in elements {
+            match element {
+                Expr::Spread(iterable) => {
+                    let iterable = self.eval_expr(iterable, env)?;
+                    values.extend(self.iterate(iterable)?);
+                }
+                element => values.push(self.eval_expr(element, env)?),
+            }
+        }
+        Ok(values)
+    }
+
+    // `{...value}`: objects copy their properties and arrays their items under
+    // index keys; `null` and `undefined` add nothing.
+    pub(crate) fn spread_into(&mut self, object: &mut Object, value: Value) -> EvalResult<()> {
+        match value {
+            Value::Object(source) => object.extend(&source.borrow()),
+            Value::Array(items) => {
+                for (index, item) in items.borrow().iter().enumerate() {
+                    object.set(&index.to_string(), item.clone());
+                }
+            }
+            Value::Null | Value::Undefined => {}
+            other => {
+                return throw("TypeError", format!(
+                    "Cannot spread {} into an object",
+                    self.stringify(other)
+                ));
+            }
+        }
+        Ok(())
+    }
+
+    // Calls made by the runtime, such as `then` handlers or server routes.
+    // Callbacks may leave out trailing parameters they don't need, so extra
+    // arguments are dropped rather than rejected.
+    pub fn call_function(&mut self, function: Value, args: Vec<Value>) -> EvalResult<Value> {
+        self.invoke(function, args, Vec::new(), false)
+    }
+
+    // `exact` rejects surplus positional arguments, as calls written in source do.
+    pub(crate) fn invoke(&mut self, function: Value, args: Vec<Value>, keywords: Vec<(String, Value)>, exact: bool) -> EvalResult<Value> {
+        match function {
+            // Only the VM can suspend a call at an `await` and carry on with
+            // the caller, so async functions run there.
+            Value::Function { function, closure } if function.is_async => {
+                let compiled = self.compiled(&function);
+                vm::call(self, compiled, &closure, args, keywords, exact)
+            }
+            Value::Function { function, closure } => {
+                let new_env = Environment::child(&closure, function.locals);
+                self.bind_arguments(&function.params, args, keywords, exact, &new_env)?;
+
+                match self.execute_block(&function.body, &new_env) {
+                    Ok(()) => Ok(Value::Null),
+                    Err(Control::Return(value)) => Ok(value),
+                    Err(other) => Err(other),
+                }
+            }
+            Value::Closure { function, closure } => vm::call(self, function, &closure, args, keywords, exact),
+            Value::NativeFunction(native) => {
+                if let Some((name, _)) = keywords.first() {
+                    return throw("TypeError", format!(
+                        "{}() does not take keyword arguments (got '{}')",
+                        native.name, name
+                    ));
+                }
+                (native.func)(self, args)
+            }
+            other => throw("TypeError", format!("{} is not a function", self.stringify(other))),
+        }
+    }
+
+    fn compiled(&mut self, function: &Rc<Function>) -> Rc<CompiledFunction> {
+        let (_, compiled) = self
+            .compiled
+            .entry(Rc::as_ptr(function))
+            .or_insert_with(|| (Rc::clone(function), compiler::compile_function(function)));
+        Rc::clone(compiled)
+    }
+
+    // Fills parameters from positional arguments, then keyword arguments by
+    // name; whatever is still missing takes its default or `undefined`.
+    fn bind_arguments(
+        &mut self,
+        params: &Params,
+        args: Vec<Value>,
+        keywords: Vec<(String, Value)>,
+        exact: bool,
+        env: &Env,
+    ) -> EvalResult<()> {
+        let (positional, extra) = arrange_arguments(params, args, keywords, exact)?;
+
+        // In order, so a default can refer to the parameters before it.
+        for (binding, value) in params.positional.iter().zip(positional) {
+            self.bind_with_default(binding, value, env)?;
+        }
+        if let Some(rest) = &params.rest {
+            env.borrow_mut().assign(rest, Value::array(extra));
+        }
+        Ok(())
+    }
+
+    // `await` in a script or REPL entry, which has no caller to get on with,
+    // keeps the event loop running until the promise settles, so timers, IO
+    // and other callbacks make progress meanwhile. Async functions suspend
+    // on the VM instead.
+    pub fn await_value(&mut self, value: Value) -> EvalResult<Value> {
+        let Value::Promise(promise) = value else {
+            return Ok(value);
+        };
+
+        promise.borrow_mut().handled = true;
+        event_loop::run(self, Some(&promise))?;
+
+        let settled = promise.borrow().settled();
+        match settled {
+            Some(Ok(value)) => Ok(value),
+            Some(Err(reason)) => Err(Control::Throw(reason)),
+            None => throw("AsyncError", "Awaited a promise that can never settle: no timers or IO are pending"),
+        }
+    }
+
+    fn load_module(&mut self, spec: &str) -> EvalResult<Rc<Module>> {
+        if let Some(module) = self.modules.builtin(spec) {
+            return Ok(module);
+        }
+
+        let path = self.modules
+            .resolve(spec, &self.current_dir)
+            .or_else(|err| throw("ImportError", err))?;
+
+        if let Some(module) = self.modules.cached(&path) {
+            return Ok(module);
+        }
+
+        let source = match fs::read_to_string(&path) {
+            Ok(source) => source,
+            Err(err) => return throw("ImportError", format!("Could not read module \"{}\": {}", spec, err)),
+        };
+        let mut program = try_parse_source(&source)
+            .or_else(|err| throw("ImportError", format!("Could not parse module \"{}\": {}", spec, err)))?;
+
+        self.modules.begin(&path).or_else(|err| throw("ImportError", err))?;
+
+        let module_dir = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let outer_dir = mem::replace(&mut self.current_dir, module_dir);
+        let outer_shared = mem::take(&mut self.shared);
+
+        let env = Environment::top_level(&self.globals);
+        self.resolve(&mut program, &env);
+        let result = self.run_program(program, &env);
+
+        self.current_dir = outer_dir;
+        let shared = mem::replace(&mut self.shared, outer_shared);
+
+        if let Err(err) = result {
+            self.modules.abandon(&path);
+            return Err(err);
+        }
+
+        let mut exports = HashMap::new();
+        let mut private = HashSet::new();
+        for (name, value) in env.borrow().locals() {
+            if shared.contains(&name) {
+                exports.insert(name, value);
             } else {
-                result.push(c);
+                private.insert(name);
+            }
+        }
+
+        let module = Rc::new(Module::new(spec.to_string(), exports, private));
+        self.modules.finish(&path, Rc::clone(&module));
+        Ok(module)
+    }
+
+    // `obj[key]`: array positions, string characters, and string or symbol keys.
+    pub(crate) fn index(&mut self, object: Value, index: Value) -> EvalResult<Value> {
+        let item = match (&object, &index) {
+            (Value::Array(items), Value::Number(position)) if position.fract() == 0.0 && *position >= 0.0 => {
+                items.borrow().get(*position as usize).cloned()
+            }
+            (Value::String(text), Value::Number(position)) if position.fract() == 0.0 && *position >= 0.0 => {
+                text.chars().nth(*position as usize).map(|c| Value::String(c.to_string()))
+            }
+            (Value::Array(_) | Value::String(_), Value::Number(_)) => None,
+            (Value::Object(object), Value::String(key)) => {
+                let own = object.borrow().get(key);
+                own.or_else(|| builtins::object::method(object, key))
+            }
+            (Value::Object(object), Value::Symbol(symbol)) => object.borrow().get_symbol(symbol),
+            _ => {
+                return throw("TypeError", format!(
+                    "Cannot index {} with {}",
+                    self.stringify(object),
+                    self.inspect(index)
+                ));
+            }
+        };
+        Ok(item.unwrap_or(Value::Undefined))
+    }
+
+    // Values an object yields through the iteration protocol: arrays and
+    // strings directly, objects through `[Symbol.iterator]().next()`.
+    pub fn iterate(&mut self, value: Value) -> EvalResult<Vec<Value>> {
+        let object = match value {
+            Value::Array(items) => return Ok(items.borrow().clone()),
+            Value::String(text) => return Ok(text.chars().map(|c| Value::String(c.to_string())).collect()),
+            Value::Object(object) => object,
+            other => return throw("TypeError", format!("{} is not iterable", self.stringify(other))),
+        };
+
+        let method = object.borrow().get_symbol(&builtins::symbol::iterator());
+        let Some(method) = method.filter(Value::is_callable) else {
+            return throw("TypeError", "Object is not iterable: it has no [Symbol.iterator] method");
+        };
+        let iterator = self.call_function(method, Vec::new())?;
+        let next = self.index(iterator, Value::String("next".to_string()))?;
+        if !next.is_callable() {
+            return throw("TypeError", "Iterator has no next() method");
+        }
+
+        let mut items = Vec::new();
+        loop {
+            let step = self.call_function(next.clone(), Vec::new())?;
+            let Value::Object(step) = step else {
+                return throw("TypeError", "Iterator next() must return an object");
+            };
+            let step = step.borrow();
+            if matches!(step.get("done"), Some(Value::Boolean(true))) {
+                return Ok(items);
             }
+            items.push(step.get("value").unwrap_or(Value::Undefined));
         }
+    }
 
-        result
+    // Like `stringify`, but objects with a `[Symbol.toPrimitive]` method
+    // choose their own text. Used by `log` and template strings.
+    pub fn display(&mut self, value: Value) -> EvalResult<String> {
+        if let Value::Object(object) = &value {
+            let method = object.borrow().get_symbol(&builtins::symbol::to_primitive());
+            if let Some(method) = method.filter(Value::is_callable) {
+                let primitive = self.call_function(method, vec![Value::String("string".to_string())])?;
+                if matches!(primitive, Value::Object(_) | Value::Array(_)) {
+                    return throw("TypeError", "[Symbol.toPrimitive] must return a primitive value");
+                }
+                return Ok(self.stringify(primitive));
+            }
+        }
+        Ok(self.stringify(value))
     }
 
-    fn stringify(&self, value: Value) -> String {
+    pub fn stringify(&self, value: Value) -> String {
         match value {
             Value::String(s) => s,
+            Value::Number(number) => format_number(number),
+            Value::BigInt(big) => big.to_string_radix(10),
+            Value::Boolean(value) => value.to_string(),
+            Value::Array(items) => {
+                let items: Vec<String> = items
+                    .borrow()
+                    .iter()
+                    .map(|item| self.inspect(item.clone()))
+                    .collect();
+                format!("[{}]", items.join(", "))
+            }
+            Value::Object(object) => {
+                let fields: Vec<String> = object
+                    .borrow()
+                    .entries()
+                    .map(|(key, value)| format!("{}: {}", key, self.inspect(value.clone())))
+                    .collect();
+                format!("{{{}}}", fields.join(", "))
+            }
             Value::Null => "null".to_string(),
-            Value::Function { .. } => "<function>".to_string(),
+            Value::Undefined => "undefined".to_string(),
+            Value::Function { .. } | Value::Closure { .. } => "<function>".to_string(),
+            Value::NativeFunction(native) => format!("<native function {}>", native.name),
+            Value::Module(module) => format!("<module \"{}\">", module.path),
+            Value::Promise(promise) => self.describe_promise(&promise),
+            Value::Date(date) => date.to_iso(),
+            Value::Enum(namespace) => format!("<enum {}>", namespace.name),
+            Value::EnumMember(member) => format!("{}.{}", member.owner().name, member.name),
+            Value::Symbol(symbol) => builtins::symbol::describe(&symbol),
+        }
+    }
+
+    fn describe_promise(&self, promise: &PromiseRef) -> String {
+        match &promise.borrow().state {
+            PromiseState::Pending => "<promise pending>".to_string(),
+            PromiseState::Fulfilled(value) => format!("<promise fulfilled: {}>", self.inspect(value.clone())),
+            PromiseState::Rejected(reason) => format!("<promise rejected: {}>", self.inspect(reason.clone())),
+        }
+    }
+
+    // Formats an uncaught error as `Type: message` when it carries those fields.
+    pub fn describe_error(&self, error: Value) -> String {
+        if let Value::Object(object) = &error {
+            let object = object.borrow();
+            if let (Some(kind), Some(message)) = (object.get("type"), object.get("message")) {
+                return format!("{}: {}", self.stringify(kind), self.stringify(message));
+            }
+        }
+        self.stringify(error)
+    }
+
+    // Like `stringify`, but quotes strings so nested values read unambiguously.
+    fn inspect(&self, value: Value) -> String {
+        match value {
+            Value::String(s) => format!("{:?}", s),
+            Value::BigInt(big) => format!("{}n", big.to_string_radix(10)),
+            other => self.stringify(other),
+        }
+    }
+}
+
+impl Default for Evaluator {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Whole numbers print without a fractional part, like `3` rather than `3.0`.
+pub fn format_number(number: f64) -> String {
+    if number.is_nan() {
+        "NaN".to_string()
+    } else if number.is_infinite() {
+        if number > 0.0 { "Infinity" } else { "-Infinity" }.to_string()
+    } else if number.fract() == 0.0 && number.abs() < 1e21 {
+        format!("{:.0}", number)
+    } else {
+        number.to_string()
+    }
+}
+
+// Lines up positional and keyword arguments with `params`, giving the value
+// for each positional parameter (`undefined` where missing) and the extras
+// a rest parameter collects.
+pub(crate) fn arrange_arguments(
+    params: &Params,
+    args: Vec<Value>,
+    keywords: Vec<(String, Value)>,
+    exact: bool,
+) -> EvalResult<(Vec<Value>, Vec<Value>)> {
+    let given = args.len();
+    let mut slots = vec![None; params.positional.len()];
+    let mut args = args.into_iter();
+    for (slot, value) in slots.iter_mut().zip(args.by_ref()) {
+        *slot = Some(value);
+    }
+
+    let extra: Vec<Value> = args.collect();
+    if exact && !extra.is_empty() && params.rest.is_none() {
+        let expected = params.positional.len();
+        return throw("TypeError", format!(
+            "Expected at most {} argument{}, got {}",
+            expected,
+            if expected == 1 { "" } else { "s" },
+            given
+        ));
+    }
+
+    for (name, value) in keywords {
+        let position = params
+            .positional
+            .iter()
+            .position(|binding| matches!(&binding.target, Pattern::Name(param) if param.name == name));
+        let Some(position) = position else {
+            return throw("TypeError", format!("Unknown keyword argument '{}'", name));
+        };
+        if slots[position].is_some() {
+            return throw("TypeError", format!("Argument '{}' was given more than once", name));
+        }
+        slots[position] = Some(value);
+    }
+
+    let positional = slots.into_iter().map(|slot| slot.unwrap_or(Value::Undefined)).collect();
+    Ok((positional, extra))
+}
+
+// Reads a variable from the slot the resolver gave it.
+pub(crate) fn lookup(variable: &Ident, env: &Env) -> EvalResult<Value> {
+    match env.borrow().get(variable) {
+        Some(value) => Ok(value),
+        None => throw("ReferenceError", format!("{} is not defined", variable.name)),
+    }
+}
+
+pub fn try_parse_source(source: &str) -> Result<Vec<Stmt>, ParseError> {
+    let tokens = Lexer::new(source).tokenize();
+    Parser::new(tokens).parse()
+}
+
+// For source known to be valid, such as in tests; panics with the parse error otherwise.
+pub fn parse_source(source: &str) -> Vec<Stmt> {
+    try_parse_source(source).unwrap_or_else(|err| panic!("{}", err))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{try_parse_source, Backend, Evaluator};
+    use crate::runtime::testing::{capture, logged};
+    use crate::utils::errors::Control;
+
+    #[test]
+    fn enums_are_distinct_values_usable_in_match() {
+        let output = logged(r#"
+            enum Role { Admin = "admin", User, Guest }
+            init describe(role):
+                match role:
+                    Role.Admin:
+                        return "full access"
+                    Role.User, Role.Guest:
+                        return "limited"
+            log([
+                Role.values(), Role.Admin.value, Role.Guest.value, Role.User.toString(),
+                describe(Role.Guest), Role.Admin == Role.Admin, Role.Admin == "admin", Role.User != Role.Guest
+            ])
+        "#);
+
+        assert_eq!(
+            output,
+            r#"[[Role.Admin, Role.User, Role.Guest], "admin", 2, "User", "limited", true, false, true]"#
+        );
+    }
+
+    #[test]
+    fn matches_on_enums_must_cover_every_member() {
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            let (result, output) = capture(&mut evaluator, r#"
+                enum Light { Red, Amber, Green }
+                match Light.Red:
+                    Light.Red:
+                        log("stop")
+            "#);
+
+            let Err(Control::Throw(error)) = result else {
+                panic!("expected a MatchError");
+            };
+            assert_eq!(
+                evaluator.describe_error(error),
+                "MatchError: Non-exhaustive match on enum Light: missing Amber, Green"
+            );
+            assert_eq!(output, "");
         }
     }
+
+    #[test]
+    fn members_outlive_the_enum_that_declared_them() {
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            let (result, output) = capture(&mut evaluator, r#"
+                init make():
+                    enum Light { Red, Amber, Green }
+                    return Light.Red
+                red = make()
+                log(red)
+                match red:
+                    red:
+                        log("stop")
+            "#);
+
+            let Err(Control::Throw(error)) = result else {
+                panic!("expected a MatchError");
+            };
+            assert_eq!(
+                evaluator.describe_error(error),
+                "MatchError: Non-exhaustive match on enum Light: missing Amber, Green"
+            );
+            assert_eq!(output, "Light.Red");
+        }
+    }
+
+    #[test]
+    fn functions_are_equal_only_to_themselves() {
+        let output = logged(r#"
+            init greet():
+                return "hi"
+            init counter():
+                init next():
+                    return 1
+                return next
+            first = counter()
+            log([greet == greet, first == first, first == counter(), greet == first, greet != greet])
+        "#);
+
+        assert_eq!(output, "[true, true, false, false, false]");
+    }
+
+    #[test]
+    fn syntax_errors_are_returned_rather_than_raised() {
+        let messages: Vec<String> = ["x = (1", "log(`${1 +}`)", "return 1", "log(`open"]
+            .iter()
+            .map(|source| try_parse_source(source).unwrap_err().message)
+            .collect();
+
+        assert_eq!(messages, [
+            "Expected ')' after expression (found EOF)",
+            "Unexpected token in expression: EOF",
+            "`return` outside of a function on line 1",
+            "Unterminated template string on line 1",
+        ]);
+    }
+
+    #[test]
+    fn patterns_destructure_assignments_parameters_and_loops() {
+        let output = logged(r#"
+            user = {name: "Ada", age: 36, address: {city: "London"}, admin: true}
+            {name, age: years, role = "user", address: {city}, ...others} = user
+            [first, [second, third = 3], ...rest] = [1, [2], 4, 5]
+            init describe({name, age}, [tag, ...more]):
+                return `${name} (${age}) ${tag} ${more}`
+            for ({name: label, age} in [{name: "Bo", age: 7}, {name: "Cy", age: 9}]):
+                log(`${label}=${age}`)
+            try:
+                {missing} = 42
+            catch (TypeError err):
+                failed = err.message
+            log([
+                name, years, role, city, others, first, second, third, rest,
+                describe(user, ["vip"]), describe(user, "abc"), failed
+            ])
+        "#);
+
+        assert_eq!(
+            output,
+            r#"Bo=7
+Cy=9
+["Ada", 36, "user", "London", {admin: true}, 1, 2, 3, [4, 5], "Ada (36) vip []", "Ada (36) a [\"b\", \"c\"]", "Cannot destructure 42 as it is not an object"]"#
+        );
+    }
+
+    #[test]
+    fn spreads_expand_arrays_objects_and_arguments_in_order() {
+        let output = logged(r#"
+            init join(first, ...rest):
+                return [first, rest]
+            old = [2, 3]
+            defaults = {name: "guest", role: "user", theme: "dark"}
+            user = {...defaults, name: "Ada", ...{role: "admin"}, ...null}
+            try:
+                broken = [...42]
+            catch (TypeError err):
+                list = err.message
+            try:
+                broken = {...true}
+            catch (TypeError err):
+                object = err.message
+            log([
+                [1, ...old, 4, ..."ab"], user, defaults.role, join(...[5], ...old),
+                {...["x", "y"]}, list, object
+            ])
+        "#);
+
+        assert_eq!(
+            output,
+            r#"[[1, 2, 3, 4, "a", "b"], {name: "Ada", role: "admin", theme: "dark"}, "user", [5, [2, 3]], {0: "x", 1: "y"}, "42 is not iterable", "Cannot spread true into an object"]"#
+        );
+    }
+
+    #[test]
+    fn optional_chains_and_nullish_coalescing_skip_null_and_undefined() {
+        let output = logged(r#"
+            init loud(text):
+                log(text)
+                return text
+            user = {profile: {email: "ada@example.com", tags: ["admin"]}, greet: null}
+            guest = null
+            name = null
+            name ??= "Guest"
+            count = 0
+            count ??= 10
+            try:
+                guest.profile
+            catch (TypeError err):
+                plain = err.message
+            log([
+                user?.profile?.email, guest?.profile.email, guest?.[loud("skipped")],
+                user.profile?.tags?.[0], user.greet?.(loud("skipped")), user.missing?.deep.deeper,
+                guest ?? user.profile.email, 0 ?? loud("skipped"), undefined ?? null ?? "last",
+                name, count, plain
+            ])
+        "#);
+
+        assert_eq!(
+            output,
+            r#"["ada@example.com", undefined, undefined, "admin", undefined, undefined, "ada@example.com", 0, "last", "Guest", 0, "Cannot read property 'profile' of null"]"#
+        );
+    }
+
+    #[test]
+    fn parameters_take_defaults_rest_and_keyword_arguments() {
+        let output = logged(r#"
+            init save(name, role = "user", label = `${name}:${role}`, ...tags):
+                return [name, role, label, tags]
+            init pair(first, second):
+                return [first, second]
+            try:
+                pair(1, 2, 3)
+            catch (TypeError err):
+                many = err.message
+            try:
+                save("Ada", nickname: "A")
+            catch (TypeError err):
+                unknown = err.message
+            try:
+                save("Ada", name: "Bo")
+            catch (TypeError err):
+                twice = err.message
+            try:
+                BigInt(value: "1")
+            catch (TypeError err):
+                native = err.message
+            log([
+                save("Ada"), save("Ada", "admin", "boss", "x", "y"), save(role: "guest", name: "Bo"),
+                pair(1), pair(second: 2), many, unknown, twice, native
+            ])
+        "#);
+
+        assert_eq!(
+            output,
+            r#"[["Ada", "user", "Ada:user", []], ["Ada", "admin", "boss", ["x", "y"]], ["Bo", "guest", "Bo:guest", []], [1, undefined], [undefined, 2], "Expected at most 2 arguments, got 3", "Unknown keyword argument 'nickname'", "Argument 'name' was given more than once", "BigInt() does not take keyword arguments (got 'value')"]"#
+        );
+    }
+
+    #[test]
+    fn arrow_functions_capture_their_environment() {
+        let output = logged(r#"
+            add = (x, y) =>: return x + y
+            twice = (x) => x * 2
+            init counter(start):
+                count = start
+                return () =>:
+                    count = count + 1
+                    return count
+            next = counter(10)
+            next()
+            later = Promise.resolve(20).then((value) =>:
+                doubled = twice(value)
+                return doubled + 1
+            )
+            fetchLater = () => await later
+            log([add(1, 2), twice(add(1, 2)), (1 + 2) * 3, next(), await later, await fetchLater(), () => 1])
+        "#);
+
+        assert_eq!(output, "[3, 6, 9, 12, 41, 41, <function>]");
+    }
+
+    #[test]
+    fn assignments_update_variables_members_and_indices() {
+        let output = logged(r#"
+            x = 1
+            x += 4
+            x *= 3
+            x -= 1
+            x /= 2
+            x **= 2
+            x %= 40
+            user = {name: "Ada", visits: 1}
+            user.visits++
+            user.email ??= "ada@example.com"
+            user.name ??= "ignored"
+            list = [10, 20]
+            calls = 0
+            init slot():
+                calls++
+                return 1
+            list[slot()] += 5
+            list[3] = 40
+            before = x++
+            after = --x
+            big = BigInt(9)
+            big++
+            a = b = 7
+            greeting = "Hi"
+            greeting += "!"
+            try:
+                user.name++
+            catch (TypeError err):
+                invalid = err.message
+            try:
+                missing += 1
+            catch (ReferenceError err):
+                undefinedName = err.message
+            log([x, before, after, user, list, calls, big, a, b, greeting, invalid, undefinedName])
+        "#);
+
+        assert_eq!(
+            output,
+            r#"[9, 9, 9, {name: "Ada", visits: 2, email: "ada@example.com"}, [10, 25, undefined, 40], 1, 10n, 7, 7, "Hi!", "Cannot apply '++' to Ada", "missing is not defined"]"#
+        );
+    }
+
+    #[test]
+    fn logical_operators_short_circuit_and_return_operands() {
+        let output = logged(r#"
+            calls = 0
+            init touch(value):
+                calls++
+                return value
+            log([
+                !true, !0, !"", ![], !!BigInt(0), 0 || "fallback", "set" || touch(1), null && touch(2),
+                1 && "last", !(1 == 2) && 3 > 2, 1 === 1, "1" !== 1, 1 == 1 || touch(3), calls
+            ])
+        "#);
+
+        assert_eq!(
+            output,
+            r#"[false, true, true, false, false, "fallback", "set", null, "last", true, true, true, true, 0]"#
+        );
+    }
 }
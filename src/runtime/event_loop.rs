@@ -0,0 +1,536 @@
+use crate::runtime::evaluator::Evaluator;
+use crate::runtime::promise::{PromiseRef, PromiseState, Reaction, Settled};
+use crate::runtime::values::Value;
+use crate::utils::errors::{error_value, EvalResult};
+
+use std::any::Any;
+use std::collections::{HashMap, VecDeque};
+use std::mem;
+use std::panic::{self, AssertUnwindSafe};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::{Duration, Instant};
+
+pub type Task = Box<dyn FnOnce(&mut Evaluator) -> EvalResult<()>>;
+
+// What a worker thread sends back: its result, or the message it panicked with.
+type Payload = Result<Box<dyn Any + Send>, String>;
+type Completion = Box<dyn FnOnce(&mut Evaluator, Payload) -> EvalResult<()>>;
+
+// Sources the loop has to check itself, such as a listening socket.
+pub type Poller = Box<dyn FnMut(&mut Evaluator) -> EvalResult<Poll>>;
+
+pub enum Poll {
+    Idle,
+    Progress,
+    Done,
+}
+
+// How long to sleep between checks when only pollers are keeping the loop alive.
+const POLL_INTERVAL: Duration = Duration::from_millis(5);
+
+// A callback that `setInterval` runs every time its timer comes due.
+pub type Repeating = Rc<dyn Fn(&mut Evaluator) -> EvalResult<()>>;
+
+enum TimerTask {
+    Once(Task),
+    Every(u64, Repeating),
+}
+
+struct Timer {
+    id: u64,
+    due: u64,
+    task: TimerTask,
+}
+
+// Timers read the real clock, or a virtual one that jumps straight to the
+// next due timer instead of sleeping, so tests don't have to wait.
+enum Clock {
+    Real(Instant),
+    Virtual(u64),
+}
+
+// Single-threaded scheduler: microtasks (promise reactions) run first, then
+// due timers, then completions from IO that worker threads finished.
+pub struct EventLoop {
+    microtasks: VecDeque<Task>,
+    timers: Vec<Timer>,
+    clock: Clock,
+    next_id: u64,
+    io_sender: Sender<(u64, Payload)>,
+    io_receiver: Receiver<(u64, Payload)>,
+    pending_io: HashMap<u64, Completion>,
+    pollers: Vec<Poller>,
+    // Promises that were rejected while nothing was listening.
+    rejections: Vec<PromiseRef>,
+}
+
+impl EventLoop {
+    pub fn new() -> Self {
+        let (io_sender, io_receiver) = mpsc::channel();
+        Self {
+            microtasks: VecDeque::new(),
+            timers: Vec::new(),
+            clock: Clock::Real(Instant::now()),
+            next_id: 1,
+            io_sender,
+            io_receiver,
+            pending_io: HashMap::new(),
+            pollers: Vec::new(),
+            rejections: Vec::new(),
+        }
+    }
+
+    fn next_id(&mut self) -> u64 {
+        let id = self.next_id;
+        self.next_id += 1;
+        id
+    }
+
+    pub fn queue_microtask(&mut self, task: impl FnOnce(&mut Evaluator) -> EvalResult<()> + 'static) {
+        self.microtasks.push_back(Box::new(task));
+    }
+
+    // Milliseconds since the loop started.
+    pub fn now(&self) -> u64 {
+        match self.clock {
+            Clock::Real(started) => started.elapsed().as_millis() as u64,
+            Clock::Virtual(now) => now,
+        }
+    }
+
+    pub fn use_virtual_clock(&mut self) {
+        self.clock = Clock::Virtual(self.now());
+    }
+
+    pub fn set_timer(&mut self, delay_ms: u64, task: impl FnOnce(&mut Evaluator) -> EvalResult<()> + 'static) -> u64 {
+        let id = self.next_id();
+        let due = self.now() + delay_ms;
+        self.timers.push(Timer { id, due, task: TimerTask::Once(Box::new(task)) });
+        id
+    }
+
+    // Runs `task` every `interval_ms` until the returned id is cleared.
+    pub fn set_interval(&mut self, interval_ms: u64, task: Repeating) -> u64 {
+        let id = self.next_id();
+        let due = self.now() + interval_ms;
+        self.timers.push(Timer { id, due, task: TimerTask::Every(interval_ms, task) });
+        id
+    }
+
+    pub fn clear_timer(&mut self, id: u64) -> bool {
+        let before = self.timers.len();
+        self.timers.retain(|timer| timer.id != id);
+        self.timers.len() != before
+    }
+
+    // Runs `work` on a worker thread; `complete` gets its result back on the loop's
+    // thread, or an error value if `work` panicked.
+    pub fn spawn_io<T: Send + 'static>(
+        &mut self,
+        work: impl FnOnce() -> T + Send + 'static,
+        complete: impl FnOnce(&mut Evaluator, Result<T, Value>) -> EvalResult<()> + 'static,
+    ) {
+        let id = self.next_id();
+        let sender = self.io_sender.clone();
+        thread::spawn(move || {
+            // A panicking worker still has to report back, or the loop would wait for it forever.
+            let result: Payload = panic::catch_unwind(AssertUnwindSafe(work))
+                .map(|value| Box::new(value) as Box<dyn Any + Send>)
+                .map_err(|payload| panic_message(&*payload));
+            // The loop may have been dropped if the program already ended.
+            let _ = sender.send((id, result));
+        });
+
+        self.pending_io.insert(id, Box::new(move |evaluator, payload| {
+            let result = match payload {
+                Ok(value) => match value.downcast::<T>() {
+                    Ok(value) => Ok(*value),
+                    Err(_) => unreachable!("io completion received a payload of the wrong type"),
+                },
+                Err(message) => Err(error_value("InternalError", format!("IO worker panicked: {}", message))),
+            };
+            complete(evaluator, result)
+        }));
+    }
+
+    pub fn add_poller(&mut self, poller: Poller) {
+        self.pollers.push(poller);
+    }
+
+    pub fn has_pending_work(&self) -> bool {
+        !self.microtasks.is_empty()
+            || !self.timers.is_empty()
+            || !self.pending_io.is_empty()
+            || !self.pollers.is_empty()
+    }
+
+    pub fn resolve(&mut self, promise: &PromiseRef, value: Value) {
+        match value {
+            // Resolving with another promise follows that promise instead.
+            Value::Promise(inner) if Rc::ptr_eq(&inner, promise) => {
+                let error = error_value("TypeError", "A promise cannot be resolved with itself");
+                self.settle(promise, Err(error));
+            }
+            Value::Promise(inner) => {
+                let promise = Rc::clone(promise);
+                self.subscribe(&inner, Box::new(move |evaluator, outcome| {
+                    evaluator.event_loop().settle(&promise, outcome);
+                    Ok(())
+                }));
+            }
+            value => self.settle(promise, Ok(value)),
+        }
+    }
+
+    pub fn reject(&mut self, promise: &PromiseRef, reason: Value) {
+        self.settle(promise, Err(reason));
+    }
+
+    // Settling twice is a no-op, matching JavaScript's resolve/reject functions.
+    pub fn settle(&mut self, promise: &PromiseRef, outcome: Settled) {
+        let reactions = {
+            let mut state = promise.borrow_mut();
+            if !state.is_pending() {
+                return;
+            }
+            state.state = match &outcome {
+                Ok(value) => PromiseState::Fulfilled(value.clone()),
+                Err(reason) => PromiseState::Rejected(reason.clone()),
+            };
+            mem::take(&mut state.reactions)
+        };
+
+        if outcome.is_err() && reactions.is_empty() && !promise.borrow().handled {
+            self.rejections.push(Rc::clone(promise));
+        }
+
+        for reaction in reactions {
+            let outcome = outcome.clone();
+            self.queue_microtask(move |evaluator| reaction(evaluator, outcome));
+        }
+    }
+
+    pub fn subscribe(&mut self, promise: &PromiseRef, reaction: Reaction) {
+        let settled = {
+            let mut state = promise.borrow_mut();
+            state.handled = true;
+            state.settled()
+        };
+
+        match settled {
+            None => promise.borrow_mut().reactions.push(reaction),
+            Some(outcome) => self.queue_microtask(move |evaluator| reaction(evaluator, outcome)),
+        }
+    }
+
+    // Rejection reasons that nothing ever handled; clears the list.
+    pub fn take_unhandled_rejections(&mut self) -> Vec<Value> {
+        mem::take(&mut self.rejections)
+            .into_iter()
+            .filter(|promise| !promise.borrow().handled)
+            .filter_map(|promise| promise.borrow().settled().and_then(Result::err))
+            .collect()
+    }
+
+    fn next_due_timer(&mut self) -> Option<Task> {
+        let now = self.now();
+        let index = self
+            .timers
+            .iter()
+            .enumerate()
+            .filter(|(_, timer)| timer.due <= now)
+            .min_by_key(|(_, timer)| (timer.due, timer.id))
+            .map(|(index, _)| index)?;
+
+        let timer = self.timers.remove(index);
+        match timer.task {
+            TimerTask::Once(task) => Some(task),
+            // Rescheduled before it runs, so the callback can clear its own interval.
+            TimerTask::Every(interval, repeating) => {
+                let task = Rc::clone(&repeating);
+                self.timers.push(Timer {
+                    id: timer.id,
+                    due: now + interval,
+                    task: TimerTask::Every(interval, repeating),
+                });
+                Some(Box::new(move |evaluator| task(evaluator)))
+            }
+        }
+    }
+
+    // Blocks until an IO completion arrives or it's time to look at timers/pollers again.
+    fn wait_for_io(&mut self) -> Option<(Completion, Payload)> {
+        if let Clock::Virtual(now) = &mut self.clock
+            && let Some(due) = self.timers.iter().map(|timer| timer.due).min()
+        {
+            *now = due.max(*now);
+            return None;
+        }
+
+        let mut timeout = self
+            .timers
+            .iter()
+            .map(|timer| Duration::from_millis(timer.due.saturating_sub(self.now())))
+            .min();
+        if !self.pollers.is_empty() {
+            timeout = Some(timeout.map_or(POLL_INTERVAL, |timeout| timeout.min(POLL_INTERVAL)));
+        }
+
+        if self.pending_io.is_empty() {
+            if let Some(timeout) = timeout {
+                thread::sleep(timeout);
+            }
+            return None;
+        }
+
+        let received = match timeout {
+            Some(timeout) => self.io_receiver.recv_timeout(timeout).ok(),
+            None => self.io_receiver.recv().ok(),
+        };
+
+        let (id, payload) = received?;
+        self.pending_io.remove(&id).map(|complete| (complete, payload))
+    }
+}
+
+fn panic_message(payload: &(dyn Any + Send)) -> String {
+    payload
+        .downcast_ref::<String>()
+        .cloned()
+        .or_else(|| payload.downcast_ref::<&str>().map(|message| message.to_string()))
+        .unwrap_or_else(|| "unknown cause".to_string())
+}
+
+impl Default for EventLoop {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+// Drives the loop until `until` settles, or until there is no work left at all.
+pub fn run(evaluator: &mut Evaluator, until: Option<&PromiseRef>) -> EvalResult<()> {
+    loop {
+        while let Some(task) = evaluator.event_loop().microtasks.pop_front() {
+            task(evaluator)?;
+        }
+
+        if until.is_some_and(|promise| !promise.borrow().is_pending()) {
+            return Ok(());
+        }
+
+        if let Some(task) = evaluator.event_loop().next_due_timer() {
+            task(evaluator)?;
+            continue;
+        }
+
+        if poll_sources(evaluator)? {
+            continue;
+        }
+
+        if !evaluator.event_loop().has_pending_work() {
+            return Ok(());
+        }
+
+        if let Some((complete, payload)) = evaluator.event_loop().wait_for_io() {
+            complete(evaluator, payload)?;
+        }
+    }
+}
+
+// Returns whether any poller made progress.
+fn poll_sources(evaluator: &mut Evaluator) -> EvalResult<bool> {
+    let mut pollers = mem::take(&mut evaluator.event_loop().pollers);
+    let mut progressed = false;
+    let mut result = Ok(());
+
+    pollers.retain_mut(|poller| {
+        if result.is_err() {
+            return true;
+        }
+        match poller(evaluator) {
+            Ok(Poll::Idle) => true,
+            Ok(Poll::Progress) => {
+                progressed = true;
+                true
+            }
+            Ok(Poll::Done) => {
+                progressed = true;
+                false
+            }
+            Err(err) => {
+                result = Err(err);
+                true
+            }
+        }
+    });
+
+    // Keep pollers registered while these ones were running.
+    let added = mem::take(&mut evaluator.event_loop().pollers);
+    pollers.extend(added);
+    evaluator.event_loop().pollers = pollers;
+
+    result.map(|_| progressed)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::runtime::evaluator::Backend;
+    use crate::runtime::promise::Promise;
+    use crate::runtime::testing::{capture, logged};
+    use crate::utils::errors::Control;
+
+    #[test]
+    fn awaiting_functions_return_promises_and_rejections_are_catchable() {
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            let (result, output) = capture(&mut evaluator, r#"
+                init load(name):
+                    value = await name
+                    return `loaded ${value}`
+                init fail(reason):
+                    await null
+                    throw reason
+
+                pending = load("a")
+                try:
+                    await fail("boom")
+                catch (err):
+                    log([pending, await pending, err])
+                fail("ignored")
+            "#);
+
+            assert!(result.is_ok(), "{:?}", result);
+            assert_eq!(output, r#"[<promise fulfilled: "loaded a">, "loaded a", "boom"]"#);
+
+            let rejections = evaluator.take_unhandled_rejections();
+            assert_eq!(rejections.len(), 1);
+            assert_eq!(evaluator.stringify(rejections[0].clone()), "ignored");
+        }
+    }
+
+    #[test]
+    fn awaiting_suspends_the_call_and_lets_the_caller_carry_on() {
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            evaluator.event_loop().use_virtual_clock();
+            let (result, output) = capture(&mut evaluator, r#"
+                init a():
+                    log("a start")
+                    await sleep(20)
+                    log("a end")
+                init b():
+                    log("b start")
+                    await sleep(10)
+                    log("b end")
+                pa = a()
+                pb = b()
+                log("sync end")
+            "#);
+
+            assert!(result.is_ok(), "{:?}", result);
+            assert_eq!(output, "a start\nb start\nsync end\nb end\na end");
+            assert_eq!(evaluator.event_loop().now(), 20);
+        }
+    }
+
+    #[test]
+    fn awaited_promises_may_settle_after_the_call() {
+        let output = logged(r#"
+            init waitFor(promise):
+                value = await promise
+                log(`got ${value}`)
+                return value * 2
+            init guarded(promise):
+                try:
+                    await promise
+                catch (err):
+                    return `caught ${err}`
+                finally:
+                    log("cleanup")
+
+            release = null
+            refuse = null
+            gate = Promise((resolve) => release = resolve)
+            doubled = waitFor(gate)
+            failing = Promise((resolve, reject) => refuse = reject)
+            outcome = guarded(failing)
+            log("called")
+            release(21)
+            refuse("nope")
+            log(["both", await doubled, await outcome])
+        "#);
+
+        assert_eq!(output, "called\ngot 21\ncleanup\n[\"both\", 42, \"caught nope\"]");
+    }
+
+    #[test]
+    fn concurrent_calls_wait_together() {
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            evaluator.event_loop().use_virtual_clock();
+            let (result, output) = capture(&mut evaluator, r#"
+                init job(name):
+                    await sleep(300)
+                    return name
+                log(await Promise.all([job("a"), job("b"), job("c")]))
+            "#);
+
+            assert!(result.is_ok(), "{:?}", result);
+            assert_eq!(output, r#"["a", "b", "c"]"#);
+            // One after another they would have taken 900.
+            assert_eq!(evaluator.event_loop().now(), 300);
+        }
+    }
+
+    #[test]
+    fn awaiting_io_runs_other_completions_first() {
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            let first = Promise::new();
+            let second = Promise::new();
+
+            for (promise, delay) in [(Rc::clone(&first), 40), (Rc::clone(&second), 5)] {
+                evaluator.event_loop().spawn_io(
+                    move || {
+                        thread::sleep(Duration::from_millis(delay));
+                        delay
+                    },
+                    move |evaluator, delay| {
+                        evaluator.event_loop().resolve(&promise, Value::Number(delay.unwrap() as f64));
+                        Ok(())
+                    },
+                );
+            }
+
+            let value = evaluator.await_value(Value::Promise(Rc::clone(&first))).unwrap();
+            assert_eq!(evaluator.stringify(value), "40");
+            assert!(!second.borrow().is_pending());
+        }
+    }
+
+    #[test]
+    fn a_panicking_worker_rejects_instead_of_hanging() {
+        let mut evaluator = Evaluator::new();
+        let promise = Promise::new();
+        let pending = Rc::clone(&promise);
+        evaluator.event_loop().spawn_io(
+            || -> u8 { panic!("disk on fire") },
+            move |evaluator, result| {
+                match result {
+                    Ok(value) => evaluator.event_loop().resolve(&pending, Value::Number(value as f64)),
+                    Err(reason) => evaluator.event_loop().reject(&pending, reason),
+                }
+                Ok(())
+            },
+        );
+
+        let Err(Control::Throw(reason)) = evaluator.await_value(Value::Promise(promise)) else {
+            panic!("expected the promise to be rejected");
+        };
+        assert_eq!(evaluator.describe_error(reason), "InternalError: IO worker panicked: disk on fire");
+        assert!(!evaluator.event_loop().has_pending_work());
+    }
+}
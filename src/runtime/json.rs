@@ -0,0 +1,198 @@
+use crate::runtime::evaluator::format_number;
+use crate::runtime::values::{Object, Value};
+
+use std::iter::Peekable;
+use std::str::Chars;
+
+pub fn parse(text: &str) -> Result<Value, String> {
+    let mut reader = Reader { chars: text.chars().peekable() };
+    let value = reader.value()?;
+    reader.skip_whitespace();
+    match reader.chars.next() {
+        None => Ok(value),
+        Some(c) => Err(format!("Unexpected '{}' after JSON value", c)),
+    }
+}
+
+// Functions and other values without a JSON form are written as `null`.
+pub fn stringify(value: &Value) -> String {
+    match value {
+        Value::String(s) => quote(s),
+        Value::Number(number) if number.is_finite() => format_number(*number),
+        Value::Boolean(value) => value.to_string(),
+        // Written as a plain JSON number so no digits are lost.
+        Value::BigInt(big) => big.to_string_radix(10),
+        Value::Date(date) => quote(&date.to_iso()),
+        Value::EnumMember(member) => stringify(&member.value),
+        Value::Array(items) => {
+            let items: Vec<String> = items.borrow().iter().map(stringify).collect();
+            format!("[{}]", items.join(","))
+        }
+        Value::Object(object) => {
+            let fields: Vec<String> = object
+                .borrow()
+                .entries()
+                .map(|(key, value)| format!("{}:{}", quote(key), stringify(value)))
+                .collect();
+            format!("{{{}}}", fields.join(","))
+        }
+        _ => "null".to_string(),
+    }
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::from("\"");
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            c if (c as u32) < 0x20 => out.push_str(&format!("\\u{:04x}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+struct Reader<'a> {
+    chars: Peekable<Chars<'a>>,
+}
+
+impl Reader<'_> {
+    fn skip_whitespace(&mut self) {
+        while matches!(self.chars.peek(), Some(' ' | '\n' | '\r' | '\t')) {
+            self.chars.next();
+        }
+    }
+
+    fn expect(&mut self, expected: char) -> Result<(), String> {
+        match self.chars.next() {
+            Some(c) if c == expected => Ok(()),
+            Some(c) => Err(format!("Expected '{}' in JSON but found '{}'", expected, c)),
+            None => Err(format!("Expected '{}' but JSON ended", expected)),
+        }
+    }
+
+    fn value(&mut self) -> Result<Value, String> {
+        self.skip_whitespace();
+        match self.chars.peek() {
+            Some('{') => self.object(),
+            Some('[') => self.array(),
+            Some('"') => self.string().map(Value::String),
+            Some('t') => self.word("true", Value::Boolean(true)),
+            Some('f') => self.word("false", Value::Boolean(false)),
+            Some('n') => self.word("null", Value::Null),
+            Some(c) if *c == '-' || c.is_ascii_digit() => self.number(),
+            Some(c) => Err(format!("Unexpected '{}' in JSON", c)),
+            None => Err("Unexpected end of JSON".to_string()),
+        }
+    }
+
+    fn word(&mut self, word: &str, value: Value) -> Result<Value, String> {
+        for expected in word.chars() {
+            self.expect(expected)?;
+        }
+        Ok(value)
+    }
+
+    fn number(&mut self) -> Result<Value, String> {
+        let mut text = String::new();
+        while let Some(&c) = self.chars.peek() {
+            if c.is_ascii_digit() || matches!(c, '-' | '+' | '.' | 'e' | 'E') {
+                text.push(c);
+                self.chars.next();
+            } else {
+                break;
+            }
+        }
+        text.parse()
+            .map(Value::Number)
+            .map_err(|_| format!("Invalid number '{}' in JSON", text))
+    }
+
+    fn string(&mut self) -> Result<String, String> {
+        self.expect('"')?;
+        let mut out = String::new();
+        loop {
+            match self.chars.next() {
+                Some('"') => return Ok(out),
+                Some('\\') => match self.chars.next() {
+                    Some('n') => out.push('\n'),
+                    Some('r') => out.push('\r'),
+                    Some('t') => out.push('\t'),
+                    Some('b') => out.push('\u{8}'),
+                    Some('f') => out.push('\u{c}'),
+                    Some('u') => out.push(self.unicode_escape()?),
+                    Some(c) => out.push(c),
+                    None => return Err("Unterminated string in JSON".to_string()),
+                },
+                Some(c) => out.push(c),
+                None => return Err("Unterminated string in JSON".to_string()),
+            }
+        }
+    }
+
+    fn unicode_escape(&mut self) -> Result<char, String> {
+        let high = self.hex4()?;
+        // Characters outside the BMP arrive as a UTF-16 surrogate pair.
+        if (0xD800..0xDC00).contains(&high) {
+            self.expect('\\')?;
+            self.expect('u')?;
+            let low = self.hex4()?;
+            let code = 0x10000 + ((high - 0xD800) << 10) + (low.wrapping_sub(0xDC00) & 0x3FF);
+            return char::from_u32(code).ok_or_else(|| "Invalid surrogate pair in JSON".to_string());
+        }
+        char::from_u32(high).ok_or_else(|| "Invalid \\u escape in JSON".to_string())
+    }
+
+    fn hex4(&mut self) -> Result<u32, String> {
+        let digits: String = (0..4).filter_map(|_| self.chars.next()).collect();
+        u32::from_str_radix(&digits, 16).map_err(|_| format!("Invalid \\u escape '{}' in JSON", digits))
+    }
+
+    fn array(&mut self) -> Result<Value, String> {
+        self.expect('[')?;
+        let mut items = Vec::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&']') {
+            self.chars.next();
+            return Ok(Value::array(items));
+        }
+        loop {
+            items.push(self.value()?);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some(']') => return Ok(Value::array(items)),
+                _ => return Err("Expected ',' or ']' in JSON array".to_string()),
+            }
+        }
+    }
+
+    fn object(&mut self) -> Result<Value, String> {
+        self.expect('{')?;
+        let mut object = Object::new();
+        self.skip_whitespace();
+        if self.chars.peek() == Some(&'}') {
+            self.chars.next();
+            return Ok(Value::object(object));
+        }
+        loop {
+            self.skip_whitespace();
+            let key = self.string()?;
+            self.skip_whitespace();
+            self.expect(':')?;
+            let value = self.value()?;
+            object.set(&key, value);
+            self.skip_whitespace();
+            match self.chars.next() {
+                Some(',') => continue,
+                Some('}') => return Ok(Value::object(object)),
+                _ => return Err("Expected ',' or '}' in JSON object".to_string()),
+            }
+        }
+    }
+}
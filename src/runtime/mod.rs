@@ -1,2 +1,16 @@
+pub mod bigint;
+pub mod builtins;
+pub mod bytecode;
+pub mod compiler;
+pub mod date;
+pub mod environment;
+pub mod event_loop;
 pub mod evaluator;
+pub mod json;
+pub mod modules;
+pub mod operators;
+pub mod promise;
+#[cfg(test)]
+pub(crate) mod testing;
 pub mod values;
+pub mod vm;
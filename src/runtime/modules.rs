@@ -0,0 +1,327 @@
+use crate::parser::ast::{Binding, Expr, Function, Pattern, PropertyKey, Stmt, TemplatePart};
+use crate::runtime::builtins;
+use crate::runtime::values::Value;
+
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+#[derive(Debug)]
+pub struct Module {
+    pub path: String,
+    exports: HashMap<String, Value>,
+    private: HashSet<String>,
+}
+
+impl Module {
+    pub fn new(path: String, exports: HashMap<String, Value>, private: HashSet<String>) -> Self {
+        Self {
+            path,
+            exports,
+            private,
+        }
+    }
+
+    pub fn native(name: &str, exports: Vec<(&str, Value)>) -> Self {
+        let exports = exports
+            .into_iter()
+            .map(|(name, value)| (name.to_string(), value))
+            .collect();
+        Self::new(name.to_string(), exports, HashSet::new())
+    }
+
+    pub fn export(&self, name: &str) -> Result<Value, String> {
+        if let Some(value) = self.exports.get(name) {
+            Ok(value.clone())
+        } else if self.private.contains(name) {
+            Err(format!(
+                "'{}' is private to module \"{}\" (mark it with `share` to export it)",
+                name, self.path
+            ))
+        } else {
+            Err(format!("Module \"{}\" has no export named '{}'", self.path, name))
+        }
+    }
+}
+
+pub struct ModuleLoader {
+    cache: HashMap<PathBuf, Rc<Module>>,
+    builtins: HashMap<String, Rc<Module>>,
+    loading: Vec<PathBuf>,
+}
+
+impl ModuleLoader {
+    pub fn new() -> Self {
+        Self {
+            cache: HashMap::new(),
+            builtins: HashMap::new(),
+            loading: Vec::new(),
+        }
+    }
+
+    // Built-in modules such as "fs" are created on first import and shared afterwards.
+    pub fn builtin(&mut self, name: &str) -> Option<Rc<Module>> {
+        if let Some(module) = self.builtins.get(name) {
+            return Some(Rc::clone(module));
+        }
+
+        let module = Rc::new(builtins::module(name)?);
+        self.builtins.insert(name.to_string(), Rc::clone(&module));
+        Some(module)
+    }
+
+    // Relative specifiers are resolved against the importing file's directory
+    // and get a `.gx` extension when they don't name one.
+    pub fn resolve(&self, spec: &str, base_dir: &Path) -> Result<PathBuf, String> {
+        let is_path = spec.starts_with("./") || spec.starts_with("../") || Path::new(spec).is_absolute();
+        if !is_path {
+            return Err(format!("Cannot find module \"{}\"", spec));
+        }
+
+        let mut path = base_dir.join(spec);
+        if path.extension().is_none() {
+            path.set_extension("gx");
+        }
+
+        path.canonicalize()
+            .map_err(|_| format!("Cannot find module \"{}\" (looked for {})", spec, path.display()))
+    }
+
+    pub fn cached(&self, path: &Path) -> Option<Rc<Module>> {
+        self.cache.get(path).cloned()
+    }
+
+    pub fn begin(&mut self, path: &Path) -> Result<(), String> {
+        if let Some(start) = self.loading.iter().position(|p| p == path) {
+            let cycle: Vec<String> = self.loading[start..]
+                .iter()
+                .chain(std::iter::once(&path.to_path_buf()))
+                .map(|p| p.display().to_string())
+                .collect();
+            return Err(format!("Circular import: {}", cycle.join(" -> ")));
+        }
+        self.loading.push(path.to_path_buf());
+        Ok(())
+    }
+
+    pub fn finish(&mut self, path: &Path, module: Rc<Module>) {
+        self.loading.retain(|p| p != path);
+        self.cache.insert(path.to_path_buf(), module);
+    }
+
+    // A module that threw while loading is not cached, so a later `get` retries it.
+    pub fn abandon(&mut self, path: &Path) {
+        self.loading.retain(|p| p != path);
+    }
+}
+
+// Module paths that are known without running the program, i.e. every `get`
+// whose source is a string literal. Dynamic imports are skipped.
+pub fn static_imports(program: &[Stmt]) -> Vec<String> {
+    let mut imports = Vec::new();
+    for stmt in program {
+        collect_imports(stmt, &mut imports);
+    }
+    imports
+}
+
+fn collect_imports(stmt: &Stmt, imports: &mut Vec<String>) {
+    match stmt {
+        Stmt::Get { source: Expr::Literal(path), .. } => imports.push(path.clone()),
+        Stmt::Share(inner) => collect_imports(inner, imports),
+        Stmt::Init { function, .. } => collect_function_imports(function, imports),
+        Stmt::For { pattern, iterable, body } => {
+            collect_pattern_imports(pattern, imports);
+            collect_expr_imports(iterable, imports);
+            body.iter().for_each(|stmt| collect_imports(stmt, imports));
+        }
+        Stmt::Try { body, catches, finally } => {
+            body.iter()
+                .chain(catches.iter().flat_map(|clause| clause.body.iter()))
+                .chain(finally.iter().flatten())
+                .for_each(|stmt| collect_imports(stmt, imports));
+        }
+        Stmt::Match { subject, cases, otherwise } => {
+            collect_expr_imports(subject, imports);
+            for case in cases {
+                case.patterns.iter().for_each(|pattern| collect_expr_imports(pattern, imports));
+                case.body.iter().for_each(|stmt| collect_imports(stmt, imports));
+            }
+            otherwise.iter().flatten().for_each(|stmt| collect_imports(stmt, imports));
+        }
+        Stmt::Destructure { pattern, value } => {
+            collect_pattern_imports(pattern, imports);
+            collect_expr_imports(value, imports);
+        }
+        Stmt::Enum { members, .. } => {
+            members.iter().flat_map(|(_, value)| value).for_each(|value| collect_expr_imports(value, imports));
+        }
+        Stmt::Get { source: value, .. }
+        | Stmt::Log { value }
+        | Stmt::Assign { value, .. }
+        | Stmt::Throw { value }
+        | Stmt::Return { value: Some(value) }
+        | Stmt::ExprStmt(value) => collect_expr_imports(value, imports),
+        Stmt::Return { value: None } => {}
+    }
+}
+
+// Arrow functions can sit anywhere in an expression, and their bodies can `get` too.
+fn collect_expr_imports(expr: &Expr, imports: &mut Vec<String>) {
+    match expr {
+        Expr::Function(function) => collect_function_imports(function, imports),
+        Expr::Call { callee, arguments, .. } => {
+            collect_expr_imports(callee, imports);
+            arguments.iter().for_each(|argument| collect_expr_imports(argument, imports));
+        }
+        Expr::Member { object: inner, .. }
+        | Expr::Await(inner)
+        | Expr::Unary { operand: inner, .. }
+        | Expr::Spread(inner)
+        | Expr::Keyword { value: inner, .. }
+        | Expr::Update { target: inner, .. } => collect_expr_imports(inner, imports),
+        Expr::Binary { left, right, .. }
+        | Expr::Logical { left, right, .. }
+        | Expr::Index { object: left, index: right, .. }
+        | Expr::Assign { target: left, value: right, .. } => {
+            collect_expr_imports(left, imports);
+            collect_expr_imports(right, imports);
+        }
+        Expr::Template(parts) => {
+            for part in parts {
+                if let TemplatePart::Expr(expr) = part {
+                    collect_expr_imports(expr, imports);
+                }
+            }
+        }
+        Expr::Array(items) => items.iter().for_each(|item| collect_expr_imports(item, imports)),
+        Expr::Object(fields) => {
+            for (key, value) in fields {
+                if let PropertyKey::Computed(key) = key {
+                    collect_expr_imports(key, imports);
+                }
+                collect_expr_imports(value, imports);
+            }
+        }
+        Expr::Literal(_) | Expr::Number(_) | Expr::Boolean(_) | Expr::Null | Expr::Undefined | Expr::Variable(_) => {}
+    }
+}
+
+fn collect_function_imports(function: &Function, imports: &mut Vec<String>) {
+    function.params.positional.iter().for_each(|binding| collect_binding_imports(binding, imports));
+    function.body.iter().for_each(|stmt| collect_imports(stmt, imports));
+}
+
+// Defaults in patterns are expressions, so they can hold functions as well.
+fn collect_pattern_imports(pattern: &Pattern, imports: &mut Vec<String>) {
+    match pattern {
+        Pattern::Name(_) => {}
+        Pattern::Object { properties, .. } => {
+            properties.iter().for_each(|(_, binding)| collect_binding_imports(binding, imports));
+        }
+        Pattern::Array { items, rest } => {
+            items.iter().for_each(|binding| collect_binding_imports(binding, imports));
+            if let Some(rest) = rest {
+                collect_pattern_imports(rest, imports);
+            }
+        }
+    }
+}
+
+fn collect_binding_imports(binding: &Binding, imports: &mut Vec<String>) {
+    collect_pattern_imports(&binding.target, imports);
+    if let Some(default) = &binding.default {
+        collect_expr_imports(default, imports);
+    }
+}
+
+impl Default for ModuleLoader {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::static_imports;
+    use crate::runtime::evaluator::{parse_source, Backend, Evaluator};
+    use crate::utils::errors::Control;
+
+    use std::fs;
+    use std::path::PathBuf;
+
+    fn write_fixture(dir: &str, files: &[(&str, &str)]) -> PathBuf {
+        let root = std::env::temp_dir().join(format!("genesix-modules-{}-{}", dir, std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        for (name, source) in files {
+            fs::write(root.join(name), source).unwrap();
+        }
+        root
+    }
+
+    #[test]
+    fn shared_names_and_re_exports_are_importable() {
+        let root = write_fixture("shared", &[
+            ("inner.gx", "share init shout(word): log(word)\n"),
+            ("utils.gx", "init _helper(): log(\"hidden\")\nshare VERSION = \"1.0\"\nshare get {shout} from \"./inner\"\n"),
+            ("main.gx", "get {VERSION, shout} from \"./utils\"\nshout(VERSION)\n"),
+        ]);
+
+        for backend in Backend::ALL {
+            assert!(Evaluator::with_backend(backend).run_file(&root.join("main.gx")).is_ok());
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn private_names_cannot_be_imported() {
+        let root = write_fixture("private", &[
+            ("utils.gx", "init _helper(): log(\"hidden\")\nshare VERSION = \"1.0\"\n"),
+            ("main.gx", "get {_helper} from \"./utils\"\n"),
+        ]);
+
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            let Err(Control::Throw(error)) = evaluator.run_file(&root.join("main.gx")) else {
+                panic!("expected import to fail");
+            };
+            assert_eq!(
+                evaluator.describe_error(error),
+                "ImportError: '_helper' is private to module \"./utils\" (mark it with `share` to export it)"
+            );
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn missing_or_broken_imports_are_catchable() {
+        let root = write_fixture("dynamic", &[
+            ("broken.gx", "share x = )\n"),
+            ("main.gx", "for (name in [\"./missing\", \"./broken\"]):\n    try:\n        get m from name\n    catch (ImportError err):\n        log(err.message)\n"),
+        ]);
+
+        for backend in Backend::ALL {
+            let mut evaluator = Evaluator::with_backend(backend);
+            evaluator.capture_output();
+            assert!(evaluator.run_file(&root.join("main.gx")).is_ok());
+            let output = evaluator.take_output();
+            assert!(output[0].starts_with("Cannot find module \"./missing\""), "{:?}", output);
+            assert_eq!(output[1], "Could not parse module \"./broken\": Unexpected token in expression: RightParen");
+        }
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn static_imports_skip_dynamic_sources() {
+        let program = parse_source("get fs from \"./a\"\ninit f():\n    get b from \"./b\"\nget c from input(\"?\")\n");
+        assert_eq!(static_imports(&program), vec!["./a", "./b"]);
+    }
+
+    #[test]
+    fn static_imports_include_those_in_function_expressions() {
+        let program = parse_source(
+            "load = () =>:\n    get x from \"./inner\"\n    return x\nsetTimeout(() =>:\n    get later from \"./later\"\n, 10)\n",
+        );
+        assert_eq!(static_imports(&program), vec!["./inner", "./later"]);
+    }
+}
@@ -0,0 +1,258 @@
+use crate::parser::tokens::TokenKind;
+use crate::runtime::bigint::BigInt;
+use crate::runtime::evaluator::{format_number, Evaluator};
+use crate::runtime::values::Value;
+use crate::utils::errors::{throw, EvalResult};
+
+use std::cmp::Ordering;
+
+const MIXED_BIGINT: &str = "Cannot mix BigInt and other types, use explicit conversions";
+// The most bits a BigInt shift or power may produce; anything bigger would need gigabytes.
+const MAX_BITS: u64 = 1 << 30;
+const TOO_BIG: &str = "Maximum BigInt size exceeded";
+
+pub fn unary(evaluator: &Evaluator, operator: &TokenKind, operand: Value) -> EvalResult<Value> {
+    match (operator, operand) {
+        (TokenKind::Bang, operand) => Ok(Value::Boolean(!operand.is_truthy())),
+        (TokenKind::Minus, Value::Number(number)) => Ok(Value::Number(-number)),
+        (TokenKind::Minus, Value::BigInt(big)) => Ok(Value::BigInt(big.neg())),
+        (TokenKind::Tilde, Value::Number(number)) => Ok(Value::Number(!to_int32(number, operator)? as f64)),
+        (TokenKind::Tilde, Value::BigInt(big)) => Ok(Value::BigInt(big.not())),
+        (operator, operand) => throw("TypeError", format!(
+            "Cannot apply '{}' to {}",
+            symbol(operator),
+            evaluator.stringify(operand)
+        )),
+    }
+}
+
+// Arithmetic and comparison. Operands never convert implicitly, except that
+// `+` joins as text when either side is a string.
+pub fn binary(evaluator: &Evaluator, operator: &TokenKind, left: Value, right: Value) -> EvalResult<Value> {
+    if matches!(operator, TokenKind::Greater | TokenKind::GreaterEqual | TokenKind::Less | TokenKind::LessEqual) {
+        return compare(evaluator, operator, &left, &right);
+    }
+    if is_bitwise(operator) {
+        return bitwise(evaluator, operator, left, right);
+    }
+
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => {
+            let result = match operator {
+                TokenKind::Plus => a + b,
+                TokenKind::Minus => a - b,
+                TokenKind::Star => a * b,
+                TokenKind::Slash => a / b,
+                TokenKind::Percent => a % b,
+                TokenKind::StarStar => a.powf(b),
+                other => unreachable!("parser produced unsupported arithmetic operator {:?}", other),
+            };
+            Ok(Value::Number(result))
+        }
+        (Value::BigInt(a), Value::BigInt(b)) => bigint_arithmetic(operator, &a, &b).map(Value::BigInt),
+        (left @ Value::String(_), right) | (left, right @ Value::String(_)) if *operator == TokenKind::Plus => {
+            Ok(Value::String(evaluator.stringify(left) + &evaluator.stringify(right)))
+        }
+        (Value::BigInt(_), Value::Number(_)) | (Value::Number(_), Value::BigInt(_)) => throw("TypeError", MIXED_BIGINT),
+        (left, right) => throw("TypeError", format!(
+            "Cannot apply '{}' to {} and {}",
+            symbol(operator),
+            evaluator.stringify(left),
+            evaluator.stringify(right)
+        )),
+    }
+}
+
+// The arithmetic behind a compound assignment such as `+=`.
+pub fn compound(assignment: &TokenKind) -> TokenKind {
+    match assignment {
+        TokenKind::PlusEqual | TokenKind::PlusPlus => TokenKind::Plus,
+        TokenKind::MinusEqual | TokenKind::MinusMinus => TokenKind::Minus,
+        TokenKind::StarEqual => TokenKind::Star,
+        TokenKind::SlashEqual => TokenKind::Slash,
+        TokenKind::PercentEqual => TokenKind::Percent,
+        TokenKind::StarStarEqual => TokenKind::StarStar,
+        TokenKind::AmpEqual => TokenKind::Amp,
+        TokenKind::PipeEqual => TokenKind::Pipe,
+        TokenKind::CaretEqual => TokenKind::Caret,
+        TokenKind::LessLessEqual => TokenKind::LessLess,
+        TokenKind::GreaterGreaterEqual => TokenKind::GreaterGreater,
+        TokenKind::GreaterGreaterGreaterEqual => TokenKind::GreaterGreaterGreater,
+        other => unreachable!("{:?} is not a compound assignment", other),
+    }
+}
+
+// `++` and `--` step numbers and BigInts by one.
+pub fn step(evaluator: &Evaluator, operator: &TokenKind, value: Value) -> EvalResult<Value> {
+    let one = match value {
+        Value::Number(_) => Value::Number(1.0),
+        Value::BigInt(_) => Value::BigInt(BigInt::from_i64(1)),
+        other => {
+            return throw("TypeError", format!(
+                "Cannot apply '{}' to {}",
+                if *operator == TokenKind::PlusPlus { "++" } else { "--" },
+                evaluator.stringify(other)
+            ));
+        }
+    };
+    binary(evaluator, &compound(operator), value, one)
+}
+
+fn is_bitwise(operator: &TokenKind) -> bool {
+    matches!(
+        operator,
+        TokenKind::Amp
+            | TokenKind::Pipe
+            | TokenKind::Caret
+            | TokenKind::LessLess
+            | TokenKind::GreaterGreater
+            | TokenKind::GreaterGreaterGreater
+    )
+}
+
+// Numbers work on 32 bits like JavaScript: whole numbers wrap into a signed
+// 32-bit integer (unsigned for `>>>`), and shift counts use their low five
+// bits. Fractions, NaN and infinities are rejected rather than truncated.
+// BigInts have no width limit, so they have no `>>>`.
+fn bitwise(evaluator: &Evaluator, operator: &TokenKind, left: Value, right: Value) -> EvalResult<Value> {
+    match (left, right) {
+        (Value::Number(a), Value::Number(b)) => {
+            let (a, b) = (to_int32(a, operator)?, to_int32(b, operator)?);
+            let shift = b as u32 & 31;
+            let result = match operator {
+                TokenKind::Amp => (a & b) as f64,
+                TokenKind::Pipe => (a | b) as f64,
+                TokenKind::Caret => (a ^ b) as f64,
+                TokenKind::LessLess => (a << shift) as f64,
+                TokenKind::GreaterGreater => (a >> shift) as f64,
+                _ => ((a as u32) >> shift) as f64,
+            };
+            Ok(Value::Number(result))
+        }
+        (Value::BigInt(a), Value::BigInt(b)) => {
+            let result = match operator {
+                TokenKind::Amp => a.bitand(&b),
+                TokenKind::Pipe => a.bitor(&b),
+                TokenKind::Caret => a.bitxor(&b),
+                TokenKind::GreaterGreaterGreater => {
+                    return throw("TypeError", "BigInts have no unsigned right shift, use >> instead");
+                }
+                // A negative count shifts the other way.
+                _ => {
+                    let left = (*operator == TokenKind::LessLess) != b.is_negative();
+                    let Some(bits) = b.abs().to_u64().filter(|bits| *bits <= MAX_BITS) else {
+                        if left {
+                            return throw("RangeError", TOO_BIG);
+                        }
+                        return Ok(Value::BigInt(if a.is_negative() { BigInt::from_i64(-1) } else { BigInt::zero() }));
+                    };
+                    if left { a.shl(bits as usize) } else { a.shr(bits as usize) }
+                }
+            };
+            Ok(Value::BigInt(result))
+        }
+        (Value::BigInt(_), Value::Number(_)) | (Value::Number(_), Value::BigInt(_)) => throw("TypeError", MIXED_BIGINT),
+        (left, right) => throw("TypeError", format!(
+            "Cannot apply '{}' to {} and {}",
+            symbol(operator),
+            evaluator.stringify(left),
+            evaluator.stringify(right)
+        )),
+    }
+}
+
+fn to_int32(number: f64, operator: &TokenKind) -> EvalResult<i32> {
+    if !number.is_finite() || number.fract() != 0.0 {
+        return throw("TypeError", format!(
+            "Bitwise '{}' needs whole numbers, got {}",
+            symbol(operator),
+            format_number(number)
+        ));
+    }
+    Ok(number.rem_euclid(4_294_967_296.0) as u32 as i32)
+}
+
+fn bigint_arithmetic(operator: &TokenKind, a: &BigInt, b: &BigInt) -> EvalResult<BigInt> {
+    match operator {
+        TokenKind::Plus => Ok(a.add(b)),
+        TokenKind::Minus => Ok(a.sub(b)),
+        TokenKind::Star => Ok(a.mul(b)),
+        TokenKind::Slash | TokenKind::Percent => match a.divrem(b) {
+            Some((quotient, _)) if *operator == TokenKind::Slash => Ok(quotient),
+            Some((_, remainder)) => Ok(remainder),
+            None => throw("RangeError", "Division by zero"),
+        },
+        TokenKind::StarStar => {
+            if b.is_negative() {
+                return throw("RangeError", "Exponent must be non-negative");
+            }
+            // 0, 1 and -1 only care whether the exponent is zero, even or odd;
+            // anything else gains about its own bit length per multiplication.
+            if a.bit_length() <= 1 {
+                let odd = !b.bitand(&BigInt::from_i64(1)).is_zero();
+                return Ok(a.pow(if b.is_zero() { 0 } else if odd { 1 } else { 2 }));
+            }
+            match b.to_u64().filter(|exponent| exponent.saturating_mul(a.bit_length()) <= MAX_BITS) {
+                Some(exponent) => Ok(a.pow(exponent)),
+                None => throw("RangeError", TOO_BIG),
+            }
+        }
+        other => unreachable!("parser produced unsupported arithmetic operator {:?}", other),
+    }
+}
+
+// Numbers, strings, BigInts and dates compare among themselves; a BigInt
+// and a number compare by value, as in JavaScript.
+fn compare(evaluator: &Evaluator, operator: &TokenKind, left: &Value, right: &Value) -> EvalResult<Value> {
+    let ordering = match (left, right) {
+        (Value::Number(a), Value::Number(b)) => a.partial_cmp(b),
+        (Value::String(a), Value::String(b)) => Some(a.cmp(b)),
+        (Value::BigInt(a), Value::BigInt(b)) => Some(a.cmp(b)),
+        (Value::BigInt(a), Value::Number(b)) => compare_mixed(a, *b),
+        (Value::Number(a), Value::BigInt(b)) => compare_mixed(b, *a).map(Ordering::reverse),
+        (Value::Date(a), Value::Date(b)) => Some(a.millis.cmp(&b.millis)),
+        _ => {
+            return throw("TypeError", format!(
+                "Cannot compare {} and {}",
+                evaluator.stringify(left.clone()),
+                evaluator.stringify(right.clone())
+            ));
+        }
+    };
+
+    // NaN is unordered, so every comparison with it is false.
+    let result = ordering.is_some_and(|ordering| match operator {
+        TokenKind::Greater => ordering == Ordering::Greater,
+        TokenKind::GreaterEqual => ordering != Ordering::Less,
+        TokenKind::Less => ordering == Ordering::Less,
+        _ => ordering != Ordering::Greater,
+    });
+    Ok(Value::Boolean(result))
+}
+
+fn compare_mixed(big: &BigInt, number: f64) -> Option<Ordering> {
+    match BigInt::from_f64(number) {
+        Some(whole) => Some(big.cmp(&whole)),
+        None if number.is_nan() => None,
+        None => big.to_f64().partial_cmp(&number),
+    }
+}
+
+fn symbol(operator: &TokenKind) -> &'static str {
+    match operator {
+        TokenKind::Plus => "+",
+        TokenKind::Minus => "-",
+        TokenKind::Star => "*",
+        TokenKind::Slash => "/",
+        TokenKind::Percent => "%",
+        TokenKind::StarStar => "**",
+        TokenKind::Amp => "&",
+        TokenKind::Pipe => "|",
+        TokenKind::Caret => "^",
+        TokenKind::Tilde => "~",
+        TokenKind::LessLess => "<<",
+        TokenKind::GreaterGreater => ">>",
+        TokenKind::GreaterGreaterGreater => ">>>",
+        _ => "?",
+    }
+}
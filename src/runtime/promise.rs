@@ -0,0 +1,60 @@
+use crate::runtime::evaluator::Evaluator;
+use crate::runtime::values::Value;
+use crate::utils::errors::EvalResult;
+
+use std::cell::RefCell;
+use std::fmt;
+use std::rc::Rc;
+
+pub type PromiseRef = Rc<RefCell<Promise>>;
+
+// Ok for a fulfilled promise, Err for a rejected one.
+pub type Settled = Result<Value, Value>;
+
+pub type Reaction = Box<dyn FnOnce(&mut Evaluator, Settled) -> EvalResult<()>>;
+
+#[derive(Debug, Clone)]
+pub enum PromiseState {
+    Pending,
+    Fulfilled(Value),
+    Rejected(Value),
+}
+
+pub struct Promise {
+    pub state: PromiseState,
+    // Callbacks waiting for the promise to settle; each runs as a microtask.
+    pub(crate) reactions: Vec<Reaction>,
+    // Whether anything has observed a rejection, via `await` or a reaction.
+    pub handled: bool,
+}
+
+impl Promise {
+    pub fn new() -> PromiseRef {
+        Rc::new(RefCell::new(Self {
+            state: PromiseState::Pending,
+            reactions: Vec::new(),
+            handled: false,
+        }))
+    }
+
+    pub fn is_pending(&self) -> bool {
+        matches!(self.state, PromiseState::Pending)
+    }
+
+    pub fn settled(&self) -> Option<Settled> {
+        match &self.state {
+            PromiseState::Pending => None,
+            PromiseState::Fulfilled(value) => Some(Ok(value.clone())),
+            PromiseState::Rejected(reason) => Some(Err(reason.clone())),
+        }
+    }
+}
+
+impl fmt::Debug for Promise {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("Promise")
+            .field("state", &self.state)
+            .field("reactions", &self.reactions.len())
+            .finish()
+    }
+}
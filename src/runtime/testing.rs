@@ -0,0 +1,28 @@
+// Helpers the test modules share.
+
+use crate::runtime::evaluator::{parse_source, Backend, Evaluator};
+use crate::utils::errors::EvalResult;
+
+// Runs `source` on `evaluator` and gives back the result along with what it
+// logged, one line per `log`.
+pub fn capture(evaluator: &mut Evaluator, source: &str) -> (EvalResult<()>, String) {
+    evaluator.capture_output();
+    let result = evaluator.evaluate(parse_source(source));
+    (result, evaluator.take_output().join("\n"))
+}
+
+// Runs `source` on every backend, expecting it to finish without leaving a
+// rejection unhandled, and gives back what it logged, which has to be the
+// same each time.
+pub fn logged(source: &str) -> String {
+    let mut outputs = Vec::new();
+    for backend in Backend::ALL {
+        let mut evaluator = Evaluator::with_backend(backend);
+        let (result, output) = capture(&mut evaluator, source);
+        assert!(result.is_ok(), "{:?} failed: {:?}", backend, result);
+        assert!(evaluator.take_unhandled_rejections().is_empty(), "{:?} left rejections unhandled", backend);
+        outputs.push(output);
+    }
+    assert_eq!(outputs[0], outputs[1], "backends disagree");
+    outputs.remove(0)
+}
@@ -1,11 +1,237 @@
-use crate::parser::ast::Stmt;
+use crate::parser::ast::Function;
+use crate::runtime::bigint::BigInt;
+use crate::runtime::bytecode::CompiledFunction;
+use crate::runtime::date::DateTime;
+use crate::runtime::environment::Env;
+use crate::runtime::evaluator::Evaluator;
+use crate::runtime::modules::Module;
+use crate::runtime::promise::PromiseRef;
+use crate::utils::errors::EvalResult;
+
+use std::cell::{OnceCell, RefCell};
+use std::collections::HashMap;
+use std::fmt;
+use std::rc::Rc;
 
 #[derive(Clone, Debug)]
 pub enum Value {
     String(String),
+    Number(f64),
+    BigInt(BigInt),
+    Boolean(bool),
+    Array(Rc<RefCell<Vec<Value>>>),
+    Object(Rc<RefCell<Object>>),
     Function {
-        params: Vec<String>,
-        body: Vec<Stmt>,
+        function: Rc<Function>,
+        closure: Env,
+    },
+    // A function compiled for the bytecode VM.
+    Closure {
+        function: Rc<CompiledFunction>,
+        closure: Env,
     },
+    NativeFunction(Rc<NativeFunction>),
+    Module(Rc<Module>),
+    Promise(PromiseRef),
+    Date(DateTime),
+    Enum(Rc<Enum>),
+    EnumMember(Rc<EnumMember>),
+    Symbol(Rc<Symbol>),
     Null,
+    Undefined,
+}
+
+impl Value {
+    pub fn array(items: Vec<Value>) -> Self {
+        Value::Array(Rc::new(RefCell::new(items)))
+    }
+
+    pub fn object(object: Object) -> Self {
+        Value::Object(Rc::new(RefCell::new(object)))
+    }
+
+    pub fn native(name: &str, func: impl Fn(&mut Evaluator, Vec<Value>) -> EvalResult<Value> + 'static) -> Self {
+        Self::native_with_properties(name, func, Vec::new())
+    }
+
+    // A callable that also carries static members, like `Promise.all`.
+    pub fn native_with_properties(
+        name: &str,
+        func: impl Fn(&mut Evaluator, Vec<Value>) -> EvalResult<Value> + 'static,
+        properties: Vec<(&str, Value)>,
+    ) -> Self {
+        let mut object = Object::new();
+        for (key, value) in properties {
+            object.set(key, value);
+        }
+        Value::NativeFunction(Rc::new(NativeFunction {
+            name: name.to_string(),
+            func: Box::new(func),
+            properties: object,
+        }))
+    }
+
+    pub fn is_callable(&self) -> bool {
+        matches!(self, Value::Function { .. } | Value::Closure { .. } | Value::NativeFunction(_))
+    }
+
+    // `false`, `null`, `undefined`, `0`, `NaN`, `""` and `0n` are falsy; every
+    // other value, including empty arrays and objects, is truthy.
+    pub fn is_truthy(&self) -> bool {
+        match self {
+            Value::Boolean(value) => *value,
+            Value::Null | Value::Undefined => false,
+            Value::Number(number) => *number != 0.0 && !number.is_nan(),
+            Value::String(text) => !text.is_empty(),
+            Value::BigInt(big) => !big.is_zero(),
+            _ => true,
+        }
+    }
+
+    // `==` never converts between types: strings, numbers, BigInts and booleans compare
+    // by value, dates by instant, and everything else by identity.
+    pub fn equals(&self, other: &Value) -> bool {
+        match (self, other) {
+            (Value::String(a), Value::String(b)) => a == b,
+            (Value::Number(a), Value::Number(b)) => a == b,
+            (Value::BigInt(a), Value::BigInt(b)) => a == b,
+            (Value::Boolean(a), Value::Boolean(b)) => a == b,
+            (Value::Null, Value::Null) | (Value::Undefined, Value::Undefined) => true,
+            (Value::Date(a), Value::Date(b)) => a.millis == b.millis,
+            (Value::Array(a), Value::Array(b)) => Rc::ptr_eq(a, b),
+            (Value::Object(a), Value::Object(b)) => Rc::ptr_eq(a, b),
+            (Value::NativeFunction(a), Value::NativeFunction(b)) => Rc::ptr_eq(a, b),
+            (Value::Module(a), Value::Module(b)) => Rc::ptr_eq(a, b),
+            (Value::Promise(a), Value::Promise(b)) => Rc::ptr_eq(a, b),
+            (Value::Enum(a), Value::Enum(b)) => Rc::ptr_eq(a, b),
+            (Value::EnumMember(a), Value::EnumMember(b)) => Rc::ptr_eq(a, b),
+            (Value::Symbol(a), Value::Symbol(b)) => Rc::ptr_eq(a, b),
+            (
+                Value::Function { function: a, closure: a_env },
+                Value::Function { function: b, closure: b_env },
+            ) => Rc::ptr_eq(a, b) && Rc::ptr_eq(a_env, b_env),
+            (
+                Value::Closure { function: a, closure: a_env },
+                Value::Closure { function: b, closure: b_env },
+            ) => Rc::ptr_eq(a, b) && Rc::ptr_eq(a_env, b_env),
+            _ => false,
+        }
+    }
+}
+
+// Keeps keys in insertion order so printing an object is deterministic.
+// Symbol-keyed properties live apart from string keys, so they never show
+// up in `keys()`, printing or JSON.
+#[derive(Clone, Debug, Default)]
+pub struct Object {
+    keys: Vec<String>,
+    values: HashMap<String, Value>,
+    symbols: Vec<(Rc<Symbol>, Value)>,
+}
+
+impl Object {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn get(&self, key: &str) -> Option<Value> {
+        self.values.get(key).cloned()
+    }
+
+    pub fn set(&mut self, key: &str, value: Value) {
+        if self.values.insert(key.to_string(), value).is_none() {
+            self.keys.push(key.to_string());
+        }
+    }
+
+    pub fn entries(&self) -> impl Iterator<Item = (&String, &Value)> {
+        self.keys.iter().map(|key| (key, &self.values[key]))
+    }
+
+    // Copies every property of `other`, symbols included, over this object's.
+    pub fn extend(&mut self, other: &Object) {
+        for (key, value) in other.entries() {
+            self.set(key, value.clone());
+        }
+        for (symbol, value) in &other.symbols {
+            self.set_symbol(symbol, value.clone());
+        }
+    }
+
+    pub fn get_symbol(&self, symbol: &Rc<Symbol>) -> Option<Value> {
+        self.symbols
+            .iter()
+            .find(|(key, _)| Rc::ptr_eq(key, symbol))
+            .map(|(_, value)| value.clone())
+    }
+
+    pub fn set_symbol(&mut self, symbol: &Rc<Symbol>, value: Value) {
+        match self.symbols.iter_mut().find(|(key, _)| Rc::ptr_eq(key, symbol)) {
+            Some((_, existing)) => *existing = value,
+            None => self.symbols.push((Rc::clone(symbol), value)),
+        }
+    }
+}
+
+// Every call to `Symbol()` makes a new symbol; the description is only a label.
+#[derive(Debug)]
+pub struct Symbol {
+    pub description: Option<String>,
+}
+
+#[derive(Debug)]
+pub struct Enum {
+    pub name: String,
+    pub members: Vec<Rc<EnumMember>>,
+}
+
+impl Enum {
+    // Members keep their enum alive, so one returned from a function still
+    // knows its name and siblings. The owner is filled in once the enum exists.
+    pub fn new(name: &str, members: Vec<(String, Value)>) -> Rc<Self> {
+        let namespace = Rc::new(Self {
+            name: name.to_string(),
+            members: members
+                .into_iter()
+                .map(|(member, value)| Rc::new(EnumMember { owner: OnceCell::new(), name: member, value }))
+                .collect(),
+        });
+        for member in &namespace.members {
+            let _ = member.owner.set(Rc::clone(&namespace));
+        }
+        namespace
+    }
+
+    pub fn member(&self, name: &str) -> Option<Rc<EnumMember>> {
+        self.members.iter().find(|member| member.name == name).cloned()
+    }
+}
+
+// Each member is a distinct value: two members are equal only if they are the same member.
+#[derive(Debug)]
+pub struct EnumMember {
+    owner: OnceCell<Rc<Enum>>,
+    pub name: String,
+    // The explicit value, or the member's index when none was given.
+    pub value: Value,
+}
+
+impl EnumMember {
+    pub fn owner(&self) -> &Rc<Enum> {
+        self.owner.get().expect("enum members are only created by Enum::new")
+    }
+}
+
+pub type NativeFn = dyn Fn(&mut Evaluator, Vec<Value>) -> EvalResult<Value>;
+
+pub struct NativeFunction {
+    pub name: String,
+    pub func: Box<NativeFn>,
+    pub properties: Object,
+}
+
+impl fmt::Debug for NativeFunction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "<native {}>", self.name)
+    }
 }
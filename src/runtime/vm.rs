@@ -0,0 +1,744 @@
+use crate::parser::tokens::TokenKind;
+use crate::runtime::builtins::promise::to_promise;
+use crate::runtime::bytecode::{CompiledFunction, Constant, Op, BINARY_OPERATORS, UNARY_OPERATORS};
+use crate::runtime::environment::{Env, Environment};
+use crate::runtime::evaluator::{arrange_arguments, lookup, Evaluator};
+use crate::runtime::operators;
+use crate::runtime::promise::{Promise, PromiseRef, Settled};
+use crate::runtime::values::{Enum, Object, Value};
+use crate::utils::errors::{throw, Control, EvalResult};
+
+use std::rc::Rc;
+
+// Runs a compiled script in `env` and gives back the value it returns.
+pub fn run(evaluator: &mut Evaluator, script: Rc<CompiledFunction>, env: &Env) -> EvalResult<Value> {
+    let mut vm = Vm::new();
+    vm.frames.push(Frame::new(script, Rc::clone(env), 0));
+    vm.execute(evaluator)
+}
+
+// Calls a compiled closure from outside the VM, such as from a promise
+// reaction or `Evaluator::call_function`.
+pub fn call(
+    evaluator: &mut Evaluator,
+    function: Rc<CompiledFunction>,
+    closure: &Env,
+    args: Vec<Value>,
+    keywords: Vec<(String, Value)>,
+    exact: bool,
+) -> EvalResult<Value> {
+    let mut vm = Vm::new();
+    vm.enter(function, closure, args, keywords, exact)?;
+    vm.execute(evaluator)
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum HandlerKind {
+    // Takes thrown errors.
+    Catch,
+    // Runs for anything unwinding through it, then lets it carry on.
+    Finally,
+}
+
+struct Handler {
+    kind: HandlerKind,
+    target: usize,
+    stack_len: usize,
+    pending_len: usize,
+}
+
+struct Frame {
+    function: Rc<CompiledFunction>,
+    ip: usize,
+    env: Env,
+    // Where this call's values start on the stack.
+    base: usize,
+    handlers: Vec<Handler>,
+    // What was unwinding when a `finally` block was entered; `EndFinally` resumes it.
+    pending: Vec<Control>,
+    // The promise an async call handed its caller when it first suspended,
+    // settled once the call finishes.
+    promise: Option<PromiseRef>,
+}
+
+impl Frame {
+    fn new(function: Rc<CompiledFunction>, env: Env, base: usize) -> Self {
+        Self {
+            function,
+            ip: 0,
+            env,
+            base,
+            handlers: Vec::new(),
+            pending: Vec::new(),
+            promise: None,
+        }
+    }
+
+    // Async calls settle the promise they handed out, or a new one when they
+    // finish without ever suspending.
+    fn settle(self, evaluator: &mut Evaluator, outcome: Settled) -> Value {
+        let promise = self.promise.unwrap_or_else(Promise::new);
+        match outcome {
+            Ok(value) => evaluator.event_loop().resolve(&promise, value),
+            Err(reason) => evaluator.event_loop().reject(&promise, reason),
+        }
+        Value::Promise(promise)
+    }
+}
+
+// A value stack shared by every call, and one frame per call in progress.
+// Calls between compiled functions push a frame rather than recursing, so
+// deep recursion in a script doesn't grow the native stack. An async call
+// that awaits moves its frame and values into a VM of their own, which the
+// event loop resumes once the awaited promise settles.
+struct Vm {
+    stack: Vec<Value>,
+    frames: Vec<Frame>,
+}
+
+impl Vm {
+    fn new() -> Self {
+        Self {
+            stack: Vec::new(),
+            frames: Vec::new(),
+        }
+    }
+
+    // Starts a call. Parameters go on the stack for the function's prologue to
+    // bind: the rest array first, then the positional values, first one on top.
+    fn enter(
+        &mut self,
+        function: Rc<CompiledFunction>,
+        closure: &Env,
+        args: Vec<Value>,
+        keywords: Vec<(String, Value)>,
+        exact: bool,
+    ) -> EvalResult<()> {
+        let (positional, extra) = arrange_arguments(&function.params, args, keywords, exact)?;
+        let base = self.stack.len();
+        if function.params.rest.is_some() {
+            self.stack.push(Value::array(extra));
+        }
+        self.stack.extend(positional.into_iter().rev());
+        let env = Environment::child(closure, function.locals);
+        self.frames.push(Frame::new(function, env, base));
+        Ok(())
+    }
+
+    fn execute(&mut self, evaluator: &mut Evaluator) -> EvalResult<Value> {
+        loop {
+            let control = match self.run(evaluator) {
+                Ok(value) => return Ok(value),
+                Err(control) => control,
+            };
+            if let Some(value) = self.unwind(evaluator, control)? {
+                return Ok(value);
+            }
+        }
+    }
+
+    // Carries on a suspended async call with the outcome of what it awaited.
+    fn resume(mut self, evaluator: &mut Evaluator, outcome: Settled) -> EvalResult<()> {
+        match outcome {
+            Ok(value) => self.stack.push(value),
+            Err(reason) => {
+                if self.unwind(evaluator, Control::Throw(reason))?.is_some() {
+                    return Ok(());
+                }
+            }
+        }
+        self.execute(evaluator).map(|_| ())
+    }
+
+    // Looks for a handler, innermost first, ending calls that have none. Gives
+    // back a value when the outermost call ends that way, which only an async
+    // function does: its error becomes a rejected promise.
+    fn unwind(&mut self, evaluator: &mut Evaluator, control: Control) -> EvalResult<Option<Value>> {
+        let mut control = control;
+        loop {
+            let frame = self.frames.last_mut().expect("unwinding past the outermost call");
+            while let Some(handler) = frame.handlers.pop() {
+                let catches = matches!(control, Control::Throw(_));
+                if handler.kind == HandlerKind::Catch && !catches {
+                    continue;
+                }
+
+                self.stack.truncate(handler.stack_len);
+                frame.pending.truncate(handler.pending_len);
+                frame.ip = handler.target;
+                match (handler.kind, control) {
+                    (HandlerKind::Catch, Control::Throw(error)) => self.stack.push(error),
+                    (_, control) => frame.pending.push(control),
+                }
+                return Ok(None);
+            }
+
+            let frame = self.frames.pop().expect("unwinding past the outermost call");
+            self.stack.truncate(frame.base);
+            let result = match control {
+                Control::Throw(reason) if frame.function.is_async && frame.ip > frame.function.body_start as usize => {
+                    frame.settle(evaluator, Err(reason))
+                }
+                other if self.frames.is_empty() => return Err(other),
+                other => {
+                    control = other;
+                    continue;
+                }
+            };
+
+            if self.frames.is_empty() {
+                return Ok(Some(result));
+            }
+            self.stack.push(result);
+            return Ok(None);
+        }
+    }
+
+    fn pop(&mut self) -> Value {
+        self.stack.pop().expect("VM stack underflow")
+    }
+
+    fn peek(&self) -> &Value {
+        self.stack.last().expect("VM stack underflow")
+    }
+
+    fn pop_many(&mut self, count: u32) -> Vec<Value> {
+        let start = self.stack.len() - count as usize;
+        self.stack.split_off(start)
+    }
+
+    fn jump(&mut self, target: u32) {
+        self.frame().ip = target as usize;
+    }
+
+    fn frame(&mut self) -> &mut Frame {
+        self.frames.last_mut().expect("the VM always runs inside a call")
+    }
+
+    fn env(&self) -> Env {
+        Rc::clone(&self.frames.last().expect("the VM always runs inside a call").env)
+    }
+
+    fn function(&self) -> Rc<CompiledFunction> {
+        Rc::clone(&self.frames.last().expect("the VM always runs inside a call").function)
+    }
+
+    // Runs instructions until the outermost call returns or something throws.
+    fn run(&mut self, evaluator: &mut Evaluator) -> EvalResult<Value> {
+        loop {
+            let frame = self.frame();
+            let op = frame.function.chunk.code[frame.ip];
+            frame.ip += 1;
+
+            match op {
+                Op::Constant(index) => {
+                    let value = match &self.frames.last().unwrap().function.chunk.constants[index as usize] {
+                        Constant::Number(number) => Value::Number(*number),
+                        Constant::String(text) => Value::String(text.clone()),
+                        other => unreachable!("constant {:?} is not a value", other),
+                    };
+                    self.stack.push(value);
+                }
+                Op::Undefined => self.stack.push(Value::Undefined),
+                Op::Null => self.stack.push(Value::Null),
+                Op::True => self.stack.push(Value::Boolean(true)),
+                Op::False => self.stack.push(Value::Boolean(false)),
+                Op::Pop => {
+                    self.pop();
+                }
+                Op::Dup => self.stack.push(self.peek().clone()),
+                Op::Dup2 => {
+                    let start = self.stack.len() - 2;
+                    self.stack.extend_from_within(start..);
+                }
+                Op::Nip(count) => {
+                    let top = self.pop();
+                    self.pop_many(count);
+                    self.stack.push(top);
+                }
+
+                Op::GetVar(variable) => {
+                    let function = self.function();
+                    let value = lookup(function.chunk.variable(variable), &self.env())?;
+                    self.stack.push(value);
+                }
+                Op::SetVar(variable) => {
+                    let value = self.pop();
+                    let function = self.function();
+                    self.env().borrow_mut().assign(function.chunk.variable(variable), value);
+                }
+                Op::UpdateVar { variable, increment, prefix } => {
+                    let function = self.function();
+                    let variable = function.chunk.variable(variable);
+                    let env = self.env();
+                    let current = lookup(variable, &env)?;
+                    let updated = operators::step(evaluator, step_operator(increment), current.clone())?;
+                    env.borrow_mut().assign(variable, updated.clone());
+                    self.stack.push(if prefix { updated } else { current });
+                }
+
+                Op::GetMember(property) => {
+                    let object = self.pop();
+                    let function = self.function();
+                    let value = evaluator.member(object, function.chunk.name(property))?;
+                    self.stack.push(value);
+                }
+                Op::GetIndex => {
+                    let index = self.pop();
+                    let object = self.pop();
+                    let value = evaluator.index(object, index)?;
+                    self.stack.push(value);
+                }
+                Op::GetProperty => {
+                    let key = self.pop();
+                    let object = self.pop();
+                    let value = evaluator.get_property(object, key)?;
+                    self.stack.push(value);
+                }
+                Op::SetProperty => {
+                    let value = self.pop();
+                    let key = self.pop();
+                    let object = self.pop();
+                    evaluator.set_property(object, key, value.clone())?;
+                    self.stack.push(value);
+                }
+                Op::UpdateProperty { increment, prefix } => {
+                    let key = self.pop();
+                    let object = self.pop();
+                    let current = evaluator.get_property(object.clone(), key.clone())?;
+                    let updated = operators::step(evaluator, step_operator(increment), current.clone())?;
+                    evaluator.set_property(object, key, updated.clone())?;
+                    self.stack.push(if prefix { updated } else { current });
+                }
+
+                Op::Binary(operator) => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    let value = operators::binary(evaluator, &BINARY_OPERATORS[operator as usize], left, right)?;
+                    self.stack.push(value);
+                }
+                Op::Unary(operator) => {
+                    let operand = self.pop();
+                    let value = operators::unary(evaluator, &UNARY_OPERATORS[operator as usize], operand)?;
+                    self.stack.push(value);
+                }
+                Op::Equal => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.stack.push(Value::Boolean(left.equals(&right)));
+                }
+                Op::NotEqual => {
+                    let right = self.pop();
+                    let left = self.pop();
+                    self.stack.push(Value::Boolean(!left.equals(&right)));
+                }
+
+                Op::Jump(target) => self.jump(target),
+                Op::JumpIfFalse(target) => {
+                    if !self.pop().is_truthy() {
+                        self.jump(target);
+                    }
+                }
+                Op::JumpIfFalsy(target) => {
+                    if self.peek().is_truthy() {
+                        self.pop();
+                    } else {
+                        self.jump(target);
+                    }
+                }
+                Op::JumpIfTruthy(target) => {
+                    if self.peek().is_truthy() {
+                        self.jump(target);
+                    } else {
+                        self.pop();
+                    }
+                }
+                Op::JumpIfNotNullish(target) => {
+                    if matches!(self.peek(), Value::Null | Value::Undefined) {
+                        self.pop();
+                    } else {
+                        self.jump(target);
+                    }
+                }
+                Op::JumpIfNotUndefined(target) => {
+                    if !matches!(self.peek(), Value::Undefined) {
+                        self.jump(target);
+                    }
+                }
+                Op::JumpIfNullish(target) => {
+                    if matches!(self.peek(), Value::Null | Value::Undefined) {
+                        self.pop();
+                        self.stack.push(Value::Undefined);
+                        self.jump(target);
+                    }
+                }
+
+                Op::Array(count) => {
+                    let items = self.pop_many(count);
+                    self.stack.push(Value::array(items));
+                }
+                Op::Push => {
+                    let value = self.pop();
+                    if let Value::Array(items) = self.peek() {
+                        items.borrow_mut().push(value);
+                    }
+                }
+                Op::SpreadArray => {
+                    let iterable = self.pop();
+                    let values = evaluator.iterate(iterable)?;
+                    if let Value::Array(items) = self.peek() {
+                        items.borrow_mut().extend(values);
+                    }
+                }
+                Op::Object => self.stack.push(Value::object(Object::new())),
+                Op::InitProperty => {
+                    let value = self.pop();
+                    let key = self.pop();
+                    if let Value::Object(object) = self.peek() {
+                        evaluator.define_property(&mut object.borrow_mut(), key, value)?;
+                    }
+                }
+                Op::SpreadObject => {
+                    let value = self.pop();
+                    if let Value::Object(object) = self.peek() {
+                        let object = Rc::clone(object);
+                        evaluator.spread_into(&mut object.borrow_mut(), value)?;
+                    }
+                }
+                Op::Template(count) => {
+                    let mut text = String::new();
+                    for part in self.pop_many(count) {
+                        text.push_str(&evaluator.display(part)?);
+                    }
+                    self.stack.push(Value::String(text));
+                }
+                Op::Closure(index) => {
+                    let function = match &self.function().chunk.constants[index as usize] {
+                        Constant::Function(function) => Rc::clone(function),
+                        other => unreachable!("constant {:?} is not a function", other),
+                    };
+                    let closure = self.env();
+                    self.stack.push(Value::Closure { function, closure });
+                }
+
+                Op::Call(count) => {
+                    let args = self.pop_many(count);
+                    let callee = self.pop();
+                    self.call_value(evaluator, callee, args, Vec::new())?;
+                }
+                Op::CallWith(names) => {
+                    let function = self.function();
+                    let names = function.chunk.names(names);
+                    let values = self.pop_many(names.len() as u32);
+                    let keywords = names.iter().cloned().zip(values).collect();
+                    let args = match self.pop() {
+                        Value::Array(items) => items.borrow().clone(),
+                        other => unreachable!("positional arguments are gathered in an array, got {:?}", other),
+                    };
+                    let callee = self.pop();
+                    self.call_value(evaluator, callee, args, keywords)?;
+                }
+                Op::Return => {
+                    let value = self.pop();
+                    let frame = self.frames.pop().expect("returning from outside a call");
+                    self.stack.truncate(frame.base);
+
+                    // Functions that await hand their caller a promise of the result.
+                    let value = if frame.function.is_async { frame.settle(evaluator, Ok(value)) } else { value };
+
+                    if self.frames.is_empty() {
+                        return Ok(value);
+                    }
+                    self.stack.push(value);
+                }
+                Op::ReturnFromScript => {
+                    let value = self.pop();
+                    return Err(Control::Return(value));
+                }
+                Op::Await => {
+                    let value = self.pop();
+                    if !self.function().is_async {
+                        // A script or REPL entry has no caller to get on with, so it waits in place.
+                        let value = evaluator.await_value(value)?;
+                        self.stack.push(value);
+                        continue;
+                    }
+                    let promise = self.suspend(evaluator, value);
+                    if self.frames.is_empty() {
+                        return Ok(promise);
+                    }
+                    self.stack.push(promise);
+                }
+
+                Op::Iterate => {
+                    let iterable = self.pop();
+                    let items = evaluator.iterate(iterable)?;
+                    self.stack.push(Value::array(items));
+                    self.stack.push(Value::Number(0.0));
+                }
+                Op::ForNext(done) => {
+                    let length = self.stack.len();
+                    let (Value::Array(items), Value::Number(position)) = (&self.stack[length - 2], &self.stack[length - 1]) else {
+                        unreachable!("a loop keeps its items and position on the stack");
+                    };
+                    let position = *position;
+                    let item = items.borrow().get(position as usize).cloned();
+                    match item {
+                        Some(item) => {
+                            self.stack[length - 1] = Value::Number(position + 1.0);
+                            self.stack.push(item);
+                        }
+                        None => {
+                            self.stack.truncate(length - 2);
+                            self.jump(done);
+                        }
+                    }
+                }
+
+                Op::PushCatch(target) => self.push_handler(HandlerKind::Catch, target),
+                Op::PushFinally(target) => self.push_handler(HandlerKind::Finally, target),
+                Op::PopHandlers(count) => {
+                    let handlers = &mut self.frame().handlers;
+                    handlers.truncate(handlers.len() - count as usize);
+                }
+                Op::ErrorType(error_type) => {
+                    let error = self.pop();
+                    let function = self.function();
+                    let matches = evaluator.error_has_type(&error, function.chunk.name(error_type));
+                    self.stack.push(Value::Boolean(matches));
+                }
+                Op::Throw => {
+                    let error = self.pop();
+                    return Err(Control::Throw(error));
+                }
+                Op::EndFinally => {
+                    let control = self.frame().pending.pop().expect("a finally block ended with nothing to resume");
+                    return Err(control);
+                }
+
+                Op::DestructureObject => match self.pop() {
+                    Value::Object(object) => {
+                        let copy = object.borrow().clone();
+                        self.stack.push(Value::object(copy));
+                    }
+                    other => {
+                        return throw("TypeError", format!(
+                            "Cannot destructure {} as it is not an object",
+                            evaluator.stringify(other)
+                        ));
+                    }
+                },
+                Op::OwnProperty(key) => {
+                    let Value::Object(object) = self.peek() else {
+                        unreachable!("object patterns destructure a copied object");
+                    };
+                    let function = self.function();
+                    let value = object.borrow().get(function.chunk.name(key)).unwrap_or(Value::Undefined);
+                    self.stack.push(value);
+                }
+                Op::ObjectRest(taken) => {
+                    let Value::Object(object) = self.peek() else {
+                        unreachable!("object patterns destructure a copied object");
+                    };
+                    let function = self.function();
+                    let taken = function.chunk.names(taken);
+                    let mut remaining = Object::new();
+                    for (key, value) in object.borrow().entries() {
+                        if !taken.contains(key) {
+                            remaining.set(key, value.clone());
+                        }
+                    }
+                    self.stack.push(Value::object(remaining));
+                }
+                Op::DestructureArray => match self.pop() {
+                    value @ (Value::Array(_) | Value::String(_) | Value::Object(_)) => {
+                        let items = evaluator.iterate(value)?;
+                        self.stack.push(Value::array(items));
+                    }
+                    other => {
+                        return throw("TypeError", format!(
+                            "Cannot destructure {} as it is not iterable",
+                            evaluator.stringify(other)
+                        ));
+                    }
+                },
+                Op::Element(index) => {
+                    let Value::Array(items) = self.peek() else {
+                        unreachable!("array patterns destructure a copied array");
+                    };
+                    let item = items.borrow().get(index as usize).cloned().unwrap_or(Value::Undefined);
+                    self.stack.push(item);
+                }
+                Op::ElementsFrom(start) => {
+                    let Value::Array(items) = self.peek() else {
+                        unreachable!("array patterns destructure a copied array");
+                    };
+                    let rest = items.borrow().iter().skip(start as usize).cloned().collect();
+                    self.stack.push(Value::array(rest));
+                }
+
+                Op::Log => {
+                    let value = self.pop();
+                    let text = evaluator.display(value)?;
+                    evaluator.write_line(text);
+                }
+                Op::Import(target) => {
+                    let spec = self.pop();
+                    let function = self.function();
+                    let Constant::Import(target) = &function.chunk.constants[target as usize] else {
+                        unreachable!("Import refers to an import target");
+                    };
+                    evaluator.import(target, spec, &self.env())?;
+                }
+                Op::Share(names) => {
+                    let function = self.function();
+                    evaluator.share(function.chunk.names(names));
+                }
+                Op::Enum(definition) => {
+                    let function = self.function();
+                    let Constant::Enum { name, members } = &function.chunk.constants[definition as usize] else {
+                        unreachable!("Enum refers to an enum definition");
+                    };
+                    let values = self.pop_many(members.len() as u32);
+                    let members = members.iter().cloned().zip(values).collect();
+                    self.stack.push(Value::Enum(Enum::new(name, members)));
+                }
+                Op::Match(table) => {
+                    let function = self.function();
+                    let Constant::Match { cases, otherwise, end } = &function.chunk.constants[table as usize] else {
+                        unreachable!("Match refers to a match table");
+                    };
+                    let patterns = self.pop_many(cases.iter().map(|(count, _)| count).sum());
+                    let subject = self.pop();
+                    if otherwise.is_none() {
+                        evaluator.check_exhaustive(&subject, &patterns)?;
+                    }
+
+                    let mut patterns = patterns.iter();
+                    let mut target = otherwise.unwrap_or(*end);
+                    for (count, start) in cases {
+                        let mut arm = patterns.by_ref().take(*count as usize);
+                        if arm.any(|pattern| pattern.equals(&subject)) {
+                            target = *start;
+                            break;
+                        }
+                    }
+                    self.jump(target);
+                }
+            }
+        }
+    }
+
+    // Moves the running async call into a VM of its own that resumes once
+    // `awaited` settles, and gives back the promise its caller gets. A call
+    // that was already resumed once has no caller waiting any more.
+    fn suspend(&mut self, evaluator: &mut Evaluator, awaited: Value) -> Value {
+        let mut frame = self.frames.pop().expect("awaiting outside a call");
+        let stack = self.stack.split_off(frame.base);
+        for handler in &mut frame.handlers {
+            handler.stack_len -= frame.base;
+        }
+        frame.base = 0;
+        let promise = Rc::clone(frame.promise.get_or_insert_with(Promise::new));
+
+        let awaited = to_promise(evaluator, awaited);
+        let coroutine = Vm { stack, frames: vec![frame] };
+        evaluator.event_loop().subscribe(&awaited, Box::new(move |evaluator, outcome| {
+            coroutine.resume(evaluator, outcome)
+        }));
+        Value::Promise(promise)
+    }
+
+    fn push_handler(&mut self, kind: HandlerKind, target: u32) {
+        let stack_len = self.stack.len();
+        let frame = self.frame();
+        let pending_len = frame.pending.len();
+        frame.handlers.push(Handler {
+            kind,
+            target: target as usize,
+            stack_len,
+            pending_len,
+        });
+    }
+
+    // Compiled functions get a new frame on this VM; anything else is called directly.
+    fn call_value(
+        &mut self,
+        evaluator: &mut Evaluator,
+        callee: Value,
+        args: Vec<Value>,
+        keywords: Vec<(String, Value)>,
+    ) -> EvalResult<()> {
+        match callee {
+            Value::Closure { function, closure } => self.enter(function, &closure, args, keywords, true),
+            other => {
+                let value = evaluator.invoke(other, args, keywords, true)?;
+                self.stack.push(value);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn step_operator(increment: bool) -> &'static TokenKind {
+    if increment { &TokenKind::PlusPlus } else { &TokenKind::MinusMinus }
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::runtime::evaluator::{Backend, Evaluator};
+    use crate::runtime::testing::{capture, logged};
+
+    #[test]
+    fn finally_blocks_run_on_every_way_out_of_a_try() {
+        let output = logged(r#"
+            steps = []
+            init early():
+                try:
+                    try:
+                        return "returned"
+                    finally:
+                        steps = [...steps, "inner"]
+                catch (err):
+                    steps = [...steps, "never"]
+                finally:
+                    steps = [...steps, "outer"]
+            init rethrown():
+                try:
+                    throw {type: "ValueError", message: "bad"}
+                catch (TypeError err):
+                    steps = [...steps, "never"]
+                finally:
+                    steps = [...steps, "cleanup"]
+            init overridden():
+                try:
+                    throw "lost"
+                finally:
+                    return "finally wins"
+            init caught():
+                try:
+                    rethrown()
+                catch (ValueError err):
+                    return err.message
+            log([early(), caught(), overridden(), steps])
+        "#);
+
+        assert_eq!(output, r#"["returned", "bad", "finally wins", ["inner", "outer", "cleanup"]]"#);
+    }
+
+    #[test]
+    fn recursion_depth_is_not_limited_by_the_native_stack() {
+        let mut evaluator = Evaluator::with_backend(Backend::Bytecode);
+        let (result, output) = capture(&mut evaluator, r#"
+            init count(n):
+                match n:
+                    0:
+                        return 0
+                return 1 + count(n - 1)
+            log(count(100000))
+        "#);
+
+        assert!(result.is_ok(), "{:?}", result);
+        assert_eq!(output, "100000");
+    }
+}
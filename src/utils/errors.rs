@@ -0,0 +1,26 @@
+use crate::runtime::values::{Object, Value};
+
+// Anything that unwinds out of a statement before it completes normally.
+#[derive(Debug, Clone)]
+pub enum Control {
+    Throw(Value),
+    // Unwinds to the nearest function call, which evaluates to the value.
+    Return(Value),
+    // os.exit(code): not catchable, but `finally` blocks still run on the way out.
+    Exit(i32),
+}
+
+pub type EvalResult<T> = Result<T, Control>;
+
+// Errors raised by the runtime are plain objects so scripts can inspect them
+// the same way as values they `throw` themselves.
+pub fn error_value(kind: &str, message: impl Into<String>) -> Value {
+    let mut object = Object::new();
+    object.set("type", Value::String(kind.to_string()));
+    object.set("message", Value::String(message.into()));
+    Value::object(object)
+}
+
+pub fn throw<T>(kind: &str, message: impl Into<String>) -> EvalResult<T> {
+    Err(Control::Throw(error_value(kind, message)))
+}